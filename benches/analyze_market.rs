@@ -0,0 +1,38 @@
+use binance_ws::{db::Database, App};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde_json::{json, Value};
+
+const LEVELS: usize = 1000;
+
+// Synthetic BTCUSDT book with a mix of round and irregular prices and sizes
+fn synthetic_book(levels: usize) -> Value {
+    let level = |i: usize, price: f64| {
+        let quantity = if i.is_multiple_of(7) {
+            format!("{:.2}", (i % 20) as f64)
+        } else {
+            format!("{:.5}", 0.01 + (i as f64 * 0.01731) % 3.0)
+        };
+        json!([format!("{:.2}", price), quantity])
+    };
+
+    let bids: Vec<Value> = (0..levels)
+        .map(|i| level(i, 50000.0 - i as f64 * 0.37))
+        .collect();
+    let asks: Vec<Value> = (0..levels)
+        .map(|i| level(i, 50000.5 + i as f64 * 0.41))
+        .collect();
+
+    json!({ "symbol": "BTCUSDT", "bids": bids, "asks": asks })
+}
+
+fn bench_analyze_market(c: &mut Criterion) {
+    let mut app = App::with_database(Database::open_in_memory().expect("in-memory database"));
+    app.update_orders(&synthetic_book(LEVELS));
+
+    c.bench_function("analyze_market_1000_levels", |b| {
+        b.iter(|| black_box(app.analyze_market()))
+    });
+}
+
+criterion_group!(benches, bench_analyze_market);
+criterion_main!(benches);
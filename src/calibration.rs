@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use tracing::info;
+
+pub const DEFAULT_WARMUP_SAMPLES: usize = 2000;
+
+// Learns a per-symbol cutoff from the symbol's own score distribution: after
+// `warmup_samples` level scores the threshold is fixed at `percentile`, so e.g.
+// 80 labels roughly the top 20% of that symbol's levels as human.
+#[derive(Debug, Clone)]
+pub struct Calibration {
+    pub percentile: f64,
+    pub warmup_samples: usize,
+    samples: HashMap<String, Vec<f64>>,
+    thresholds: HashMap<String, f64>,
+}
+
+impl Calibration {
+    pub fn new(percentile: f64, warmup_samples: usize) -> Self {
+        Calibration {
+            percentile: percentile.clamp(0.0, 100.0),
+            warmup_samples: warmup_samples.max(1),
+            samples: HashMap::new(),
+            thresholds: HashMap::new(),
+        }
+    }
+
+    pub fn observe<I: IntoIterator<Item = f64>>(&mut self, symbol: &str, scores: I) {
+        if self.thresholds.contains_key(symbol) {
+            return;
+        }
+        let samples = self.samples.entry(symbol.to_string()).or_default();
+        samples.extend(scores);
+        if samples.len() < self.warmup_samples {
+            return;
+        }
+
+        let mut sorted = self.samples.remove(symbol).unwrap_or_default();
+        sorted.sort_by(f64::total_cmp);
+        let threshold = cutoff(&sorted, self.percentile);
+        info!(
+            symbol,
+            threshold,
+            percentile = self.percentile,
+            "Calibrated human threshold"
+        );
+        self.thresholds.insert(symbol.to_string(), threshold);
+    }
+
+    pub fn threshold(&self, symbol: &str) -> Option<f64> {
+        self.thresholds.get(symbol).copied()
+    }
+
+    // Fraction of the warm-up collected, 1.0 once calibrated
+    pub fn progress(&self, symbol: &str) -> f64 {
+        if self.thresholds.contains_key(symbol) {
+            return 1.0;
+        }
+        let collected = self.samples.get(symbol).map_or(0, Vec::len);
+        (collected as f64 / self.warmup_samples as f64).min(1.0)
+    }
+
+    pub fn thresholds(&self) -> &HashMap<String, f64> {
+        &self.thresholds
+    }
+
+    pub fn reset(&mut self) {
+        self.samples.clear();
+        self.thresholds.clear();
+    }
+}
+
+// Levels score as human strictly above the threshold, so with ties at the percentile
+// value (scores are often exactly 1.0) it can't simply be that value: it would label
+// nothing. Takes that value or the next distinct one below, whichever leaves closer to
+// (100 - percentile)% of the samples above it; the lower one on a tie.
+fn cutoff(sorted: &[f64], percentile: f64) -> f64 {
+    let index = ((percentile / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    let value = sorted[index];
+    let target = (100.0 - percentile) / 100.0 * sorted.len() as f64;
+    let above = |cutoff: f64| sorted.len() - sorted.partition_point(|&s| s <= cutoff);
+    let lower = sorted[..sorted.partition_point(|&s| s < value)].last();
+    match lower {
+        Some(&lower)
+            if (above(lower) as f64 - target).abs() <= (above(value) as f64 - target).abs() =>
+        {
+            lower
+        }
+        _ => value,
+    }
+}
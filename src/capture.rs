@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_IMBALANCE_SWING: f64 = 0.5;
+pub const CAPTURE_MESSAGES: usize = 200; // message history entries per bundle
+pub const CAPTURE_ANALYSIS_ROWS: usize = 20;
+
+// Conditions that save a context bundle; see App::capture_events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CaptureTrigger {
+    HumanSpike,     // likely-human orders at the human alert threshold
+    ImbalanceSwing, // imbalance moved at least `imbalance_swing` between two windows
+    CrossedBook,    // best bid at or above best ask
+}
+
+impl CaptureTrigger {
+    pub const ALL: [CaptureTrigger; 3] = [
+        CaptureTrigger::HumanSpike,
+        CaptureTrigger::ImbalanceSwing,
+        CaptureTrigger::CrossedBook,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CaptureTrigger::HumanSpike => "human-spike",
+            CaptureTrigger::ImbalanceSwing => "imbalance-swing",
+            CaptureTrigger::CrossedBook => "crossed-book",
+        }
+    }
+}
+
+impl std::str::FromStr for CaptureTrigger {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        CaptureTrigger::ALL
+            .into_iter()
+            .find(|trigger| trigger.name().eq_ignore_ascii_case(value))
+            .ok_or_else(|| {
+                format!(
+                    "expected human-spike, imbalance-swing or crossed-book, got {:?}",
+                    value
+                )
+            })
+    }
+}
+
+// Where bundles go and which conditions write them. Each (symbol, trigger) pair is
+// debounced by the human alert's cooldown so a lasting condition saves one bundle.
+#[derive(Debug, Clone)]
+pub struct EventCapture {
+    pub dir: PathBuf,
+    pub triggers: Vec<CaptureTrigger>,
+    pub imbalance_swing: f64,
+    last_fired: HashMap<(String, CaptureTrigger), Instant>,
+}
+
+impl EventCapture {
+    pub fn new(dir: PathBuf) -> Self {
+        EventCapture {
+            dir,
+            triggers: CaptureTrigger::ALL.to_vec(),
+            imbalance_swing: DEFAULT_IMBALANCE_SWING,
+            last_fired: HashMap::new(),
+        }
+    }
+
+    // True when `trigger` may fire for `symbol` now; firing starts its cooldown
+    pub fn ready(
+        &mut self,
+        symbol: &str,
+        trigger: CaptureTrigger,
+        cooldown: Duration,
+        now: Instant,
+    ) -> bool {
+        let key = (symbol.to_string(), trigger);
+        if self
+            .last_fired
+            .get(&key)
+            .is_some_and(|&at| now.duration_since(at) < cooldown)
+        {
+            return false;
+        }
+        self.last_fired.insert(key, now);
+        true
+    }
+
+    pub fn bundle_path(&self, symbol: &str, trigger: CaptureTrigger, ts_ms: u64) -> PathBuf {
+        self.dir
+            .join(format!("{}-{}-{}.json", symbol, trigger.name(), ts_ms))
+    }
+}
@@ -0,0 +1,910 @@
+use binance_ws::{
+    calibration::{Calibration, DEFAULT_WARMUP_SAMPLES},
+    capture::{CaptureTrigger, DEFAULT_IMBALANCE_SWING},
+    config::Config,
+    db::{SnapshotPolicy, WriteGate},
+    exchange::UpdateSpeed,
+    file_store::StoreBackend,
+    marker_width,
+    symbols::{self, SymbolAliases, SymbolList},
+    AppError, ConfidenceCutoffs, DuplicatePolicy, MarkerSet, NotionalFilter, PriceBand,
+    RoundNotional, TapeColumn, DEFAULT_ALERT_COOLDOWN, DEFAULT_BURST_MULTIPLIER,
+    DEFAULT_DISPLAY_LEVELS, DEFAULT_FILL_WINDOW_BPS, DEFAULT_HUMAN_THRESHOLD,
+    DEFAULT_IMBALANCE_WINDOWS, DEFAULT_MAX_INDICATORS, DEFAULT_MOVERS_WINDOWS, DEFAULT_NEAR_LEVELS,
+    SYMBOLS,
+};
+use clap::{parser::ValueSource, CommandFactory, FromArgMatches, Parser, Subcommand};
+use std::io::IsTerminal;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
+
+pub const MAINNET_REST_URL: &str = "https://api.binance.com";
+pub const MAINNET_WS_URL: &str = "wss://stream.binance.com:9443";
+pub const TESTNET_REST_URL: &str = "https://testnet.binance.vision";
+pub const TESTNET_WS_URL: &str = "wss://stream.testnet.binance.vision";
+pub const FUTURES_WS_URL: &str = "wss://fstream.binance.com";
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "binance-ws",
+    about = "Live Binance order book human/bot analysis"
+)]
+pub struct Cli {
+    /// TOML file with defaults for any of these options (see `config generate`)
+    #[arg(long, value_name = "PATH", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// JSON file of known bot quantity fingerprints
+    #[arg(long, value_name = "PATH", global = true)]
+    pub fingerprints: Option<PathBuf>,
+
+    /// File that receives diagnostic logs (verbosity via RUST_LOG)
+    #[arg(long, default_value = "binance-ws.log", global = true)]
+    pub log_file: PathBuf,
+
+    /// Score above which a level is classified as human
+    #[arg(long, default_value_t = DEFAULT_HUMAN_THRESHOLD, global = true)]
+    pub human_threshold: f64,
+
+    /// Quote-currency units whose 1-10x multiples (scaled by powers of ten) count as a
+    /// round, human-looking notional; implies --round-notional
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub round_notional_units: Vec<f64>,
+
+    /// Raise the human score of levels with a round notional
+    #[arg(long, global = true)]
+    pub round_notional: bool,
+
+    /// Score above which a book level is shown as likely human
+    #[arg(long, default_value_t = ConfidenceCutoffs::default().human, global = true)]
+    pub human_cutoff: f64,
+
+    /// Score below which a book level is shown as likely bot; between the cutoffs is uncertain
+    #[arg(long, default_value_t = ConfidenceCutoffs::default().bot, global = true)]
+    pub bot_cutoff: f64,
+
+    /// Comma-separated symbols to track, e.g. btcusdt,ethusdt
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub symbols: Vec<String>,
+
+    /// Run the full heuristics only for these symbols (comma-separated); others get book stats only
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub analyze: Vec<String>,
+
+    /// Symbols listed first in the overview and symbol cycling, in this order (comma-separated)
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub pin: Vec<String>,
+
+    /// Symbols left out of the overview but still tracked and stored (comma-separated)
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub hide: Vec<String>,
+
+    /// File with one symbol per line ('#' comments allowed), merged with --symbols
+    #[arg(long, value_name = "PATH", global = true)]
+    pub symbols_file: Option<PathBuf>,
+
+    /// How far from the best price (in basis points) a quantity drop still counts as a fill
+    /// on the inferred tape; deeper drops are treated as cancels
+    #[arg(long, default_value_t = DEFAULT_FILL_WINDOW_BPS, global = true)]
+    pub fill_window_bps: f64,
+
+    /// Also subscribe to <symbol>@aggTrade for a real time & sales panel (doubles the streams)
+    #[arg(long, global = true)]
+    pub trades: bool,
+
+    /// Also subscribe to the futures !forceOrder stream and mark levels near recent liquidations
+    #[arg(long, global = true)]
+    pub liquidations: bool,
+
+    /// Base URL for the futures WebSocket carrying the liquidation stream
+    #[arg(long, value_parser = parse_wss_url, global = true)]
+    pub liquidations_url: Option<Url>,
+
+    /// Quote asset stripped from displayed symbols, e.g. USDT shows BTCUSDT as BTC
+    #[arg(long, value_name = "ASSET", global = true)]
+    pub strip_quote: Option<String>,
+
+    /// Display name for a symbol, e.g. BTCUSDT=BTC (repeatable, wins over --strip-quote)
+    #[arg(long, value_name = "SYMBOL=NAME", value_parser = parse_symbol_alias, global = true)]
+    pub alias: Vec<(String, String)>,
+
+    /// Render without colors or text styling (also enabled by NO_COLOR or a non-tty stdout)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Book panel marker for human-classified levels (1-2 columns wide)
+    #[arg(long, value_parser = parse_marker, global = true)]
+    pub human_marker: Option<String>,
+
+    /// Book panel marker for bot-classified levels (1-2 columns wide)
+    #[arg(long, value_parser = parse_marker, global = true)]
+    pub bot_marker: Option<String>,
+
+    /// Book panel marker for levels between the bot and human cutoffs (1-2 columns wide)
+    #[arg(long, value_parser = parse_marker, global = true)]
+    pub uncertain_marker: Option<String>,
+
+    /// Book panel marker for bid rows (1-2 columns wide)
+    #[arg(long, value_parser = parse_marker, global = true)]
+    pub bid_marker: Option<String>,
+
+    /// Book panel marker for ask rows (1-2 columns wide)
+    #[arg(long, value_parser = parse_marker, global = true)]
+    pub ask_marker: Option<String>,
+
+    /// Leave the mouse to the terminal (text selection) instead of click/scroll handling
+    #[arg(long, global = true)]
+    pub no_mouse: bool,
+
+    /// Save the message history here periodically and on exit, and reload it on startup
+    #[arg(long, value_name = "PATH", global = true)]
+    pub history_file: Option<PathBuf>,
+
+    /// Record the live feed as JSONL (plus a .idx seek index) to this file
+    #[arg(long, value_name = "PATH", global = true)]
+    pub record: Option<PathBuf>,
+
+    /// Calibrate each symbol's threshold at this percentile of its own scores (e.g. 80)
+    #[arg(long, value_name = "PERCENTILE", global = true)]
+    pub calibrate_percentile: Option<f64>,
+
+    /// Level scores collected per symbol before its calibrated threshold is fixed
+    #[arg(long, default_value_t = DEFAULT_WARMUP_SAMPLES, global = true)]
+    pub calibration_samples: usize,
+
+    /// Tape panel columns as field:width, e.g. time:5,side:4,price:10
+    /// (fields: time, symbol, type, side, price, quantity, total)
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub tape_columns: Vec<TapeColumn>,
+
+    /// How to merge levels repeated at the same price in one payload: keep-last or sum
+    #[arg(long, default_value = "keep-last", global = true)]
+    pub duplicate_levels: DuplicatePolicy,
+
+    /// Log warnings for book integrity problems such as duplicate price levels
+    #[arg(long, global = true)]
+    pub validate_book: bool,
+
+    /// Store a full book snapshot per symbol at most every N seconds
+    #[arg(long, global = true, conflicts_with = "snapshot_on_change_pct")]
+    pub snapshot_every_secs: Option<u64>,
+
+    /// Store a full book snapshot when the best bid or ask moves by at least this many percent
+    #[arg(long, global = true)]
+    pub snapshot_on_change_pct: Option<f64>,
+
+    /// Seconds without a main-loop iteration before the watchdog reports a stall
+    #[arg(long, default_value_t = 5, global = true)]
+    pub stall_threshold_secs: u64,
+
+    /// Flag a quote-stuffing burst when a book's update rate exceeds its baseline by this factor
+    #[arg(long, default_value_t = DEFAULT_BURST_MULTIPLIER, global = true)]
+    pub burst_multiplier: f64,
+
+    /// Ring the bell and flash the title when the current symbol has at least this many
+    /// likely-human orders; 'b' toggles the alert while running
+    #[arg(long, global = true)]
+    pub alert_human_orders: Option<usize>,
+
+    /// Minimum seconds between two human-activity alerts
+    #[arg(long, default_value_t = DEFAULT_ALERT_COOLDOWN.as_secs(), global = true)]
+    pub alert_cooldown_secs: u64,
+
+    /// Save a JSON bundle of book, recent messages and analysis here when a capture trigger fires
+    #[arg(long, value_name = "DIR", global = true)]
+    pub capture_dir: Option<PathBuf>,
+
+    /// Capture triggers: human-spike (at --alert-human-orders), imbalance-swing, crossed-book
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "human-spike,imbalance-swing,crossed-book",
+        global = true
+    )]
+    pub capture_on: Vec<CaptureTrigger>,
+
+    /// Imbalance change between two windows that counts as a swing for capture
+    #[arg(long, default_value_t = DEFAULT_IMBALANCE_SWING, global = true)]
+    pub capture_imbalance_swing: f64,
+
+    /// Hide book levels whose notional (price * quantity) is below this value
+    #[arg(long, default_value_t = 0.0, global = true)]
+    pub min_notional: f64,
+
+    /// Per-symbol minimum notional override, e.g. BTCUSDT=1000 (repeatable)
+    #[arg(long, value_name = "SYMBOL=NOTIONAL", value_parser = parse_symbol_value, global = true)]
+    pub min_notional_for: Vec<(String, f64)>,
+
+    /// Only analyze levels within this percentage of mid, e.g. 0.5 for +/-0.5%
+    #[arg(long, value_name = "PCT", global = true)]
+    pub analysis_band_pct: Option<f64>,
+
+    /// Per-symbol analysis band override, e.g. BTCUSDT=0.2 (repeatable)
+    #[arg(long, value_name = "SYMBOL=PCT", value_parser = parse_symbol_value, global = true)]
+    pub analysis_band_pct_for: Vec<(String, f64)>,
+
+    /// Only store an analysis row when the human ratio moved more than this since the last row
+    #[arg(long, value_name = "DELTA", global = true)]
+    pub write_min_ratio_delta: Option<f64>,
+
+    /// Only store an analysis row when total orders moved more than this since the last row
+    #[arg(long, value_name = "DELTA", global = true)]
+    pub write_min_orders_delta: Option<i64>,
+
+    /// With a write delta set, still store a row at least this often
+    #[arg(long, default_value_t = 10, global = true)]
+    pub heartbeat_minutes: u64,
+
+    /// Analyze and display but store nothing for this long after startup, e.g. 10s
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, global = true)]
+    pub warmup: Option<Duration>,
+
+    /// Where analysis records are stored: sqlite, or csv for per-day files under --data-dir
+    #[arg(long, default_value = "sqlite", global = true)]
+    pub store_backend: StoreBackend,
+
+    /// Root directory for --store-backend csv, laid out as <dir>/<SYMBOL>/<YYYY-MM-DD>.csv
+    #[arg(long, default_value = "data", global = true)]
+    pub data_dir: PathBuf,
+
+    /// Also append every stored analysis row to <DIR>/<SYMBOL>.csv, with spread and imbalance
+    #[arg(long, value_name = "DIR", global = true)]
+    pub csv_dir: Option<PathBuf>,
+
+    /// Append every level's raw heuristic outputs and score to this CSV on each analysis
+    #[arg(long, value_name = "PATH", global = true)]
+    pub dump_signals: Option<PathBuf>,
+
+    /// Minutes between WAL checkpoints of the database while the dashboard runs
+    #[arg(long, default_value_t = 10, global = true)]
+    pub checkpoint_minutes: u64,
+
+    /// How long the latest stored analysis is served from memory before re-querying
+    #[arg(long, default_value_t = 1000, global = true)]
+    pub latest_cache_ms: u64,
+
+    /// Advance to the next symbol on this interval, e.g. 10s or 2m ('r' pauses, '<' '>' adjust)
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, global = true)]
+    pub rotate: Option<Duration>,
+
+    /// Most recent distinct indicators (iceberg, fingerprint, ...) kept per book level
+    #[arg(long, default_value_t = DEFAULT_MAX_INDICATORS, global = true)]
+    pub max_indicators: usize,
+
+    /// Book levels shown per side (the full fetched depth is still analyzed)
+    #[arg(long, default_value_t = DEFAULT_DISPLAY_LEVELS, global = true)]
+    pub display_levels: usize,
+
+    /// Number of 5 second analysis windows compared in the top movers panel
+    #[arg(long, default_value_t = DEFAULT_MOVERS_WINDOWS, global = true)]
+    pub movers_windows: usize,
+
+    /// Re-run the analysis every frame instead of only for symbols that received updates
+    #[arg(long, global = true)]
+    pub analyze_every_frame: bool,
+
+    /// Hold the displayed analysis numbers for this long; the book and stored analysis
+    /// still update at the feed rate (0 refreshes every frame)
+    #[arg(long, default_value_t = 0, global = true)]
+    pub analysis_refresh_ms: u64,
+
+    /// Number of 5 second windows shown in the book imbalance chart
+    #[arg(long, default_value_t = DEFAULT_IMBALANCE_WINDOWS, global = true)]
+    pub imbalance_windows: usize,
+
+    /// Levels per side counted as near the touch in the book pressure split
+    #[arg(long, default_value_t = DEFAULT_NEAR_LEVELS, global = true)]
+    pub near_levels: usize,
+
+    /// Serve OpenMetrics (human ratio histogram, latency summary) on this address
+    #[arg(long, value_name = "ADDR", global = true)]
+    pub metrics_addr: Option<SocketAddr>,
+
+    /// Push the metrics to this Prometheus Pushgateway, e.g. http://localhost:9091
+    #[arg(long, value_name = "URL", global = true)]
+    pub pushgateway_url: Option<Url>,
+
+    /// Seconds between Pushgateway pushes
+    #[arg(long, default_value_t = 15, global = true)]
+    pub push_interval_secs: u64,
+
+    /// Comma-separated hex colors for the human ratio gradient, bot-heavy first
+    #[arg(
+        long,
+        value_name = "COLORS",
+        value_delimiter = ',',
+        value_parser = parse_hex_color,
+        global = true
+    )]
+    pub ratio_palette: Vec<(u8, u8, u8)>,
+
+    /// Base URL for the Binance REST API
+    #[arg(long, env = "BINANCE_REST_URL", value_parser = parse_https_url, global = true)]
+    pub rest_url: Option<Url>,
+
+    /// Base URL for the Binance WebSocket streams
+    #[arg(long, env = "BINANCE_WS_URL", value_parser = parse_wss_url, global = true)]
+    pub ws_url: Option<Url>,
+
+    /// Depth stream update speed: 100ms or 1000ms
+    #[arg(long, default_value = "100ms", global = true)]
+    pub update_speed: UpdateSpeed,
+
+    /// Seconds without updates after which a book is greyed out and left out of stored
+    /// analysis and the market-wide figures
+    #[arg(long, default_value_t = 10, global = true)]
+    pub stale_after_secs: u64,
+
+    /// Initial book snapshots fetched at once on (re)connect; lower it to ease REST rate limits
+    #[arg(long, default_value_t = 5, global = true)]
+    pub snapshot_concurrency: usize,
+
+    /// Give up after this many consecutive failed connection attempts (unlimited if unset)
+    #[arg(long, global = true)]
+    pub max_reconnect_attempts: Option<u32>,
+
+    /// Upper bound on the exponential reconnect backoff
+    #[arg(long, default_value_t = 300, global = true)]
+    pub max_reconnect_delay_secs: u64,
+
+    /// Use the Binance spot testnet for any endpoint not set explicitly
+    #[arg(long, global = true)]
+    pub testnet: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+// Copies each config value whose flag was not given explicitly
+macro_rules! from_config {
+    ($cli:ident, $config:ident, $explicit:ident; $($field:ident),* $(,)?) => {
+        $(
+            if let Some(value) = $config.$field.take() {
+                if !$explicit(stringify!($field)) {
+                    $cli.$field = value.into();
+                }
+            }
+        )*
+    };
+}
+
+impl Cli {
+    // Parses the command line, then fills in anything it left unset from --config
+    pub fn load() -> Result<Cli, AppError> {
+        let matches = Cli::command().get_matches();
+        let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        if let Some(path) = cli.config.clone() {
+            let config = Config::load(&path)?;
+            let explicit = |id: &str| {
+                matches!(
+                    matches.value_source(id),
+                    Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+                )
+            };
+            cli.apply_config(config, explicit)?;
+        }
+        Ok(cli)
+    }
+
+    fn apply_config(
+        &mut self,
+        mut config: Config,
+        explicit: impl Fn(&str) -> bool,
+    ) -> Result<(), AppError> {
+        from_config!(self, config, explicit;
+            fingerprints, log_file, human_threshold, round_notional_units, round_notional,
+            human_cutoff, bot_cutoff,
+            symbols, analyze, pin, hide, symbols_file,
+            fill_window_bps, trades, liquidations, strip_quote, no_color, no_mouse,
+            history_file, record,
+            calibrate_percentile, calibration_samples, validate_book,
+            snapshot_every_secs, snapshot_on_change_pct, stall_threshold_secs, burst_multiplier,
+            alert_human_orders, alert_cooldown_secs, capture_dir, capture_imbalance_swing,
+            min_notional, analysis_band_pct,
+            write_min_ratio_delta, write_min_orders_delta, heartbeat_minutes,
+            data_dir, csv_dir, dump_signals, checkpoint_minutes, latest_cache_ms,
+            max_indicators, display_levels, movers_windows,
+            analyze_every_frame, analysis_refresh_ms, imbalance_windows, near_levels,
+            metrics_addr, push_interval_secs,
+            stale_after_secs, snapshot_concurrency,
+            max_reconnect_attempts, max_reconnect_delay_secs, testnet,
+        );
+
+        // The rest go through the same validation as their flags
+        let invalid = |key: &str, e: String| AppError::Config(format!("{}: {}", key, e));
+        for (key, value, target) in [
+            ("human-marker", config.human_marker, &mut self.human_marker),
+            ("bot-marker", config.bot_marker, &mut self.bot_marker),
+            (
+                "uncertain-marker",
+                config.uncertain_marker,
+                &mut self.uncertain_marker,
+            ),
+            ("bid-marker", config.bid_marker, &mut self.bid_marker),
+            ("ask-marker", config.ask_marker, &mut self.ask_marker),
+        ] {
+            if let Some(value) = value {
+                if !explicit(&key.replace('-', "_")) {
+                    *target = Some(parse_marker(&value).map_err(|e| invalid(key, e))?);
+                }
+            }
+        }
+        if let Some(value) = config.warmup {
+            if !explicit("warmup") {
+                self.warmup = Some(parse_duration(&value).map_err(|e| invalid("warmup", e))?);
+            }
+        }
+        if let Some(value) = config.rotate {
+            if !explicit("rotate") {
+                self.rotate = Some(parse_duration(&value).map_err(|e| invalid("rotate", e))?);
+            }
+        }
+        if let Some(value) = config.store_backend {
+            if !explicit("store_backend") {
+                self.store_backend = value.parse().map_err(|e| invalid("store-backend", e))?;
+            }
+        }
+        if let Some(value) = config.update_speed {
+            if !explicit("update_speed") {
+                self.update_speed = value.parse().map_err(|e| invalid("update-speed", e))?;
+            }
+        }
+        if let Some(value) = config.duplicate_levels {
+            if !explicit("duplicate_levels") {
+                self.duplicate_levels =
+                    value.parse().map_err(|e| invalid("duplicate-levels", e))?;
+            }
+        }
+        if let Some(triggers) = config.capture_on {
+            if !explicit("capture_on") {
+                self.capture_on = triggers
+                    .iter()
+                    .map(|t| t.parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| invalid("capture-on", e))?;
+            }
+        }
+        if let Some(columns) = config.tape_columns {
+            if !explicit("tape_columns") {
+                self.tape_columns = columns
+                    .iter()
+                    .map(|c| c.parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| invalid("tape-columns", e))?;
+            }
+        }
+        if let Some(colors) = config.ratio_palette {
+            if !explicit("ratio_palette") {
+                self.ratio_palette = colors
+                    .iter()
+                    .map(|c| parse_hex_color(c))
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| invalid("ratio-palette", e))?;
+            }
+        }
+        if let Some(url) = config.rest_url {
+            if !explicit("rest_url") {
+                self.rest_url = Some(parse_https_url(&url).map_err(|e| invalid("rest-url", e))?);
+            }
+        }
+        if let Some(url) = config.ws_url {
+            if !explicit("ws_url") {
+                self.ws_url = Some(parse_wss_url(&url).map_err(|e| invalid("ws-url", e))?);
+            }
+        }
+        if let Some(url) = config.liquidations_url {
+            if !explicit("liquidations_url") {
+                self.liquidations_url =
+                    Some(parse_wss_url(&url).map_err(|e| invalid("liquidations-url", e))?);
+            }
+        }
+        if let Some(url) = config.pushgateway_url {
+            if !explicit("pushgateway_url") {
+                let url =
+                    Url::parse(&url).map_err(|e| invalid("pushgateway-url", e.to_string()))?;
+                self.pushgateway_url = Some(url);
+            }
+        }
+        if let Some(aliases) = config.alias {
+            if !explicit("alias") {
+                let mut aliases: Vec<(String, String)> = aliases
+                    .into_iter()
+                    .map(|(symbol, name)| (symbol.to_uppercase(), name))
+                    .collect();
+                aliases.sort();
+                self.alias = aliases;
+            }
+        }
+        if let Some(overrides) = config.min_notional_for {
+            if !explicit("min_notional_for") {
+                let mut overrides: Vec<(String, f64)> = overrides
+                    .into_iter()
+                    .map(|(symbol, notional)| (symbol.to_uppercase(), notional))
+                    .collect();
+                overrides.sort_by(|a, b| a.0.cmp(&b.0));
+                self.min_notional_for = overrides;
+            }
+        }
+        if let Some(overrides) = config.analysis_band_pct_for {
+            if !explicit("analysis_band_pct_for") {
+                let mut overrides: Vec<(String, f64)> = overrides
+                    .into_iter()
+                    .map(|(symbol, pct)| (symbol.to_uppercase(), pct))
+                    .collect();
+                overrides.sort_by(|a, b| a.0.cmp(&b.0));
+                self.analysis_band_pct_for = overrides;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn notional_filter(&self) -> NotionalFilter {
+        NotionalFilter {
+            min_notional: self.min_notional,
+            overrides: self.min_notional_for.iter().cloned().collect(),
+        }
+    }
+
+    pub fn price_band(&self) -> PriceBand {
+        PriceBand {
+            pct: self.analysis_band_pct,
+            overrides: self.analysis_band_pct_for.iter().cloned().collect(),
+        }
+    }
+
+    // Gating is off unless one of the deltas is given; an unset delta never triggers a write
+    pub fn write_gate(&self) -> Option<WriteGate> {
+        if self.write_min_ratio_delta.is_none() && self.write_min_orders_delta.is_none() {
+            return None;
+        }
+        Some(WriteGate {
+            min_ratio_delta: self.write_min_ratio_delta.unwrap_or(f64::INFINITY),
+            min_orders_delta: self.write_min_orders_delta.unwrap_or(i64::MAX),
+            heartbeat: Duration::from_secs(self.heartbeat_minutes * 60),
+        })
+    }
+
+    pub fn plain_output(&self) -> bool {
+        self.no_color
+            || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+            || !std::io::stdout().is_terminal()
+    }
+
+    pub fn markers(&self) -> MarkerSet {
+        let defaults = MarkerSet::default();
+        MarkerSet {
+            human: self.human_marker.clone().unwrap_or(defaults.human),
+            bot: self.bot_marker.clone().unwrap_or(defaults.bot),
+            uncertain: self.uncertain_marker.clone().unwrap_or(defaults.uncertain),
+            bid: self.bid_marker.clone().unwrap_or(defaults.bid),
+            ask: self.ask_marker.clone().unwrap_or(defaults.ask),
+        }
+    }
+
+    pub fn round_notional(&self) -> Option<RoundNotional> {
+        if !self.round_notional && self.round_notional_units.is_empty() {
+            return None;
+        }
+        let mut round_notional = RoundNotional::default();
+        if !self.round_notional_units.is_empty() {
+            round_notional.units = self.round_notional_units.clone();
+        }
+        Some(round_notional)
+    }
+
+    pub fn snapshot_policy(&self) -> Option<SnapshotPolicy> {
+        match (self.snapshot_every_secs, self.snapshot_on_change_pct) {
+            (Some(secs), _) => Some(SnapshotPolicy::Interval(Duration::from_secs(secs))),
+            (None, Some(percent)) => Some(SnapshotPolicy::OnChange(percent)),
+            (None, None) => None,
+        }
+    }
+
+    pub fn calibration(&self) -> Option<Calibration> {
+        self.calibrate_percentile
+            .map(|percentile| Calibration::new(percentile, self.calibration_samples))
+    }
+
+    pub fn aliases(&self) -> SymbolAliases {
+        SymbolAliases {
+            quote_suffix: self.strip_quote.as_ref().map(|s| s.to_uppercase()),
+            overrides: self.alias.iter().cloned().collect(),
+        }
+    }
+
+    // Falls back to the built-in symbols when neither --symbols nor --symbols-file is given
+    pub fn symbol_list(&self) -> Result<SymbolList, AppError> {
+        let mut raw = self.symbols.clone();
+        if let Some(path) = &self.symbols_file {
+            raw.extend(symbols::load_symbols_file(path)?);
+        }
+        let mut list = symbols::normalize(raw);
+        if list.symbols.is_empty() {
+            list.symbols = SYMBOLS.iter().map(|s| s.to_string()).collect();
+        }
+        Ok(list)
+    }
+
+    pub fn rest_url(&self) -> Url {
+        self.rest_url.clone().unwrap_or_else(|| {
+            let default = if self.testnet {
+                TESTNET_REST_URL
+            } else {
+                MAINNET_REST_URL
+            };
+            Url::parse(default).expect("default REST URL is valid")
+        })
+    }
+
+    // The liquidation stream is only published on mainnet futures
+    pub fn liquidations_url(&self) -> Option<Url> {
+        self.liquidations.then(|| {
+            self.liquidations_url.clone().unwrap_or_else(|| {
+                Url::parse(FUTURES_WS_URL).expect("default futures WebSocket URL is valid")
+            })
+        })
+    }
+
+    pub fn ws_url(&self) -> Url {
+        self.ws_url.clone().unwrap_or_else(|| {
+            let default = if self.testnet {
+                TESTNET_WS_URL
+            } else {
+                MAINNET_WS_URL
+            };
+            Url::parse(default).expect("default WebSocket URL is valid")
+        })
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print a single updating summary line instead of the full dashboard
+    Line {
+        /// Symbol to summarize, e.g. btcusdt
+        #[arg(long, default_value = "btcusdt")]
+        symbol: String,
+
+        /// Milliseconds between printed updates
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+    },
+    /// Score the same live feed with two classifier configs and log both ratios
+    Compare {
+        /// Human threshold for config B (config A uses --human-threshold)
+        #[arg(long)]
+        threshold_b: Option<f64>,
+
+        /// Fingerprint file for config B (config A uses --fingerprints)
+        #[arg(long, value_name = "PATH")]
+        fingerprints_b: Option<PathBuf>,
+
+        /// CSV file that receives one row per symbol per interval
+        #[arg(long, default_value = "compare.csv")]
+        csv: PathBuf,
+
+        /// Milliseconds between comparisons
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+    },
+    /// Fetch one REST snapshot, analyze it, print book + analysis as JSON and exit
+    Snapshot {
+        /// Symbol to fetch, e.g. btcusdt
+        #[arg(long, default_value = "btcusdt")]
+        symbol: String,
+
+        /// Levels per side to request
+        #[arg(long, default_value_t = 10)]
+        depth: u32,
+
+        /// Also store the analysis in the database
+        #[arg(long)]
+        store: bool,
+    },
+    /// Write a static chart of the book to a file and exit
+    Export {
+        /// Cumulative bid/ask depth SVG, e.g. --depth-chart btcusdt depth.svg
+        #[arg(long, num_args = 2, value_names = ["SYMBOL", "OUT"], required = true)]
+        depth_chart: Vec<String>,
+
+        /// Levels per side when fetching the live book
+        #[arg(long, default_value_t = 100)]
+        depth: u32,
+
+        /// Read the book from a --record file instead of the live REST snapshot
+        #[arg(long, value_name = "PATH", requires = "at")]
+        recording: Option<PathBuf>,
+
+        /// Unix timestamp in milliseconds to read from the recording
+        #[arg(long)]
+        at: Option<u64>,
+    },
+    /// Print stored row counts per symbol and the database size
+    Stats,
+    /// Rebuild the database file to reclaim space left by deleted rows
+    Vacuum,
+    /// Re-run the current classifier over stored book snapshots and print one row per snapshot
+    Reanalyze {
+        /// Symbol whose snapshots to load, e.g. btcusdt
+        #[arg(long)]
+        symbol: String,
+
+        /// Unix timestamp in seconds to start from
+        #[arg(long)]
+        from: u64,
+
+        /// Unix timestamp in seconds to stop at (inclusive)
+        #[arg(long)]
+        to: u64,
+
+        /// Also write the rows to the market_reanalysis table
+        #[arg(long)]
+        store: bool,
+    },
+    /// List notes taken with 'm' in the dashboard, oldest first
+    Annotations {
+        /// Only notes for this symbol, e.g. btcusdt
+        #[arg(long)]
+        symbol: Option<String>,
+    },
+    /// Print stored analysis windows whose human ratio falls in a range, newest first
+    Find {
+        /// Symbol to search, e.g. btcusdt
+        #[arg(long)]
+        symbol: String,
+
+        /// Lowest human ratio to match, from 0.0 to 1.0
+        #[arg(long, default_value_t = 0.0)]
+        min_ratio: f64,
+
+        /// Highest human ratio to match (inclusive)
+        #[arg(long, default_value_t = 1.0)]
+        max_ratio: f64,
+
+        /// Unix timestamp in seconds to search from
+        #[arg(long, default_value_t = 0)]
+        from: u64,
+
+        /// Unix timestamp in seconds to search up to (inclusive); defaults to now
+        #[arg(long)]
+        to: Option<u64>,
+
+        /// Most rows to print
+        #[arg(long, default_value_t = 100)]
+        limit: i64,
+    },
+    /// Fill gaps in stored analysis by reanalyzing stored book snapshots
+    Backfill {
+        /// Symbol to backfill, e.g. btcusdt
+        #[arg(long)]
+        symbol: String,
+
+        /// Unix timestamp in seconds to start from
+        #[arg(long)]
+        from: u64,
+
+        /// Unix timestamp in seconds to stop at (inclusive)
+        #[arg(long)]
+        to: u64,
+
+        /// Length of the intervals checked for missing analysis
+        #[arg(long, default_value_t = 60)]
+        interval_secs: u64,
+    },
+    /// Work with --config files
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    /// Print the state of a recorded session at a point in time
+    Replay {
+        /// Recording written with --record
+        #[arg(long, value_name = "PATH")]
+        file: PathBuf,
+
+        /// Unix timestamp in milliseconds to seek to
+        #[arg(long)]
+        at: u64,
+
+        /// Play on from there in the dashboard instead of printing a summary
+        #[arg(long)]
+        play: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Write a commented config with every option at its default
+    Generate {
+        /// Destination file; prints to stdout when omitted
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+    },
+}
+
+fn parse_url_with_scheme(value: &str, scheme: &str) -> Result<Url, String> {
+    let url = Url::parse(value).map_err(|e| format!("invalid URL: {}", e))?;
+    if url.scheme() != scheme {
+        return Err(format!(
+            "expected a {}:// URL, got {}://",
+            scheme,
+            url.scheme()
+        ));
+    }
+    Ok(url)
+}
+
+fn parse_https_url(value: &str) -> Result<Url, String> {
+    parse_url_with_scheme(value, "https")
+}
+
+fn parse_wss_url(value: &str) -> Result<Url, String> {
+    parse_url_with_scheme(value, "wss")
+}
+
+// "500ms", "10s", "2m", or a bare number of seconds
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: f64 = amount
+        .parse()
+        .map_err(|_| format!("expected a duration like 10s, got {:?}", value))?;
+    let seconds = match unit {
+        "ms" => amount / 1000.0,
+        "" | "s" => amount,
+        "m" => amount * 60.0,
+        _ => {
+            return Err(format!(
+                "unknown unit {:?} in {:?}, use ms, s or m",
+                unit, value
+            ))
+        }
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|e| format!("invalid duration {:?}: {}", value, e))
+}
+
+fn parse_symbol_value(value: &str) -> Result<(String, f64), String> {
+    let (symbol, amount) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected SYMBOL=VALUE, got {}", value))?;
+    let amount = amount
+        .parse::<f64>()
+        .map_err(|e| format!("invalid value for {}: {}", symbol, e))?;
+    Ok((symbol.to_uppercase(), amount))
+}
+
+fn parse_symbol_alias(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((symbol, alias)) if !symbol.is_empty() && !alias.is_empty() => {
+            Ok((symbol.to_uppercase(), alias.to_string()))
+        }
+        _ => Err(format!("expected SYMBOL=NAME, got {}", value)),
+    }
+}
+
+fn parse_marker(value: &str) -> Result<String, String> {
+    match marker_width(value) {
+        1 | 2 => Ok(value.to_string()),
+        width => Err(format!(
+            "marker must be 1-2 columns wide, {:?} is {}",
+            value, width
+        )),
+    }
+}
+
+fn parse_hex_color(value: &str) -> Result<(u8, u8, u8), String> {
+    let hex = value.trim().trim_start_matches('#');
+    // Checked before slicing: a multi-byte character could put a slice off a char boundary
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("expected a #rrggbb color, got {}", value));
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| format!("invalid hex color: {}", value))
+    };
+    Ok((channel(0)?, channel(2)?, channel(4)?))
+}
@@ -0,0 +1,59 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// Source of monotonic and wall-clock time, so time-based logic can run against a fake clock
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+
+    fn unix_millis(&self) -> u64;
+
+    fn unix_secs(&self) -> u64 {
+        self.unix_millis() / 1000
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn unix_millis(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+    }
+}
+
+// Stands still until advanced; clones share the same time
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    state: Arc<Mutex<(Instant, u64)>>, // (monotonic now, unix ms)
+}
+
+impl MockClock {
+    pub fn new(unix_millis: u64) -> Self {
+        MockClock {
+            state: Arc::new(Mutex::new((Instant::now(), unix_millis))),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.0 += by;
+        state.1 += by.as_millis() as u64;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.state.lock().unwrap().0
+    }
+
+    fn unix_millis(&self) -> u64 {
+        self.state.lock().unwrap().1
+    }
+}
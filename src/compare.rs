@@ -0,0 +1,78 @@
+use binance_ws::{db::get_current_timestamp, fatal_error, App, AppError, MarketAnalysis};
+use serde_json::Value;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+pub async fn run(
+    mut app_a: App,
+    mut app_b: App,
+    mut rx: mpsc::Receiver<Value>,
+    csv_path: &Path,
+    interval: Duration,
+) -> Result<(), AppError> {
+    let write_header = !csv_path.exists();
+    let mut csv = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(csv_path)?;
+    if write_header {
+        writeln!(csv, "timestamp,symbol,ratio_a,ratio_b,delta")?;
+    }
+
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = ticker.tick() => {
+                // Identical input for both configs
+                while let Ok(result) = rx.try_recv() {
+                    app_a.update_orders(&result);
+                    if let Some(error) = fatal_error(&result) {
+                        eprintln!("\nFeed stopped: {}", error);
+                        return Ok(());
+                    }
+                    app_b.update_orders(&result);
+                }
+
+                let analyses_a = app_a.analyze_all_symbols();
+                let mut analyses_b = app_b.analyze_all_symbols();
+                let mut symbols: Vec<&String> = analyses_a.keys().collect();
+                symbols.sort();
+
+                let timestamp = get_current_timestamp();
+                for symbol in symbols {
+                    let (Some(ratio_a), Some(ratio_b)) = (
+                        human_ratio(&analyses_a[symbol]),
+                        analyses_b.remove(symbol).as_ref().and_then(human_ratio),
+                    ) else {
+                        continue;
+                    };
+                    let delta = ratio_b - ratio_a;
+
+                    writeln!(csv, "{},{},{:.4},{:.4},{:+.4}", timestamp, symbol, ratio_a, ratio_b, delta)?;
+                    println!(
+                        "{:<10} A {:>5.1}%  B {:>5.1}%  delta {:+.1}pp",
+                        symbol,
+                        ratio_a * 100.0,
+                        ratio_b * 100.0,
+                        delta * 100.0
+                    );
+                }
+                csv.flush()?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn human_ratio(analysis: &MarketAnalysis) -> Option<f64> {
+    if analysis.total_orders > 0 {
+        Some(analysis.likely_human_orders as f64 / analysis.total_orders as f64)
+    } else {
+        None
+    }
+}
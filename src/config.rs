@@ -0,0 +1,205 @@
+use crate::AppError;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+// Values from --config; keys match the long flag names. Anything set on the command
+// line (or through its environment variable) wins over the file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub fingerprints: Option<PathBuf>,
+    pub log_file: Option<PathBuf>,
+    pub human_threshold: Option<f64>,
+    pub round_notional_units: Option<Vec<f64>>,
+    pub round_notional: Option<bool>,
+    pub human_cutoff: Option<f64>,
+    pub bot_cutoff: Option<f64>,
+    pub symbols: Option<Vec<String>>,
+    pub analyze: Option<Vec<String>>,
+    pub pin: Option<Vec<String>>,
+    pub hide: Option<Vec<String>>,
+    pub symbols_file: Option<PathBuf>,
+    pub fill_window_bps: Option<f64>,
+    pub trades: Option<bool>,
+    pub liquidations: Option<bool>,
+    pub liquidations_url: Option<String>,
+    pub strip_quote: Option<String>,
+    pub alias: Option<HashMap<String, String>>, // symbol = display name
+    pub no_color: Option<bool>,
+    pub human_marker: Option<String>,
+    pub bot_marker: Option<String>,
+    pub uncertain_marker: Option<String>,
+    pub bid_marker: Option<String>,
+    pub ask_marker: Option<String>,
+    pub no_mouse: Option<bool>,
+    pub history_file: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub calibrate_percentile: Option<f64>,
+    pub calibration_samples: Option<usize>,
+    pub tape_columns: Option<Vec<String>>,
+    pub duplicate_levels: Option<String>,
+    pub validate_book: Option<bool>,
+    pub snapshot_every_secs: Option<u64>,
+    pub snapshot_on_change_pct: Option<f64>,
+    pub stall_threshold_secs: Option<u64>,
+    pub burst_multiplier: Option<f64>,
+    pub alert_human_orders: Option<usize>,
+    pub alert_cooldown_secs: Option<u64>,
+    pub capture_dir: Option<PathBuf>,
+    pub capture_on: Option<Vec<String>>,
+    pub capture_imbalance_swing: Option<f64>,
+    pub min_notional: Option<f64>,
+    pub min_notional_for: Option<HashMap<String, f64>>, // symbol = notional
+    pub analysis_band_pct: Option<f64>,
+    pub analysis_band_pct_for: Option<HashMap<String, f64>>, // symbol = percent
+    pub write_min_ratio_delta: Option<f64>,
+    pub write_min_orders_delta: Option<i64>,
+    pub heartbeat_minutes: Option<u64>,
+    pub warmup: Option<String>,
+    pub store_backend: Option<String>,
+    pub data_dir: Option<PathBuf>,
+    pub csv_dir: Option<PathBuf>,
+    pub dump_signals: Option<PathBuf>,
+    pub checkpoint_minutes: Option<u64>,
+    pub latest_cache_ms: Option<u64>,
+    pub rotate: Option<String>,
+    pub max_indicators: Option<usize>,
+    pub display_levels: Option<usize>,
+    pub movers_windows: Option<usize>,
+    pub imbalance_windows: Option<usize>,
+    pub near_levels: Option<usize>,
+    pub analyze_every_frame: Option<bool>,
+    pub analysis_refresh_ms: Option<u64>,
+    pub metrics_addr: Option<SocketAddr>,
+    pub pushgateway_url: Option<String>,
+    pub push_interval_secs: Option<u64>,
+    pub ratio_palette: Option<Vec<String>>,
+    pub rest_url: Option<String>,
+    pub ws_url: Option<String>,
+    pub update_speed: Option<String>,
+    pub stale_after_secs: Option<u64>,
+    pub snapshot_concurrency: Option<usize>,
+    pub max_reconnect_attempts: Option<u32>,
+    pub max_reconnect_delay_secs: Option<u64>,
+    pub testnet: Option<bool>,
+}
+
+impl Config {
+    pub fn parse(contents: &str) -> Result<Self, AppError> {
+        toml::from_str(contents).map_err(|e| AppError::Config(e.to_string()))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AppError> {
+        let path = path.as_ref();
+        toml::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| AppError::Config(format!("{}: {}", path.display(), e)))
+    }
+}
+
+// Written by `config generate`; every setting is commented out at its default
+pub const DEFAULT_CONFIG: &str = r##"# binance-ws configuration, loaded with --config <PATH>.
+# Keys are the long flag names; flags given on the command line override this file.
+# Values shown are the defaults, except those marked "example": those settings are
+# off or unset unless configured.
+
+# Symbols to track (defaults to btcusdt, ethusdt, bnbusdt, xrpusdt)
+# symbols = ["btcusdt", "ethusdt"]  # example
+# symbols-file = "symbols.txt"  # example
+# Run the full heuristics only for these; others get book stats only
+# analyze = ["btcusdt"]  # example
+# pin = ["btcusdt"]  # example
+# hide = []
+
+# Classification
+# human-threshold = 0.6
+# round-notional-units = [100.0, 250.0, 500.0]
+# round-notional = false
+# human-cutoff = 0.7
+# bot-cutoff = 0.3
+# fingerprints = "fingerprints.json"  # example
+# calibrate-percentile = 80.0  # example
+# calibration-samples = 2000
+# burst-multiplier = 3.0
+# alert-human-orders = 20  # example
+# alert-cooldown-secs = 30
+# capture-dir = "captures"  # example
+# capture-on = ["human-spike", "imbalance-swing", "crossed-book"]
+# capture-imbalance-swing = 0.5
+
+# Book and feed
+# trades = false
+# liquidations = false
+# liquidations-url = "wss://fstream.binance.com"
+# fill-window-bps = 5.0
+# min-notional = 0.0
+# analysis-band-pct = 0.5  # example
+# duplicate-levels = "keep-last"
+# validate-book = false
+# rest-url = "https://api.binance.com"
+# ws-url = "wss://stream.binance.com:9443"
+# testnet = false
+# update-speed = "100ms"
+# stale-after-secs = 10
+# snapshot-concurrency = 5
+# max-reconnect-attempts = 10  # example; unlimited by default
+# max-reconnect-delay-secs = 300
+
+# Display
+# rotate = "10s"  # example
+# max-indicators = 8
+# display-levels = 10
+# movers-windows = 6
+# imbalance-windows = 60
+# near-levels = 5
+# analyze-every-frame = false
+# analysis-refresh-ms = 0
+# strip-quote = "USDT"  # example
+# no-color = false
+# no-mouse = false
+# human-marker = "🧑"
+# bot-marker = "🤖"
+# uncertain-marker = "❔"
+# bid-marker = "▲"
+# ask-marker = "▼"
+# tape-columns = ["time:5", "side:4", "quantity:10", "price:10"]
+# ratio-palette = ["dc322f", "e6b41e", "50c850"]
+
+# Storage
+# log-file = "binance-ws.log"
+# history-file = "history.jsonl"  # example
+# record = "session.jsonl"  # example
+# write-min-ratio-delta = 0.01  # example
+# write-min-orders-delta = 5  # example
+# heartbeat-minutes = 10
+# warmup = "10s"  # example
+# store-backend = "sqlite"
+# data-dir = "data"
+# csv-dir = "data"  # example
+# dump-signals = "signals.csv"  # example
+# checkpoint-minutes = 10
+# latest-cache-ms = 1000
+# snapshot-every-secs = 60  # example
+# snapshot-on-change-pct = 0.5  # example
+
+# Monitoring
+# metrics-addr = "127.0.0.1:9000"  # example
+# pushgateway-url = "http://localhost:9091"  # example
+# push-interval-secs = 15
+# stall-threshold-secs = 5
+
+# Tables go last: every key after a [table] header belongs to that table
+
+# Per-symbol minimum notional
+# [min-notional-for]
+# BTCUSDT = 1000.0  # example
+
+# Per-symbol analysis band around mid, in percent
+# [analysis-band-pct-for]
+# BTCUSDT = 0.2  # example
+
+# Display names per symbol
+# [alias]
+# BTCUSDT = "BTC"  # example
+"##;
@@ -1,6 +1,12 @@
-use rusqlite::{Connection, Result};
-use std::time::{SystemTime, UNIX_EPOCH};
+use crate::clock::{Clock, SystemClock};
+use crate::AppError;
+use rusqlite::{Connection, OptionalExtension, Result};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
+#[derive(Debug, Clone, Serialize)]
 pub struct MarketAnalysisRecord {
     pub symbol: String,
     pub timestamp: u64,
@@ -10,13 +16,290 @@ pub struct MarketAnalysisRecord {
     pub human_ratio: f64,
 }
 
+impl MarketAnalysisRecord {
+    // Rounds window averages to whole orders. bot_orders is what the rounded counts leave,
+    // so total = human + bot always holds and nothing goes negative; the ratio is unrounded.
+    pub fn from_average(symbol: &str, timestamp: u64, avg_total: f64, avg_human: f64) -> Self {
+        let total_orders = avg_total.round().max(0.0) as i64;
+        let human_orders = (avg_human.round().max(0.0) as i64).min(total_orders);
+        MarketAnalysisRecord {
+            symbol: symbol.to_string(),
+            timestamp,
+            total_orders,
+            human_orders,
+            bot_orders: total_orders - human_orders,
+            human_ratio: if avg_total > 0.0 {
+                avg_human / avg_total
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+// Skips analysis rows that barely differ from the last one written for the symbol,
+// while still writing at least once per heartbeat so gaps mean the process was down
+#[derive(Debug, Clone)]
+pub struct WriteGate {
+    pub min_ratio_delta: f64,
+    pub min_orders_delta: i64,
+    pub heartbeat: Duration,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LastWrite {
+    pub human_ratio: f64,
+    pub total_orders: i64,
+    pub at: Instant,
+}
+
+impl WriteGate {
+    pub fn should_write(
+        &self,
+        last: Option<&LastWrite>,
+        record: &MarketAnalysisRecord,
+        now: Instant,
+    ) -> bool {
+        let Some(last) = last else {
+            return true;
+        };
+        now.duration_since(last.at) >= self.heartbeat
+            || (record.human_ratio - last.human_ratio).abs() > self.min_ratio_delta
+            || (record.total_orders - last.total_orders).abs() > self.min_orders_delta
+    }
+}
+
+// Full book levels as JSON [[price, quantity], ...], best price first
+#[derive(Debug, Clone)]
+pub struct BookSnapshotRecord {
+    pub symbol: String,
+    pub timestamp: u64, // ms
+    pub bids: String,
+    pub asks: String,
+}
+
+// Which book updates are stored as full snapshots: at most one per interval, or only
+// when either side of the top of book has moved by at least this many percent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapshotPolicy {
+    Interval(Duration),
+    OnChange(f64),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LastSnapshot {
+    pub best_bid: f64,
+    pub best_ask: f64,
+    pub at: Instant,
+}
+
+impl SnapshotPolicy {
+    pub fn should_store(
+        &self,
+        last: Option<&LastSnapshot>,
+        best_bid: f64,
+        best_ask: f64,
+        now: Instant,
+    ) -> bool {
+        let Some(last) = last else {
+            return true;
+        };
+        match *self {
+            SnapshotPolicy::Interval(interval) => now.duration_since(last.at) >= interval,
+            SnapshotPolicy::OnChange(percent) => {
+                let moved = |from: f64, to: f64| {
+                    from > 0.0 && ((to - from) / from).abs() * 100.0 >= percent
+                };
+                moved(last.best_bid, best_bid) || moved(last.best_ask, best_ask)
+            }
+        }
+    }
+}
+
+// Holds analysis records that failed to insert so they can be retried on the next tick
+pub struct WriteBuffer {
+    records: VecDeque<MarketAnalysisRecord>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl WriteBuffer {
+    pub fn new(capacity: usize) -> Self {
+        WriteBuffer {
+            records: VecDeque::new(),
+            capacity: capacity.max(1),
+            dropped: 0,
+        }
+    }
+
+    // Queues a record, discarding the oldest one when full
+    pub fn push(&mut self, record: MarketAnalysisRecord) {
+        if self.records.len() >= self.capacity {
+            self.records.pop_front();
+            self.dropped += 1;
+        }
+        self.records.push_back(record);
+    }
+
+    // Inserts queued records oldest first, stopping at the first failure
+    pub fn flush(&mut self, store: &dyn AnalysisStore) -> std::result::Result<usize, AppError> {
+        let mut written = 0;
+        while let Some(record) = self.records.front() {
+            store.insert_analysis(record)?;
+            self.records.pop_front();
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    // Records lost because the buffer overflowed
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+// A note taken from the dashboard, with the top of book as it was; prices and quantities
+// are kept as the exchange's decimal strings, None for an empty side
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotationRecord {
+    pub symbol: String,
+    pub timestamp: u64, // seconds, same as market_analysis
+    pub note: String,
+    pub bid_price: Option<String>,
+    pub bid_quantity: Option<String>,
+    pub ask_price: Option<String>,
+    pub ask_quantity: Option<String>,
+}
+
+pub struct LatencyRecord {
+    pub symbol: String,
+    pub timestamp: u64,
+    pub latency_ms: i64,
+    pub clock_skew: bool,
+}
+
+// Where analysis records are written and read back: the market_analysis table by default,
+// or date-partitioned files (see file_store::CsvStore)
+pub trait AnalysisStore {
+    fn insert_analysis(&self, record: &MarketAnalysisRecord) -> std::result::Result<(), AppError>;
+
+    fn get_latest_analysis(
+        &self,
+        symbol: &str,
+    ) -> std::result::Result<Option<MarketAnalysisRecord>, AppError>;
+
+    // Newest first
+    fn get_analysis_history(
+        &self,
+        symbol: &str,
+        limit: i64,
+    ) -> std::result::Result<Vec<MarketAnalysisRecord>, AppError>;
+
+    // start_ts <= timestamp <= end_ts, oldest first
+    fn get_analysis_between(
+        &self,
+        symbol: &str,
+        start_ts: u64,
+        end_ts: u64,
+    ) -> std::result::Result<Vec<MarketAnalysisRecord>, AppError>;
+
+    // Records for one symbol, or all of them with None
+    fn count_records(&self, symbol: Option<&str>) -> std::result::Result<i64, AppError>;
+
+    // min_ratio <= human_ratio <= max_ratio and start_ts <= timestamp <= end_ts, newest first
+    fn find_by_ratio(
+        &self,
+        symbol: &str,
+        min_ratio: f64,
+        max_ratio: f64,
+        start_ts: u64,
+        end_ts: u64,
+        limit: i64,
+    ) -> std::result::Result<Vec<MarketAnalysisRecord>, AppError>;
+}
+
 pub struct Database {
     conn: Connection,
 }
 
+impl AnalysisStore for Database {
+    fn insert_analysis(&self, record: &MarketAnalysisRecord) -> std::result::Result<(), AppError> {
+        Ok(Database::insert_analysis(self, record)?)
+    }
+
+    fn get_latest_analysis(
+        &self,
+        symbol: &str,
+    ) -> std::result::Result<Option<MarketAnalysisRecord>, AppError> {
+        Ok(Database::get_latest_analysis(self, symbol)?)
+    }
+
+    fn get_analysis_history(
+        &self,
+        symbol: &str,
+        limit: i64,
+    ) -> std::result::Result<Vec<MarketAnalysisRecord>, AppError> {
+        Ok(Database::get_analysis_history(self, symbol, limit)?)
+    }
+
+    fn get_analysis_between(
+        &self,
+        symbol: &str,
+        start_ts: u64,
+        end_ts: u64,
+    ) -> std::result::Result<Vec<MarketAnalysisRecord>, AppError> {
+        Ok(Database::get_analysis_between(
+            self, symbol, start_ts, end_ts,
+        )?)
+    }
+
+    fn count_records(&self, symbol: Option<&str>) -> std::result::Result<i64, AppError> {
+        Ok(Database::count_records(self, symbol)?)
+    }
+
+    fn find_by_ratio(
+        &self,
+        symbol: &str,
+        min_ratio: f64,
+        max_ratio: f64,
+        start_ts: u64,
+        end_ts: u64,
+        limit: i64,
+    ) -> std::result::Result<Vec<MarketAnalysisRecord>, AppError> {
+        Ok(Database::find_by_ratio(
+            self, symbol, min_ratio, max_ratio, start_ts, end_ts, limit,
+        )?)
+    }
+}
+
 impl Database {
     pub fn new() -> Result<Self> {
-        let conn = Connection::open("market_analysis.db")?;
+        Self::open("market_analysis.db")
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        // Only takes effect on a new file (or after a VACUUM); lets incremental_vacuum hand
+        // pages freed by deletes back to the OS without rebuilding the whole database
+        conn.execute_batch("PRAGMA auto_vacuum = INCREMENTAL")?;
+        // Readers (stats, a second dashboard) don't block the writer; the WAL is
+        // folded back periodically by checkpoint. In-memory databases stay in memory mode.
+        conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
 
         // Create the table if it doesn't exist
         conn.execute(
@@ -32,14 +315,114 @@ impl Database {
             [],
         )?;
 
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_market_analysis_symbol_timestamp
+             ON market_analysis (symbol, timestamp)",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS feed_latency (
+                id INTEGER PRIMARY KEY,
+                symbol TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                latency_ms INTEGER NOT NULL,
+                clock_skew INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        // Same shape as market_analysis, filled by re-running the classifier on snapshots
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS market_reanalysis (
+                id INTEGER PRIMARY KEY,
+                symbol TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                total_orders INTEGER NOT NULL,
+                human_orders INTEGER NOT NULL,
+                bot_orders INTEGER NOT NULL,
+                human_ratio REAL NOT NULL
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS book_snapshots (
+                id INTEGER PRIMARY KEY,
+                symbol TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                bids TEXT NOT NULL,
+                asks TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Running total per symbol, see OrderBook::spoof_candidates
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS spoof_counts (
+                symbol TEXT PRIMARY KEY,
+                count INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS annotations (
+                id INTEGER PRIMARY KEY,
+                symbol TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                note TEXT NOT NULL,
+                bid_price TEXT,
+                bid_quantity TEXT,
+                ask_price TEXT,
+                ask_quantity TEXT
+            )",
+            [],
+        )?;
+
         Ok(Database { conn })
     }
 
+    // Folds the WAL back into the main file and truncates it; a no-op outside WAL mode
+    pub fn checkpoint(&self) -> Result<()> {
+        self.conn
+            .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+    }
+
+    // Rebuilds the file so space freed by deletes is reclaimed, and converts a database
+    // created before auto-vacuum. In WAL mode the rebuilt pages go through the WAL, so it is
+    // checkpointed afterwards for the main file to actually shrink.
+    pub fn vacuum(&self) -> Result<()> {
+        self.conn.execute_batch("VACUUM")?;
+        self.checkpoint()
+    }
+
+    // Truncates free pages off the end of the file; a no-op without incremental auto-vacuum
+    pub fn incremental_vacuum(&self) -> Result<()> {
+        self.conn.execute_batch("PRAGMA incremental_vacuum")
+    }
+
+    // Lets SQLite refresh query planner statistics; meant for shutdown
+    pub fn optimize(&self) -> Result<()> {
+        self.conn.execute_batch("PRAGMA optimize")
+    }
+
     pub fn insert_analysis(&self, record: &MarketAnalysisRecord) -> Result<()> {
+        self.insert_analysis_into("market_analysis", record)
+    }
+
+    pub fn insert_reanalysis(&self, record: &MarketAnalysisRecord) -> Result<()> {
+        self.insert_analysis_into("market_reanalysis", record)
+    }
+
+    fn insert_analysis_into(&self, table: &str, record: &MarketAnalysisRecord) -> Result<()> {
         self.conn.execute(
-            "INSERT INTO market_analysis (
-                symbol, timestamp, total_orders, human_orders, bot_orders, human_ratio
-            ) VALUES (?, ?, ?, ?, ?, ?)",
+            &format!(
+                "INSERT INTO {} (
+                    symbol, timestamp, total_orders, human_orders, bot_orders, human_ratio
+                ) VALUES (?, ?, ?, ?, ?, ?)",
+                table
+            ),
             (
                 &record.symbol,
                 record.timestamp,
@@ -52,6 +435,124 @@ impl Database {
         Ok(())
     }
 
+    pub fn insert_snapshot(&self, record: &BookSnapshotRecord) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO book_snapshots (symbol, timestamp, bids, asks) VALUES (?, ?, ?, ?)",
+            (&record.symbol, record.timestamp, &record.bids, &record.asks),
+        )?;
+        Ok(())
+    }
+
+    pub fn insert_annotation(&self, record: &AnnotationRecord) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO annotations
+             (symbol, timestamp, note, bid_price, bid_quantity, ask_price, ask_quantity)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            (
+                &record.symbol,
+                record.timestamp,
+                &record.note,
+                &record.bid_price,
+                &record.bid_quantity,
+                &record.ask_price,
+                &record.ask_quantity,
+            ),
+        )?;
+        Ok(())
+    }
+
+    // Notes for one symbol, or every symbol with None, oldest first
+    pub fn get_annotations(&self, symbol: Option<&str>) -> Result<Vec<AnnotationRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT symbol, timestamp, note, bid_price, bid_quantity, ask_price, ask_quantity
+             FROM annotations
+             WHERE ?1 IS NULL OR symbol = ?1
+             ORDER BY timestamp, id",
+        )?;
+
+        let rows = stmt.query_map([symbol], |row| {
+            Ok(AnnotationRecord {
+                symbol: row.get(0)?,
+                timestamp: row.get(1)?,
+                note: row.get(2)?,
+                bid_price: row.get(3)?,
+                bid_quantity: row.get(4)?,
+                ask_price: row.get(5)?,
+                ask_quantity: row.get(6)?,
+            })
+        })?;
+
+        let mut records = Vec::new();
+        for record in rows {
+            records.push(record?);
+        }
+        Ok(records)
+    }
+
+    pub fn add_spoofs(&self, symbol: &str, count: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO spoof_counts (symbol, count) VALUES (?, ?)
+             ON CONFLICT (symbol) DO UPDATE SET count = count + excluded.count",
+            (symbol, count),
+        )?;
+        Ok(())
+    }
+
+    pub fn spoof_count(&self, symbol: &str) -> Result<i64> {
+        self.conn
+            .query_row(
+                "SELECT count FROM spoof_counts WHERE symbol = ?",
+                [symbol],
+                |row| row.get(0),
+            )
+            .optional()
+            .map(|count| count.unwrap_or(0))
+    }
+
+    // Snapshots with timestamps (ms) in [start_ms, end_ms], oldest first
+    pub fn get_snapshots_between(
+        &self,
+        symbol: &str,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Vec<BookSnapshotRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT symbol, timestamp, bids, asks
+             FROM book_snapshots
+             WHERE symbol = ? AND timestamp BETWEEN ? AND ?
+             ORDER BY timestamp ASC",
+        )?;
+
+        let rows = stmt.query_map((symbol, start_ms, end_ms), |row| {
+            Ok(BookSnapshotRecord {
+                symbol: row.get(0)?,
+                timestamp: row.get(1)?,
+                bids: row.get(2)?,
+                asks: row.get(3)?,
+            })
+        })?;
+
+        let mut records = Vec::new();
+        for record in rows {
+            records.push(record?);
+        }
+        Ok(records)
+    }
+
+    pub fn insert_latency(&self, record: &LatencyRecord) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO feed_latency (symbol, timestamp, latency_ms, clock_skew)
+             VALUES (?, ?, ?, ?)",
+            (
+                &record.symbol,
+                record.timestamp,
+                record.latency_ms,
+                record.clock_skew,
+            ),
+        )?;
+        Ok(())
+    }
+
     pub fn get_latest_analysis(&self, symbol: &str) -> Result<Option<MarketAnalysisRecord>> {
         let mut stmt = self.conn.prepare(
             "SELECT symbol, timestamp, total_orders, human_orders, bot_orders, human_ratio 
@@ -107,11 +608,107 @@ impl Database {
         }
         Ok(records)
     }
+
+    // Analysis rows for one symbol, or across all symbols with None
+    pub fn count_records(&self, symbol: Option<&str>) -> Result<i64> {
+        match symbol {
+            Some(symbol) => self.conn.query_row(
+                "SELECT COUNT(*) FROM market_analysis WHERE symbol = ?",
+                [symbol],
+                |row| row.get(0),
+            ),
+            None => self
+                .conn
+                .query_row("SELECT COUNT(*) FROM market_analysis", [], |row| row.get(0)),
+        }
+    }
+
+    // Size in bytes from SQLite's page accounting, so it also works for in-memory databases
+    pub fn db_file_size(&self) -> Result<u64> {
+        let page_count: i64 = self
+            .conn
+            .query_row("PRAGMA page_count", [], |row| row.get(0))?;
+        let page_size: i64 = self
+            .conn
+            .query_row("PRAGMA page_size", [], |row| row.get(0))?;
+        Ok((page_count * page_size) as u64)
+    }
+
+    // Records with start_ts <= timestamp <= end_ts, oldest first
+    pub fn get_analysis_between(
+        &self,
+        symbol: &str,
+        start_ts: u64,
+        end_ts: u64,
+    ) -> Result<Vec<MarketAnalysisRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT symbol, timestamp, total_orders, human_orders, bot_orders, human_ratio
+             FROM market_analysis
+             WHERE symbol = ? AND timestamp BETWEEN ? AND ?
+             ORDER BY timestamp ASC",
+        )?;
+
+        let rows = stmt.query_map((symbol, start_ts, end_ts), |row| {
+            Ok(MarketAnalysisRecord {
+                symbol: row.get(0)?,
+                timestamp: row.get(1)?,
+                total_orders: row.get(2)?,
+                human_orders: row.get(3)?,
+                bot_orders: row.get(4)?,
+                human_ratio: row.get(5)?,
+            })
+        })?;
+
+        let mut records = Vec::new();
+        for record in rows {
+            records.push(record?);
+        }
+        Ok(records)
+    }
+
+    // Records with min_ratio <= human_ratio <= max_ratio and start_ts <= timestamp <= end_ts,
+    // newest first
+    pub fn find_by_ratio(
+        &self,
+        symbol: &str,
+        min_ratio: f64,
+        max_ratio: f64,
+        start_ts: u64,
+        end_ts: u64,
+        limit: i64,
+    ) -> Result<Vec<MarketAnalysisRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT symbol, timestamp, total_orders, human_orders, bot_orders, human_ratio
+             FROM market_analysis
+             WHERE symbol = ? AND human_ratio BETWEEN ? AND ? AND timestamp BETWEEN ? AND ?
+             ORDER BY timestamp DESC
+             LIMIT ?",
+        )?;
+
+        let params = (symbol, min_ratio, max_ratio, start_ts, end_ts, limit);
+        let rows = stmt.query_map(params, |row| {
+            Ok(MarketAnalysisRecord {
+                symbol: row.get(0)?,
+                timestamp: row.get(1)?,
+                total_orders: row.get(2)?,
+                human_orders: row.get(3)?,
+                bot_orders: row.get(4)?,
+                human_ratio: row.get(5)?,
+            })
+        })?;
+
+        let mut records = Vec::new();
+        for record in rows {
+            records.push(record?);
+        }
+        Ok(records)
+    }
 }
 
 pub fn get_current_timestamp() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs()
+    SystemClock.unix_secs()
+}
+
+pub fn get_current_timestamp_millis() -> u64 {
+    SystemClock.unix_millis()
 }
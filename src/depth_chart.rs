@@ -0,0 +1,87 @@
+use crate::{AppError, OrderBook, OrderBookEntry};
+use plotters::prelude::*;
+use std::path::Path;
+
+const WIDTH: u32 = 1000;
+const HEIGHT: u32 = 600;
+
+// (price, cumulative quantity) from the best price outwards
+pub type DepthCurve = Vec<(f64, f64)>;
+
+fn cumulative(orders: &[OrderBookEntry]) -> DepthCurve {
+    let mut total = 0.0;
+    orders
+        .iter()
+        .filter_map(|o| {
+            Some((
+                o.price.parse::<f64>().ok()?,
+                o.quantity.parse::<f64>().ok()?,
+            ))
+        })
+        .map(|(price, quantity)| {
+            total += quantity;
+            (price, total)
+        })
+        .collect()
+}
+
+// (bids, asks) depth curves
+pub fn cumulative_depth(book: &OrderBook) -> (DepthCurve, DepthCurve) {
+    (cumulative(&book.bids), cumulative(&book.asks))
+}
+
+// Writes cumulative bid (green) and ask (red) depth as an SVG
+pub fn render_svg<P: AsRef<Path>>(book: &OrderBook, symbol: &str, path: P) -> Result<(), AppError> {
+    let (bids, asks) = cumulative_depth(book);
+    let prices = bids.iter().chain(asks.iter()).map(|&(price, _)| price);
+    let (min_price, max_price) = prices.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+        (lo.min(p), hi.max(p))
+    });
+    if !min_price.is_finite() || !max_price.is_finite() {
+        return Err(AppError::Chart(format!("no book levels for {}", symbol)));
+    }
+    let max_depth = bids
+        .iter()
+        .chain(asks.iter())
+        .map(|&(_, depth)| depth)
+        .fold(0.0, f64::max);
+    // Keep a single-level book from collapsing to a zero-width axis
+    let pad = ((max_price - min_price) * 0.02).max(max_price.abs() * 1e-6);
+
+    let chart_error = |e: &dyn std::fmt::Display| AppError::Chart(e.to_string());
+    let root = SVGBackend::new(path.as_ref(), (WIDTH, HEIGHT)).into_drawing_area();
+    root.fill(&WHITE).map_err(|e| chart_error(&e))?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(format!("{} depth", symbol), ("sans-serif", 24))
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(
+            (min_price - pad)..(max_price + pad),
+            0.0..(max_depth * 1.05).max(f64::EPSILON),
+        )
+        .map_err(|e| chart_error(&e))?;
+    chart
+        .configure_mesh()
+        .x_desc("Price")
+        .y_desc("Cumulative quantity")
+        .draw()
+        .map_err(|e| chart_error(&e))?;
+
+    // Stepped so each level's quantity shows as a flat run up to the next price
+    for (levels, color) in [(&bids, GREEN), (&asks, RED)] {
+        let mut steps = Vec::with_capacity(levels.len() * 2);
+        let mut previous = 0.0;
+        for &(price, depth) in levels.iter() {
+            steps.push((price, previous));
+            steps.push((price, depth));
+            previous = depth;
+        }
+        chart
+            .draw_series(AreaSeries::new(steps, 0.0, color.mix(0.3)).border_style(color))
+            .map_err(|e| chart_error(&e))?;
+    }
+
+    root.present().map_err(|e| chart_error(&e))?;
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::mpsc::error::SendError;
+
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("database error: {0}")]
+    Db(#[from] rusqlite::Error),
+    #[error("websocket error: {0}")]
+    Ws(Box<tokio_tungstenite::tungstenite::Error>),
+    #[error("http error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("parse error: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("invalid url: {0}")]
+    Url(#[from] url::ParseError),
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("unknown symbol: {0}")]
+    UnknownSymbol(String),
+    #[error("connection stale: no updates for {0:?}")]
+    Stale(Duration),
+    #[error("giving up after {0} failed connection attempts")]
+    ReconnectLimit(u32),
+    #[error("binance error {code}: {msg}")]
+    Api { code: i64, msg: String },
+    #[error("IP banned by binance: {0}")]
+    Banned(String),
+    #[error("bad depth snapshot for {0}: {1}")]
+    BadSnapshot(String, String),
+    #[error("config error: {0}")]
+    Config(String),
+    #[error("chart error: {0}")]
+    Chart(String),
+    #[error("update channel closed")]
+    ChannelClosed,
+}
+
+impl AppError {
+    // Network hiccups worth retrying, as opposed to configuration or storage failures.
+    // Binance's -1003 (too many requests) clears once the request weight window passes;
+    // a ban (HTTP 418) does not, and every retry during one lengthens it.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            AppError::Ws(_)
+                | AppError::Http(_)
+                | AppError::Stale(_)
+                | AppError::Api { code: -1003, .. }
+        )
+    }
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for AppError {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        AppError::Ws(Box::new(e))
+    }
+}
+
+impl<T> From<SendError<T>> for AppError {
+    fn from(_: SendError<T>) -> Self {
+        AppError::ChannelClosed
+    }
+}
@@ -0,0 +1,124 @@
+use crate::AppError;
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+// Trading rules from Binance's exchangeInfo for one symbol
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymbolFilters {
+    pub step_size: Decimal, // LOT_SIZE quantity increment
+    pub tick_size: Decimal, // PRICE_FILTER price increment
+}
+
+// Depth stream push interval; Binance only accepts these two
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UpdateSpeed {
+    #[default]
+    Ms100,
+    Ms1000,
+}
+
+impl UpdateSpeed {
+    // Diff depth stream name for a lowercase symbol, e.g. btcusdt@depth@100ms
+    pub fn depth_stream(self, symbol: &str) -> String {
+        match self {
+            UpdateSpeed::Ms100 => format!("{}@depth@100ms", symbol),
+            UpdateSpeed::Ms1000 => format!("{}@depth", symbol), // 1000ms is the stream default
+        }
+    }
+}
+
+impl std::str::FromStr for UpdateSpeed {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "100ms" => Ok(UpdateSpeed::Ms100),
+            "1000ms" | "1s" => Ok(UpdateSpeed::Ms1000),
+            other => Err(format!("expected 100ms or 1000ms, got {:?}", other)),
+        }
+    }
+}
+
+// Binance answers a request naming an unknown symbol with {"code": -1121, "msg": "Invalid symbol."}
+pub fn is_invalid_symbol_error(response: &Value) -> bool {
+    response.get("code").and_then(|c| c.as_i64()) == Some(-1121)
+}
+
+// HTTP status Binance answers with once an IP is banned for ignoring rate limits
+const BANNED_STATUS: u16 = 418;
+
+// Normalizes an /api/v3/depth response to {"symbol", "bids", "asks", "lastUpdateId"}.
+// Rate limits and bans come back as JSON too ({"code": -1003, "msg": ...}), so an error
+// body or a missing side is an error rather than an empty book. A ban shares -1003 with
+// plain rate limiting and is told apart by its 418 status.
+pub fn parse_depth_snapshot(
+    symbol: &str,
+    status: u16,
+    response: &Value,
+) -> Result<Value, AppError> {
+    let symbol = symbol.to_uppercase();
+    if let Some(code) = response.get("code").and_then(|c| c.as_i64()) {
+        let msg = response.get("msg").and_then(|m| m.as_str()).unwrap_or("");
+        if status == BANNED_STATUS {
+            return Err(AppError::Banned(msg.to_string()));
+        }
+        return Err(AppError::Api {
+            code,
+            msg: format!("{} ({})", msg, symbol),
+        });
+    }
+    for side in ["bids", "asks"] {
+        if !response.get(side).is_some_and(|levels| levels.is_array()) {
+            return Err(AppError::BadSnapshot(
+                symbol,
+                format!("{} missing or not an array", side),
+            ));
+        }
+    }
+    Ok(json!({
+        "symbol": symbol,
+        "bids": response["bids"],
+        "asks": response["asks"],
+        "lastUpdateId": response["lastUpdateId"]
+    }))
+}
+
+// Parses an /api/v3/exchangeInfo response into filters keyed by uppercase symbol
+pub fn parse_exchange_info(response: &Value) -> HashMap<String, SymbolFilters> {
+    let mut filters = HashMap::new();
+    let Some(symbols) = response.get("symbols").and_then(|s| s.as_array()) else {
+        return filters;
+    };
+
+    for symbol in symbols {
+        let Some(name) = symbol.get("symbol").and_then(|s| s.as_str()) else {
+            continue;
+        };
+        let filter_value = |filter_type: &str, field: &str| -> Option<Decimal> {
+            symbol
+                .get("filters")?
+                .as_array()?
+                .iter()
+                .find(|f| f.get("filterType").and_then(|t| t.as_str()) == Some(filter_type))?
+                .get(field)?
+                .as_str()?
+                .parse()
+                .ok()
+        };
+
+        if let (Some(step_size), Some(tick_size)) = (
+            filter_value("LOT_SIZE", "stepSize"),
+            filter_value("PRICE_FILTER", "tickSize"),
+        ) {
+            filters.insert(
+                name.to_uppercase(),
+                SymbolFilters {
+                    step_size,
+                    tick_size,
+                },
+            );
+        }
+    }
+    filters
+}
@@ -0,0 +1,325 @@
+use crate::db::{AnalysisStore, MarketAnalysisRecord};
+use crate::{AppError, LevelSignals};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+const HEADER: &str = "symbol,timestamp,total_orders,human_orders,bot_orders,human_ratio";
+const SINK_HEADER: &str =
+    "symbol,timestamp,total_orders,human_orders,bot_orders,human_ratio,spread,imbalance";
+const SIGNAL_HEADER: &str = "timestamp,symbol,side,price,round_price,human_size,human_spacing,\
+step_alignment,round_notional,score,classified";
+
+// Where analysis records go; snapshots, latency and everything else stay in SQLite
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StoreBackend {
+    #[default]
+    Sqlite,
+    Csv,
+}
+
+impl std::str::FromStr for StoreBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "sqlite" => Ok(StoreBackend::Sqlite),
+            "csv" => Ok(StoreBackend::Csv),
+            other => Err(format!("expected sqlite or csv, got {:?}", other)),
+        }
+    }
+}
+
+// UTC calendar date of a unix timestamp in seconds, as YYYY-MM-DD
+pub fn utc_date(ts: u64) -> String {
+    // Days-to-civil conversion from Howard Hinnant's date algorithms
+    let days = (ts / SECONDS_PER_DAY) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Append-only CSV files, one per symbol and UTC day: <root>/BTCUSDT/2024-01-01.csv
+pub struct CsvStore {
+    root: PathBuf,
+}
+
+impl CsvStore {
+    pub fn open<P: AsRef<Path>>(root: P) -> Result<Self, AppError> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(&root)?;
+        Ok(CsvStore { root })
+    }
+
+    pub fn file_for(&self, symbol: &str, ts: u64) -> PathBuf {
+        self.root.join(symbol).join(format!("{}.csv", utc_date(ts)))
+    }
+
+    fn read_file(path: &Path) -> Result<Vec<MarketAnalysisRecord>, AppError> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let parse_error = |line: &str| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{}: bad row {:?}", path.display(), line),
+            )
+        };
+        let mut records = Vec::new();
+        for line in contents.lines().skip(1).filter(|l| !l.trim().is_empty()) {
+            let fields: Vec<&str> = line.split(',').collect();
+            let [symbol, timestamp, total, human, bot, ratio] = fields[..] else {
+                return Err(parse_error(line).into());
+            };
+            records.push(MarketAnalysisRecord {
+                symbol: symbol.to_string(),
+                timestamp: timestamp.parse().map_err(|_| parse_error(line))?,
+                total_orders: total.parse().map_err(|_| parse_error(line))?,
+                human_orders: human.parse().map_err(|_| parse_error(line))?,
+                bot_orders: bot.parse().map_err(|_| parse_error(line))?,
+                human_ratio: ratio.parse().map_err(|_| parse_error(line))?,
+            });
+        }
+        Ok(records)
+    }
+
+    // Day files for a symbol, newest first
+    fn day_files(&self, symbol: &str) -> Result<Vec<PathBuf>, AppError> {
+        let dir = self.root.join(symbol);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "csv"))
+            .collect();
+        files.sort();
+        files.reverse();
+        Ok(files)
+    }
+}
+
+impl AnalysisStore for CsvStore {
+    fn insert_analysis(&self, record: &MarketAnalysisRecord) -> Result<(), AppError> {
+        let path = self.file_for(&record.symbol, record.timestamp);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "{}", HEADER)?;
+        }
+        writeln!(
+            file,
+            "{},{},{},{},{},{}",
+            record.symbol,
+            record.timestamp,
+            record.total_orders,
+            record.human_orders,
+            record.bot_orders,
+            record.human_ratio
+        )?;
+        Ok(())
+    }
+
+    fn get_latest_analysis(&self, symbol: &str) -> Result<Option<MarketAnalysisRecord>, AppError> {
+        Ok(self.get_analysis_history(symbol, 1)?.into_iter().next())
+    }
+
+    fn get_analysis_history(
+        &self,
+        symbol: &str,
+        limit: i64,
+    ) -> Result<Vec<MarketAnalysisRecord>, AppError> {
+        let limit = limit.max(0) as usize;
+        let mut records = Vec::new();
+        for path in self.day_files(symbol)? {
+            if records.len() >= limit {
+                break;
+            }
+            let mut day = Self::read_file(&path)?;
+            day.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
+            records.extend(day);
+        }
+        records.truncate(limit);
+        Ok(records)
+    }
+
+    fn get_analysis_between(
+        &self,
+        symbol: &str,
+        start_ts: u64,
+        end_ts: u64,
+    ) -> Result<Vec<MarketAnalysisRecord>, AppError> {
+        let mut records = Vec::new();
+        for day in start_ts / SECONDS_PER_DAY..=end_ts / SECONDS_PER_DAY {
+            records.extend(
+                Self::read_file(&self.file_for(symbol, day * SECONDS_PER_DAY))?
+                    .into_iter()
+                    .filter(|r| (start_ts..=end_ts).contains(&r.timestamp)),
+            );
+        }
+        records.sort_by_key(|r| r.timestamp);
+        Ok(records)
+    }
+
+    fn count_records(&self, symbol: Option<&str>) -> Result<i64, AppError> {
+        let symbols = match symbol {
+            Some(symbol) => vec![symbol.to_string()],
+            None => fs::read_dir(&self.root)?
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect(),
+        };
+        let mut count = 0;
+        for symbol in symbols {
+            for path in self.day_files(&symbol)? {
+                count += Self::read_file(&path)?.len() as i64;
+            }
+        }
+        Ok(count)
+    }
+
+    fn find_by_ratio(
+        &self,
+        symbol: &str,
+        min_ratio: f64,
+        max_ratio: f64,
+        start_ts: u64,
+        end_ts: u64,
+        limit: i64,
+    ) -> Result<Vec<MarketAnalysisRecord>, AppError> {
+        let limit = limit.max(0) as usize;
+        let mut records = Vec::new();
+        for path in self.day_files(symbol)? {
+            if records.len() >= limit {
+                break;
+            }
+            let mut day: Vec<MarketAnalysisRecord> = Self::read_file(&path)?
+                .into_iter()
+                .filter(|r| (min_ratio..=max_ratio).contains(&r.human_ratio))
+                .filter(|r| (start_ts..=end_ts).contains(&r.timestamp))
+                .collect();
+            day.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
+            records.extend(day);
+        }
+        records.truncate(limit);
+        Ok(records)
+    }
+}
+
+// One continuously appended file per symbol for tools that tail it: <dir>/BTCUSDT.csv.
+// Every row reopens the file, so one rotated away or deleted is recreated with a header.
+pub struct CsvSink {
+    dir: PathBuf,
+}
+
+impl CsvSink {
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, AppError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(CsvSink { dir })
+    }
+
+    pub fn file_for(&self, symbol: &str) -> PathBuf {
+        self.dir.join(format!("{}.csv", symbol))
+    }
+
+    // Spread and imbalance are left empty when the book has no top on a side
+    pub fn append(
+        &self,
+        record: &MarketAnalysisRecord,
+        spread: Option<f64>,
+        imbalance: Option<f64>,
+    ) -> Result<(), AppError> {
+        let optional = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.file_for(&record.symbol))?;
+        let mut row = String::new();
+        if file.metadata()?.len() == 0 {
+            row.push_str(SINK_HEADER);
+            row.push('\n');
+        }
+        row.push_str(&format!(
+            "{},{},{},{},{},{},{},{}\n",
+            record.symbol,
+            record.timestamp,
+            record.total_orders,
+            record.human_orders,
+            record.bot_orders,
+            record.human_ratio,
+            optional(spread),
+            optional(imbalance)
+        ));
+        // A single write so a tailing reader never sees half a row
+        file.write_all(row.as_bytes())?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+// Raw heuristic outputs for offline tuning: one row per scored level per analysis, with
+// signals as 1/0 and the ones that did not apply left empty
+pub struct SignalDump {
+    file: File,
+}
+
+impl SignalDump {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, AppError> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "{}", SIGNAL_HEADER)?;
+        }
+        Ok(SignalDump { file })
+    }
+
+    pub fn row(
+        timestamp_ms: u64,
+        symbol: &str,
+        side: &str,
+        price: &str,
+        signals: &LevelSignals,
+        score: f64,
+        classified: &str,
+    ) -> String {
+        let flag = |signal: bool| if signal { "1" } else { "0" };
+        let optional = |signal: Option<bool>| signal.map(flag).unwrap_or_default();
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{}\n",
+            timestamp_ms,
+            symbol,
+            side,
+            price,
+            flag(signals.round_price),
+            flag(signals.human_size),
+            optional(signals.human_spacing),
+            optional(signals.step_alignment),
+            optional(signals.round_notional),
+            score,
+            classified
+        )
+    }
+
+    // Rows from one analysis go out in a single write
+    pub fn write(&mut self, rows: &str) -> Result<(), AppError> {
+        self.file.write_all(rows.as_bytes())?;
+        Ok(())
+    }
+}
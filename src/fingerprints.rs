@@ -0,0 +1,48 @@
+use crate::AppError;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SizeFingerprint {
+    pub quantity: f64,
+    #[serde(default)]
+    pub tolerance: f64,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FingerprintSet {
+    pub fingerprints: Vec<SizeFingerprint>,
+}
+
+impl SizeFingerprint {
+    pub fn matches(&self, quantity: f64) -> bool {
+        (quantity - self.quantity).abs() <= self.tolerance.max(f64::EPSILON)
+    }
+
+    pub fn indicator(&self) -> String {
+        match &self.label {
+            Some(label) => format!("Known bot fingerprint: {} ({})", label, self.quantity),
+            None => format!("Known bot fingerprint: {}", self.quantity),
+        }
+    }
+}
+
+impl FingerprintSet {
+    // Expects a JSON array, e.g. [{"quantity": 0.137, "tolerance": 0.0005, "label": "mm-a"}]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AppError> {
+        let contents = fs::read_to_string(path)?;
+        let fingerprints: Vec<SizeFingerprint> = serde_json::from_str(&contents)?;
+        Ok(FingerprintSet { fingerprints })
+    }
+
+    pub fn find(&self, quantity: f64) -> Option<&SizeFingerprint> {
+        self.fingerprints.iter().find(|f| f.matches(quantity))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fingerprints.is_empty()
+    }
+}
@@ -0,0 +1,177 @@
+use crossterm::event::KeyCode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    NextSymbol,
+    TogglePause,
+    MoreLevels,
+    FewerLevels,
+    ResetCalibration,
+    ToggleGrouping,
+    FinerBuckets,
+    CoarserBuckets,
+    ToggleAnalysis,
+    ToggleAlert,
+    ToggleRotation,
+    SlowerRotation,
+    FasterRotation,
+    StepReplay,
+    FasterReplay,
+    SlowerReplay,
+    ToggleTuning,
+    LowerThreshold,
+    RaiseThreshold,
+    Annotate,
+    ToggleHelp,
+}
+
+pub struct Binding {
+    pub keys: &'static [KeyCode],
+    pub label: &'static str,
+    pub description: &'static str,
+    pub action: Action,
+}
+
+// Drives both key handling and the help overlay, so new keys only need adding here
+pub const BINDINGS: &[Binding] = &[
+    Binding {
+        keys: &[KeyCode::Char('q')],
+        label: "q",
+        description: "Quit",
+        action: Action::Quit,
+    },
+    Binding {
+        keys: &[KeyCode::Char('n')],
+        label: "n",
+        description: "Next symbol",
+        action: Action::NextSymbol,
+    },
+    Binding {
+        keys: &[KeyCode::Char(' ')],
+        label: "space",
+        description: "Pause / resume updates (or the replay)",
+        action: Action::TogglePause,
+    },
+    Binding {
+        keys: &[KeyCode::Char('+'), KeyCode::Char('=')],
+        label: "+",
+        description: "Show more book levels",
+        action: Action::MoreLevels,
+    },
+    Binding {
+        keys: &[KeyCode::Char('-')],
+        label: "-",
+        description: "Show fewer book levels",
+        action: Action::FewerLevels,
+    },
+    Binding {
+        keys: &[KeyCode::Char('c')],
+        label: "c",
+        description: "Restart threshold calibration",
+        action: Action::ResetCalibration,
+    },
+    Binding {
+        keys: &[KeyCode::Char('g')],
+        label: "g",
+        description: "Group book levels into price buckets",
+        action: Action::ToggleGrouping,
+    },
+    Binding {
+        keys: &[KeyCode::Char('[')],
+        label: "[",
+        description: "Halve the price bucket",
+        action: Action::FinerBuckets,
+    },
+    Binding {
+        keys: &[KeyCode::Char(']')],
+        label: "]",
+        description: "Double the price bucket",
+        action: Action::CoarserBuckets,
+    },
+    Binding {
+        keys: &[KeyCode::Char('a')],
+        label: "a",
+        description: "Toggle analysis for this symbol",
+        action: Action::ToggleAnalysis,
+    },
+    Binding {
+        keys: &[KeyCode::Char('b')],
+        label: "b",
+        description: "Toggle the human-activity alert",
+        action: Action::ToggleAlert,
+    },
+    Binding {
+        keys: &[KeyCode::Char('r')],
+        label: "r",
+        description: "Pause / resume symbol rotation",
+        action: Action::ToggleRotation,
+    },
+    Binding {
+        keys: &[KeyCode::Char('>'), KeyCode::Char('.')],
+        label: ">",
+        description: "Rotate symbols more slowly",
+        action: Action::SlowerRotation,
+    },
+    Binding {
+        keys: &[KeyCode::Char('<'), KeyCode::Char(',')],
+        label: "<",
+        description: "Rotate symbols faster",
+        action: Action::FasterRotation,
+    },
+    Binding {
+        keys: &[KeyCode::Char('s')],
+        label: "s",
+        description: "Replay: pause and step one update",
+        action: Action::StepReplay,
+    },
+    Binding {
+        keys: &[KeyCode::Char('f')],
+        label: "f",
+        description: "Replay: play faster (up to 10x)",
+        action: Action::FasterReplay,
+    },
+    Binding {
+        keys: &[KeyCode::Char('d')],
+        label: "d",
+        description: "Replay: play slower (down to 0.25x)",
+        action: Action::SlowerReplay,
+    },
+    Binding {
+        keys: &[KeyCode::Char('t')],
+        label: "t",
+        description: "Tune the human threshold live (nothing is stored meanwhile)",
+        action: Action::ToggleTuning,
+    },
+    Binding {
+        keys: &[KeyCode::Left],
+        label: "left",
+        description: "Lower the threshold by 0.01 while tuning",
+        action: Action::LowerThreshold,
+    },
+    Binding {
+        keys: &[KeyCode::Right],
+        label: "right",
+        description: "Raise the threshold by 0.01 while tuning",
+        action: Action::RaiseThreshold,
+    },
+    Binding {
+        keys: &[KeyCode::Char('m')],
+        label: "m",
+        description: "Mark this moment with a note (enter saves, esc cancels)",
+        action: Action::Annotate,
+    },
+    Binding {
+        keys: &[KeyCode::Char('?')],
+        label: "?",
+        description: "Show / hide this help",
+        action: Action::ToggleHelp,
+    },
+];
+
+pub fn action_for(code: KeyCode) -> Option<Action> {
+    BINDINGS
+        .iter()
+        .find(|binding| binding.keys.contains(&code))
+        .map(|binding| binding.action)
+}
@@ -1,22 +1,324 @@
+pub mod calibration;
+pub mod capture;
+pub mod clock;
+pub mod config;
 pub mod db;
+pub mod depth_chart;
+pub mod error;
+pub mod exchange;
+pub mod file_store;
+pub mod fingerprints;
+pub mod recording;
+pub mod session;
+pub mod symbols;
+pub mod telemetry;
+pub mod watchdog;
 
-use std::collections::HashMap;
+pub use error::AppError;
+
+use clock::{Clock, SystemClock};
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
 
 pub const SYMBOLS: &[&str] = &["btcusdt", "ethusdt", "bnbusdt", "xrpusdt"];
+const LATENCY_WINDOW: Duration = Duration::from_secs(30);
+const LATENCY_DB_INTERVAL: Duration = Duration::from_secs(60);
+const RATIO_SAMPLE_CAPACITY: usize = 3000;
+const WRITE_BUFFER_CAPACITY: usize = 10_000;
+const TAPE_CAPACITY: usize = 500;
+pub const MAX_PENDING_UPDATES: usize = 10_000; // per symbol; past this a fresh snapshot is fetched instead
+const LIQUIDATION_CAPACITY: usize = 200;
+pub const LIQUIDATION_MARK_FOR: Duration = Duration::from_secs(60);
+pub const DEFAULT_DISPLAY_LEVELS: usize = 10;
+pub const DEFAULT_MAX_INDICATORS: usize = 8;
+pub const MAX_DISPLAY_LEVELS: usize = 100;
+pub const MAX_PERSISTED_HISTORY: usize = 5000;
+pub const FEED_STALE_AFTER: Duration = Duration::from_secs(10);
+pub const UI_STALL_SHOWN_FOR: Duration = Duration::from_secs(30);
+pub const DEFAULT_LATEST_CACHE_TTL: Duration = Duration::from_secs(1);
+pub const ANOMALY_PERCENTILE: f64 = 95.0;
+pub const ANOMALY_MIN_SAMPLES: usize = 30;
+const WINDOW_RATIO_CAPACITY: usize = 720; // one hour of 5 second windows
+pub const DEFAULT_MOVERS_WINDOWS: usize = 6;
+pub const DEFAULT_IMBALANCE_WINDOWS: usize = 60;
+pub const DEFAULT_NEAR_LEVELS: usize = 5; // levels per side counted as near the touch
+pub const RATIO_TREND_WINDOW: Duration = Duration::from_secs(300);
+pub const RATIO_TREND_FLAT: f64 = 0.005; // slopes within ±0.5 points/minute read as flat
+pub const DEFAULT_HUMAN_THRESHOLD: f64 = 0.6;
+pub const THRESHOLD_STEP: f64 = 0.01;
+pub const DEFAULT_ALERT_HUMAN_ORDERS: usize = 20;
+pub const DEFAULT_ALERT_COOLDOWN: Duration = Duration::from_secs(30);
+pub const ALERT_FLASH_FOR: Duration = Duration::from_secs(2);
+pub const MIN_ROTATE_INTERVAL: Duration = Duration::from_secs(1);
+pub const ANALYSIS_WRITE_INTERVAL: Duration = Duration::from_secs(5);
+// Bot-dominated (red) through mixed (amber) to human-dominated (green)
+pub const DEFAULT_RATIO_PALETTE: &[(u8, u8, u8)] = &[(220, 50, 47), (230, 180, 30), (80, 200, 80)];
+
+// Book panel indicators; each is 1-2 display columns so rows stay aligned
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkerSet {
+    pub human: String,
+    pub bot: String,
+    pub uncertain: String,
+    pub bid: String,
+    pub ask: String,
+}
+
+impl Default for MarkerSet {
+    fn default() -> Self {
+        MarkerSet {
+            human: "🧑".to_string(),
+            bot: "🤖".to_string(),
+            uncertain: "❔".to_string(),
+            bid: "▲".to_string(),
+            ask: "▼".to_string(),
+        }
+    }
+}
+
+impl MarkerSet {
+    // Used in place of the defaults when rendering without color
+    pub fn ascii() -> Self {
+        MarkerSet {
+            human: "H".to_string(),
+            bot: "B".to_string(),
+            uncertain: "?".to_string(),
+            bid: "^".to_string(),
+            ask: "v".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum ConfidenceBucket {
+    LikelyHuman,
+    Uncertain,
+    LikelyBot,
+}
+
+// Scores above `human` are likely human, below `bot` likely bot, anything between uncertain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfidenceCutoffs {
+    pub human: f64,
+    pub bot: f64,
+}
+
+impl Default for ConfidenceCutoffs {
+    fn default() -> Self {
+        ConfidenceCutoffs {
+            human: 0.7,
+            bot: 0.3,
+        }
+    }
+}
+
+impl ConfidenceCutoffs {
+    pub fn classify(&self, score: f64) -> ConfidenceBucket {
+        if score > self.human {
+            ConfidenceBucket::LikelyHuman
+        } else if score < self.bot {
+            ConfidenceBucket::LikelyBot
+        } else {
+            ConfidenceBucket::Uncertain
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct BucketCounts {
+    pub likely_human: usize,
+    pub uncertain: usize,
+    pub likely_bot: usize,
+}
+
+impl BucketCounts {
+    pub fn add(&mut self, bucket: ConfidenceBucket) {
+        match bucket {
+            ConfidenceBucket::LikelyHuman => self.likely_human += 1,
+            ConfidenceBucket::Uncertain => self.uncertain += 1,
+            ConfidenceBucket::LikelyBot => self.likely_bot += 1,
+        }
+    }
+}
+
+pub fn marker_width(marker: &str) -> usize {
+    unicode_width::UnicodeWidthStr::width(marker)
+}
 
 #[derive(Debug, Clone)]
 pub struct OrderBookEntry {
     pub price: String,
     pub quantity: String,
-    pub total: f64,
     pub is_likely_human: bool,
     pub human_indicators: Vec<String>,
 }
 
-#[derive(Debug, Clone)]
+impl OrderBookEntry {
+    // Notional, always derived from the current price and quantity so it can't go stale
+    pub fn total(&self) -> f64 {
+        self.price.parse::<f64>().unwrap_or(0.0) * self.quantity.parse::<f64>().unwrap_or(0.0)
+    }
+
+    // Adds `indicator` as the most recent one, keeping at most `cap` distinct indicators;
+    // a repeat moves to the end instead of being stored twice
+    pub fn add_indicator(&mut self, indicator: &str, cap: usize) {
+        self.human_indicators
+            .retain(|existing| existing != indicator);
+        self.human_indicators.push(indicator.to_string());
+        let excess = self.human_indicators.len().saturating_sub(cap);
+        self.human_indicators.drain(..excess);
+    }
+}
+
+// What to do with several levels at the same price in one payload
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    #[default]
+    KeepLast,
+    Sum,
+}
+
+impl std::str::FromStr for DuplicatePolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "keep-last" | "last" => Ok(DuplicatePolicy::KeepLast),
+            "sum" => Ok(DuplicatePolicy::Sum),
+            other => Err(format!("expected keep-last or sum, got {:?}", other)),
+        }
+    }
+}
+
+fn decimals(value: &str) -> usize {
+    value
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len())
+}
+
+// Levels in payload order with duplicate prices merged, plus how many rows were merged away
+fn parse_levels(
+    levels: Option<&serde_json::Value>,
+    min_notional: f64,
+    duplicates: DuplicatePolicy,
+) -> (Vec<OrderBookEntry>, usize) {
+    let mut entries: Vec<OrderBookEntry> = Vec::new();
+    let mut by_price: HashMap<u64, usize> = HashMap::new();
+    let mut merged = 0;
+    if let Some(levels) = levels.and_then(|l| l.as_array()) {
+        for level in levels {
+            if let (Some(price), Some(quantity)) = (level[0].as_str(), level[1].as_str()) {
+                let price_value = price.parse::<f64>().unwrap_or(0.0);
+                let entry = OrderBookEntry {
+                    price: price.to_string(),
+                    quantity: quantity.to_string(),
+                    is_likely_human: false, // Will be updated by analysis
+                    human_indicators: Vec::new(),
+                };
+                // Keyed by parsed price so "100.0" and "100.00" count as the same level
+                let Some(&index) = by_price.get(&price_value.to_bits()) else {
+                    by_price.insert(price_value.to_bits(), entries.len());
+                    entries.push(entry);
+                    continue;
+                };
+                merged += 1;
+                let existing = &mut entries[index];
+                match duplicates {
+                    DuplicatePolicy::KeepLast => *existing = entry,
+                    DuplicatePolicy::Sum => {
+                        let sum = existing.quantity.parse::<f64>().unwrap_or(0.0)
+                            + entry.quantity.parse::<f64>().unwrap_or(0.0);
+                        let places = decimals(&existing.quantity).max(decimals(&entry.quantity));
+                        existing.quantity = format!("{:.*}", places, sum);
+                    }
+                }
+            }
+        }
+    }
+    entries.retain(|entry| entry.total() >= min_notional);
+    (entries, merged)
+}
+
+// Least-squares slope of y over x; 0.0 for fewer than two distinct x values
+pub fn linear_slope(points: &[(f64, f64)]) -> f64 {
+    let n = points.len() as f64;
+    if points.len() < 2 {
+        return 0.0;
+    }
+    let mean_x = points.iter().map(|&(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|&(_, y)| y).sum::<f64>() / n;
+    let (covariance, variance) = points.iter().fold((0.0, 0.0), |(cov, var), &(x, y)| {
+        (
+            cov + (x - mean_x) * (y - mean_y),
+            var + (x - mean_x).powi(2),
+        )
+    });
+    if variance == 0.0 {
+        0.0
+    } else {
+        covariance / variance
+    }
+}
+
+// True once `failures` consecutive attempts have failed and `max` allows no more
+pub fn reconnect_limit_reached(failures: u32, max: Option<u32>) -> bool {
+    max.is_some_and(|max| failures >= max)
+}
+
+// base * 1.5^attempts, clamped to `max` in f64 so a long outage can't overflow a Duration
+pub fn reconnect_delay(base: Duration, attempts: u32, max: Duration) -> Duration {
+    let secs = base.as_secs_f64() * 1.5f64.powi(attempts.min(i32::MAX as u32) as i32);
+    if secs.is_finite() && secs < max.as_secs_f64() {
+        Duration::from_secs_f64(secs)
+    } else {
+        max
+    }
+}
+
+// Normalizes a diff depth event, bare or inside a combined-stream {"stream", "data"}
+// envelope, to {"symbol", "bids", "asks", "lastUpdateId", "eventTime"}. Returns None for
+// other event types and for payloads missing the symbol or either side.
+pub fn transform_depth_message(raw: &serde_json::Value) -> Option<serde_json::Value> {
+    let data = raw.get("data").unwrap_or(raw);
+    if let Some(event) = data.get("e") {
+        if event.as_str() != Some("depthUpdate") {
+            return None;
+        }
+    }
+    let symbol = data.get("s")?.as_str().filter(|s| !s.is_empty())?;
+    let bids = data.get("b").filter(|b| b.is_array())?;
+    let asks = data.get("a").filter(|a| a.is_array())?;
+
+    Some(serde_json::json!({
+        "symbol": symbol.to_uppercase(),
+        "bids": bids,
+        "asks": asks,
+        "lastUpdateId": data["u"],
+        "eventTime": data["E"]
+    }))
+}
+
+// The feed task's last message before it stops, e.g. {"fatal": "giving up after 10 ..."}
+pub fn fatal_error(message: &serde_json::Value) -> Option<&str> {
+    message.get("fatal").and_then(|e| e.as_str())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderBookMessage {
+    #[serde(skip, default = "Instant::now")]
     pub timestamp: Instant,
+    pub wall_time: u64, // unix ms, survives a save/reload unlike `timestamp`
     pub symbol: String,
     pub is_human: bool,
     pub price: String,
@@ -24,350 +326,3067 @@ pub struct OrderBookMessage {
     pub side: OrderSide,
 }
 
-#[derive(Debug, Clone)]
+impl OrderBookMessage {
+    // Times come from the app clock: `now` for age checks, `wall_time` in unix ms
+    pub fn new(
+        symbol: &str,
+        entry: &OrderBookEntry,
+        side: OrderSide,
+        now: Instant,
+        wall_time: u64,
+    ) -> Self {
+        OrderBookMessage {
+            timestamp: now,
+            wall_time,
+            symbol: symbol.to_string(),
+            is_human: entry.is_likely_human,
+            price: entry.price.clone(),
+            quantity: entry.quantity.clone(),
+            side,
+        }
+    }
+}
+
+// Serialized as "BID"/"ASK"; the older "Bid"/"Ask" spellings are still accepted on load
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
 pub enum OrderSide {
+    #[serde(alias = "Bid")]
     Bid,
+    #[serde(alias = "Ask")]
     Ask,
 }
 
-pub struct OrderBook {
-    pub bids: Vec<OrderBookEntry>,
-    pub asks: Vec<OrderBookEntry>,
-    pub last_update: Instant,
-    pub persistent_orders: HashMap<String, OrderBookEntry>,
+impl fmt::Display for OrderSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OrderSide::Bid => "BID",
+            OrderSide::Ask => "ASK",
+        })
+    }
 }
 
-pub struct App {
-    pub order_books: HashMap<String, OrderBook>,
-    pub current_symbol: String,
-    pub message_history: Vec<OrderBookMessage>,
-    pub db: db::Database,
-    pub last_db_write: Instant,
-    analysis_buffer: HashMap<String, Vec<(Instant, usize, usize)>>, // (timestamp, total_orders, human_orders) per symbol
+pub const DEFAULT_FILL_WINDOW_BPS: f64 = 5.0;
+
+// Aggressive orders consume liquidity from the top of the book, so a quantity drop within
+// `max_distance_bps` of the best price on its side is read as a fill; deeper drops are
+// read as cancels. Zero only accepts drops at the best price itself.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FillInference {
+    pub max_distance_bps: f64,
 }
 
-pub struct MarketAnalysis {
-    pub total_orders: usize,
-    pub likely_human_orders: usize,
-    pub bot_patterns: Vec<String>,
-    pub human_patterns: Vec<String>,
-    pub confidence_scores: HashMap<String, f64>,
+impl Default for FillInference {
+    fn default() -> Self {
+        FillInference {
+            max_distance_bps: DEFAULT_FILL_WINDOW_BPS,
+        }
+    }
 }
 
-impl App {
-    pub fn new() -> Result<App, Box<dyn std::error::Error>> {
-        let db = db::Database::new()?;
+impl FillInference {
+    // `best` is the best price on `side` before the update
+    pub fn is_fill(&self, side: OrderSide, best: f64, price: f64) -> bool {
+        let distance = match side {
+            OrderSide::Bid => best - price,
+            OrderSide::Ask => price - best,
+        };
+        distance <= best.abs() * self.max_distance_bps / 10_000.0
+    }
 
-        Ok(App {
-            order_books: crate::SYMBOLS
-                .iter()
-                .map(|&symbol| {
-                    (
-                        symbol.to_uppercase(),
-                        OrderBook {
-                            bids: Vec::new(),
-                            asks: Vec::new(),
-                            last_update: Instant::now(),
-                            persistent_orders: HashMap::new(),
-                        },
-                    )
-                })
-                .collect(),
-            current_symbol: "BTCUSDT".to_string(),
-            message_history: Vec::with_capacity(10000),
-            db,
-            last_db_write: Instant::now(),
-            analysis_buffer: HashMap::new(),
-        })
+    // A shrinking bid was hit by a seller, a shrinking ask was lifted by a buyer
+    pub fn aggressor(&self, side: OrderSide) -> TradeSide {
+        match side {
+            OrderSide::Bid => TradeSide::Sell,
+            OrderSide::Ask => TradeSide::Buy,
+        }
     }
+}
 
-    fn update_analysis_buffer(&mut self, symbol: &str, total_orders: usize, human_orders: usize) {
-        let now = Instant::now();
-        let buffer = self.analysis_buffer.entry(symbol.to_string()).or_default();
+// Aggressor side of a trade: buyers lift asks, sellers hit bids
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
 
-        // Add new data point
-        buffer.push((now, total_orders, human_orders));
+#[derive(Debug, Clone)]
+pub struct Trade {
+    pub timestamp: Instant,
+    pub symbol: String,
+    pub price: f64,
+    pub quantity: f64,
+    pub side: TradeSide,
+    pub inferred: bool, // derived from a book change rather than reported by the exchange
+    pub trade_time: Option<i64>, // exchange trade time in ms, when reported
+}
 
-        // Remove data points older than 5 seconds
-        buffer.retain(|(timestamp, _, _)| timestamp.elapsed() < Duration::from_secs(5));
+impl Trade {
+    // Parses an aggTrade event; the buyer being the maker means the seller was the aggressor
+    pub fn from_agg_trade(data: &serde_json::Value) -> Option<Trade> {
+        let buyer_is_maker = data.get("m")?.as_bool()?;
+        Some(Trade {
+            timestamp: Instant::now(),
+            symbol: data.get("s")?.as_str()?.to_uppercase(),
+            price: data.get("p")?.as_str()?.parse().ok()?,
+            quantity: data.get("q")?.as_str()?.parse().ok()?,
+            side: if buyer_is_maker {
+                TradeSide::Sell
+            } else {
+                TradeSide::Buy
+            },
+            inferred: false,
+            trade_time: data.get("T").and_then(|t| t.as_i64()),
+        })
     }
+}
 
-    fn calculate_average_analysis(&self, symbol: &str) -> Option<(f64, f64)> {
-        if let Some(buffer) = self.analysis_buffer.get(symbol) {
-            if buffer.is_empty() {
-                return None;
-            }
+// A forced liquidation from the futures !forceOrder stream
+#[derive(Debug, Clone)]
+pub struct Liquidation {
+    pub timestamp: Instant,
+    pub symbol: String,
+    pub side: TradeSide, // side of the liquidation order: SELL closes a long into the bids
+    pub price: f64,
+    pub quantity: f64,
+    pub trade_time: Option<i64>, // exchange trade time in ms, when reported
+}
+
+impl Liquidation {
+    // Parses a forceOrder event; the average fill price is used once the order has filled
+    pub fn from_force_order(data: &serde_json::Value) -> Option<Liquidation> {
+        let order = data.get("o")?;
+        let field = |key: &str| order.get(key)?.as_str()?.parse::<f64>().ok();
+        let price = field("ap").filter(|&p| p > 0.0).or_else(|| field("p"))?;
+        Some(Liquidation {
+            timestamp: Instant::now(),
+            symbol: order.get("s")?.as_str()?.to_uppercase(),
+            side: match order.get("S")?.as_str()? {
+                "BUY" => TradeSide::Buy,
+                "SELL" => TradeSide::Sell,
+                _ => return None,
+            },
+            price,
+            quantity: field("q")?,
+            trade_time: order.get("T").and_then(|t| t.as_i64()),
+        })
+    }
+}
+
+// Fields the tape panel can show, selected by name in --tape-columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TapeField {
+    Time,
+    Symbol,
+    Type,
+    Side,
+    Price,
+    Quantity,
+    Total,
+}
 
-            let total_sum: usize = buffer.iter().map(|(_, total, _)| total).sum();
-            let human_sum: usize = buffer.iter().map(|(_, _, human)| human).sum();
-            let count = buffer.len();
+impl TapeField {
+    pub const ALL: &'static [(&'static str, TapeField)] = &[
+        ("time", TapeField::Time),
+        ("symbol", TapeField::Symbol),
+        ("type", TapeField::Type),
+        ("side", TapeField::Side),
+        ("price", TapeField::Price),
+        ("quantity", TapeField::Quantity),
+        ("total", TapeField::Total),
+    ];
 
-            Some((
-                total_sum as f64 / count as f64,
-                human_sum as f64 / count as f64,
-            ))
-        } else {
-            None
+    pub fn title(self) -> &'static str {
+        match self {
+            TapeField::Time => "Time",
+            TapeField::Symbol => "Symbol",
+            TapeField::Type => "Type",
+            TapeField::Side => "Side",
+            TapeField::Price => "Price",
+            TapeField::Quantity => "Qty",
+            TapeField::Total => "Total",
         }
     }
 
-    pub fn analyze_market(&mut self) -> MarketAnalysis {
-        let current_symbol = self.current_symbol.clone();
-        let analysis = if let Some(order_book) = self.order_books.get(&current_symbol) {
-            let round_numbers = self.analyze_round_numbers();
-            let order_sizes = self.analyze_order_sizes();
-            let order_placement = self.analyze_order_placement();
+    pub fn format(self, trade: &Trade) -> String {
+        match self {
+            TapeField::Time => format!("{:.0}s", trade.timestamp.elapsed().as_secs_f64()),
+            TapeField::Symbol => trade.symbol.clone(),
+            TapeField::Type => if trade.inferred { "INF" } else { "TRD" }.to_string(),
+            TapeField::Side => match trade.side {
+                TradeSide::Buy => "BUY".to_string(),
+                TradeSide::Sell => "SELL".to_string(),
+            },
+            TapeField::Price => trade.price.to_string(),
+            TapeField::Quantity => format!("{:.4}", trade.quantity),
+            TapeField::Total => format!("{:.2}", trade.price * trade.quantity),
+        }
+    }
+}
 
-            let mut confidence_scores = HashMap::new();
-            let mut human_patterns = Vec::new();
-            let mut bot_patterns = Vec::new();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TapeColumn {
+    pub field: TapeField,
+    pub width: usize,
+}
 
-            // Combine analyses
-            for (price, indicators) in round_numbers
-                .iter()
-                .zip(order_sizes.iter())
-                .zip(order_placement.iter())
-                .map(|((a, b), c)| (a.0.clone(), vec![a.1, b.1, c.1]))
-            {
-                let human_score =
-                    indicators.iter().filter(|&&x| x).count() as f64 / indicators.len() as f64;
+impl TapeColumn {
+    // Right-aligned and cut to the column width so rows stay aligned
+    pub fn cell(&self, trade: &Trade) -> String {
+        let text = self.field.format(trade);
+        let text: String = text.chars().take(self.width).collect();
+        format!("{:>width$}", text, width = self.width)
+    }
+}
 
-                confidence_scores.insert(price.clone(), human_score);
+// "field:width", e.g. "price:10"; the width defaults to 8 when omitted
+impl std::str::FromStr for TapeColumn {
+    type Err = String;
 
-                if human_score > 0.6 {
-                    human_patterns.push(format!("Order at {} shows human behavior", price));
-                } else {
-                    bot_patterns.push(format!("Order at {} likely automated", price));
-                }
-            }
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, width) = match value.split_once(':') {
+            Some((name, width)) => (
+                name,
+                width
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&w| w > 0)
+                    .ok_or_else(|| format!("invalid width in {:?}", value))?,
+            ),
+            None => (value, 8),
+        };
+        let field = TapeField::ALL
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name.trim()))
+            .map(|&(_, field)| field)
+            .ok_or_else(|| {
+                let names: Vec<&str> = TapeField::ALL.iter().map(|&(n, _)| n).collect();
+                format!(
+                    "unknown tape field {:?}, expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })?;
+        Ok(TapeColumn { field, width })
+    }
+}
 
-            let likely_human_orders = confidence_scores
-                .values()
-                .filter(|&&score| score > 0.6)
-                .count();
+pub fn default_tape_columns() -> Vec<TapeColumn> {
+    vec![
+        TapeColumn {
+            field: TapeField::Time,
+            width: 5,
+        },
+        TapeColumn {
+            field: TapeField::Side,
+            width: 4,
+        },
+        TapeColumn {
+            field: TapeField::Quantity,
+            width: 10,
+        },
+        TapeColumn {
+            field: TapeField::Price,
+            width: 10,
+        },
+    ]
+}
 
-            let total_orders = order_book.bids.len() + order_book.asks.len();
+const MM_FOOTPRINT_LEVELS: usize = 5;
+pub const MM_FOOTPRINT_THRESHOLD: f64 = 0.8; // adds a bot pattern line, nothing more
 
-            // Update the analysis buffer
-            self.update_analysis_buffer(&current_symbol, total_orders, likely_human_orders);
+// Levels kept per side when diffs are applied, unless a snapshot was deeper
+pub const DEFAULT_BOOK_DEPTH: usize = 20;
+const MAX_BOOK_LEVELS: usize = 5000; // per side; levels further out only cost memory
 
-            // Write to database every 5 seconds using averaged data
-            if self.last_db_write.elapsed() >= Duration::from_secs(5) {
-                if let Some((avg_total, avg_human)) =
-                    self.calculate_average_analysis(&current_symbol)
-                {
-                    let record = db::MarketAnalysisRecord {
-                        symbol: current_symbol.clone(),
-                        timestamp: db::get_current_timestamp(),
-                        total_orders: avg_total as i64,
-                        human_orders: avg_human as i64,
-                        bot_orders: (avg_total - avg_human) as i64,
-                        human_ratio: if avg_total > 0.0 {
-                            avg_human / avg_total
-                        } else {
-                            0.0
-                        },
-                    };
+const UPDATE_BASELINE_WINDOW: Duration = Duration::from_secs(60);
+const MIN_BURST_RATE: f64 = 5.0; // updates/s; below this no rate counts as a burst
+pub const DEFAULT_BURST_MULTIPLIER: f64 = 3.0;
 
-                    if let Err(e) = self.db.insert_analysis(&record) {
-                        eprintln!("Failed to store market analysis: {}", e);
-                    }
-                    self.last_db_write = Instant::now();
-                }
-            }
+const ICEBERG_WINDOW: Duration = Duration::from_secs(60);
+const ICEBERG_MIN_REFILLS: usize = 2;
+pub const ICEBERG_INDICATOR: &str = "iceberg?";
+pub const LIQUIDATION_INDICATOR: &str = "liquidation";
 
-            MarketAnalysis {
-                total_orders,
-                likely_human_orders,
-                bot_patterns,
-                human_patterns,
-                confidence_scores,
-            }
-        } else {
-            MarketAnalysis::default()
-        };
+const SPOOF_PERCENTILE: f64 = 95.0; // of level notional across both sides
+const SPOOF_MIN_LEVELS: usize = 10; // fewer levels than this make the percentile meaningless
+const SPOOF_MAX_LIFETIME: Duration = Duration::from_secs(10);
+const SPOOF_WINDOW: Duration = Duration::from_secs(60);
+pub const SPOOF_INDICATOR: &str = "spoof?";
 
-        analysis
-    }
+// A top-percentile level posted away from the touch, watched until it is pulled or settles
+#[derive(Debug, Clone)]
+struct LargeLevel {
+    side: OrderSide,
+    appeared: Instant,
+    notional: f64,
+}
 
-    fn analyze_round_numbers(&self) -> Vec<(String, bool)> {
-        let mut results = Vec::new();
-        if let Some(order_book) = self.order_books.get(&self.current_symbol) {
-            for order in order_book.bids.iter().chain(order_book.asks.iter()) {
-                if let Ok(price) = order.price.parse::<f64>() {
-                    let decimal_part = price.fract();
-                    let whole_part = price.trunc();
+// Quantity history of one price level, for spotting refills back to the same size
+#[derive(Debug, Clone)]
+struct LevelTrack {
+    peak: f64,
+    depleted: bool,
+    refills: Vec<Instant>,
+    last_seen: Instant,
+}
+
+// One level named in a diff event, with its quantity before and after; 0.0 means absent
+#[derive(Debug, Clone)]
+struct LevelUpdate {
+    side: OrderSide,
+    price: String,
+    before: f64,
+    after: f64,
+}
 
-                    let is_round =
-                        decimal_part == 0.0 || decimal_part == 0.5 || decimal_part == 0.25;
-                    let is_psychological = whole_part % 1000.0 == 0.0 || // e.g., 50000
-                        whole_part % 500.0 == 0.0 ||  // e.g., 49500
-                        whole_part % 100.0 == 0.0; // e.g., 49100
+// A level present on both ticks whose quantity changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelChange {
+    pub price: String,
+    pub before: String,
+    pub after: String,
+}
 
-                    results.push((order.price.clone(), is_round || is_psychological));
-                }
-            }
-        }
-        results
+#[derive(Debug, Clone, Default)]
+pub struct SideDiff {
+    pub added: Vec<OrderBookEntry>,
+    pub removed: Vec<OrderBookEntry>,
+    pub modified: Vec<LevelChange>,
+}
+
+impl SideDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
     }
+}
 
-    fn analyze_order_sizes(&self) -> Vec<(String, bool)> {
-        let mut results = Vec::new();
-        if let Some(order_book) = self.order_books.get(&self.current_symbol) {
-            for order in order_book.bids.iter().chain(order_book.asks.iter()) {
-                if let Ok(quantity) = order.quantity.parse::<f64>() {
-                    let whole_part = quantity.trunc();
-                    let decimal_part = quantity.fract();
+#[derive(Debug, Clone, Default)]
+pub struct BookDiff {
+    pub bids: SideDiff,
+    pub asks: SideDiff,
+}
+
+impl BookDiff {
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+}
 
-                    let is_human_like = decimal_part == 0.0 ||  // Whole numbers
-                        decimal_part == 0.5 ||  // Half units
-                        decimal_part == 0.25 || // Quarter units
-                        whole_part <= 10.0 ||   // Small round numbers
-                        whole_part % 5.0 == 0.0; // Multiples of 5
+// Levels are matched on numeric price, so "100.0" and "100.00" are the same level
+fn diff_levels(current: &[OrderBookEntry], previous: &[OrderBookEntry]) -> SideDiff {
+    let price_key = |entry: &OrderBookEntry| entry.price.parse::<f64>().ok().map(f64::to_bits);
+    let quantity = |entry: &OrderBookEntry| entry.quantity.parse::<f64>().unwrap_or(0.0);
+    let before: HashMap<u64, &OrderBookEntry> = previous
+        .iter()
+        .filter_map(|entry| Some((price_key(entry)?, entry)))
+        .collect();
+    let after: HashSet<u64> = current.iter().filter_map(price_key).collect();
 
-                    results.push((order.quantity.clone(), is_human_like));
-                }
-            }
+    let mut diff = SideDiff::default();
+    for entry in current {
+        let Some(key) = price_key(entry) else {
+            continue;
+        };
+        match before.get(&key) {
+            None => diff.added.push(entry.clone()),
+            Some(old) if quantity(old) != quantity(entry) => diff.modified.push(LevelChange {
+                price: entry.price.clone(),
+                before: old.quantity.clone(),
+                after: entry.quantity.clone(),
+            }),
+            Some(_) => {}
         }
-        results
     }
+    diff.removed = previous
+        .iter()
+        .filter(|entry| price_key(entry).is_some_and(|key| !after.contains(&key)))
+        .cloned()
+        .collect();
+    diff
+}
 
-    fn analyze_order_placement(&self) -> Vec<(String, bool)> {
-        let mut results = Vec::new();
-        if let Some(order_book) = self.order_books.get(&self.current_symbol) {
-            for orders in [&order_book.bids, &order_book.asks] {
-                for window in orders.windows(2) {
-                    if let (Ok(price1), Ok(price2)) = (
-                        window[0].price.parse::<f64>(),
-                        window[1].price.parse::<f64>(),
-                    ) {
-                        let diff = (price2 - price1).abs();
-                        let is_human_like = diff > 0.01 && // Not too precise
-                            diff.fract() != 0.0 && // Not perfectly spaced
-                            diff % 0.1 != 0.0; // Not aligned to common intervals
-
-                        results.push((window[0].price.clone(), is_human_like));
-                    }
-                }
-            }
+pub struct OrderBook {
+    pub bids: Vec<OrderBookEntry>, // top `depth` levels of the full book, best first
+    pub asks: Vec<OrderBookEntry>,
+    full_bids: Vec<OrderBookEntry>, // every level maintained from snapshots and diffs
+    full_asks: Vec<OrderBookEntry>,
+    previous_bids: Vec<OrderBookEntry>, // levels before the latest update
+    previous_asks: Vec<OrderBookEntry>,
+    pub last_update: Instant,
+    pub persistent_orders: HashMap<String, OrderBookEntry>,
+    level_tracks: HashMap<String, LevelTrack>, // keyed by price
+    pub depth: usize,                          // levels per side shown in bids/asks
+    update_times: VecDeque<Instant>,           // within UPDATE_BASELINE_WINDOW, oldest first
+    large_levels: HashMap<String, LargeLevel>, // keyed by price, see track_spoofs
+    spoofs: VecDeque<(String, Instant)>,       // (price, when pulled) within SPOOF_WINDOW
+    new_spoofs: usize,                         // pulled by the latest update
+    pub max_indicators: usize,                 // per level, see OrderBookEntry::add_indicator
+}
+
+impl OrderBook {
+    pub fn new() -> OrderBook {
+        OrderBook {
+            bids: Vec::new(),
+            asks: Vec::new(),
+            full_bids: Vec::new(),
+            full_asks: Vec::new(),
+            previous_bids: Vec::new(),
+            previous_asks: Vec::new(),
+            last_update: Instant::now(),
+            persistent_orders: HashMap::new(),
+            level_tracks: HashMap::new(),
+            depth: usize::MAX,
+            update_times: VecDeque::new(),
+            large_levels: HashMap::new(),
+            spoofs: VecDeque::new(),
+            new_spoofs: 0,
+            max_indicators: DEFAULT_MAX_INDICATORS,
         }
-        results
     }
 
-    pub fn next_symbol(&mut self) {
-        let symbols: Vec<_> = self.order_books.keys().cloned().collect();
-        if let Some(pos) = symbols.iter().position(|s| s == &self.current_symbol) {
-            self.current_symbol = symbols[(pos + 1) % symbols.len()].clone();
+    // Builds a book from a Binance depth payload ({"bids": [[price, qty], ...], "asks": ...})
+    pub fn from_json(value: &serde_json::Value) -> OrderBook {
+        let mut order_book = OrderBook::new();
+        order_book.replace_levels(value, 0.0, DuplicatePolicy::default(), Instant::now());
+        order_book
+    }
+
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> Result<OrderBook, AppError> {
+        let contents = fs::read_to_string(path)?;
+        let value: serde_json::Value = serde_json::from_str(&contents)?;
+        Ok(OrderBook::from_json(&value))
+    }
+
+    // Applies a depth message: one with an eventTime is a diff event from the stream,
+    // anything else is a full snapshot. `now` comes from the caller's clock and becomes the
+    // book's last_update
+    pub fn apply(
+        &mut self,
+        result: &serde_json::Value,
+        min_notional: f64,
+        duplicates: DuplicatePolicy,
+        now: Instant,
+    ) -> usize {
+        if result.get("eventTime").is_some() {
+            self.apply_diff(result, min_notional, duplicates, now)
+        } else {
+            self.replace_levels(result, min_notional, duplicates, now)
         }
     }
 
-    pub fn update_orders(&mut self, result: &serde_json::Value) {
-        if let Some(symbol) = result.get("symbol").and_then(|s| s.as_str()) {
-            if let Some(order_book) = self.order_books.get_mut(symbol) {
-                // Clear existing orders
-                order_book.bids.clear();
-                order_book.asks.clear();
-
-                // Process bids
-                if let Some(bids) = result.get("bids").and_then(|b| b.as_array()) {
-                    for bid in bids {
-                        if let (Some(price), Some(quantity)) = (bid[0].as_str(), bid[1].as_str()) {
-                            let total = price.parse::<f64>().unwrap_or(0.0)
-                                * quantity.parse::<f64>().unwrap_or(0.0);
-                            let entry = OrderBookEntry {
-                                price: price.to_string(),
-                                quantity: quantity.to_string(),
-                                total,
-                                is_likely_human: false, // Will be updated by analysis
-                                human_indicators: Vec::new(),
-                            };
-                            order_book.bids.push(entry);
-                        }
-                    }
-                }
+    // Replaces both sides with the levels in `result`, dropping levels below `min_notional`.
+    // `now` comes from the caller's clock and becomes the book's last_update.
+    // Returns how many duplicate price rows were merged
+    pub fn replace_levels(
+        &mut self,
+        result: &serde_json::Value,
+        min_notional: f64,
+        duplicates: DuplicatePolicy,
+        now: Instant,
+    ) -> usize {
+        let (bids, merged_bids) = parse_levels(result.get("bids"), min_notional, duplicates);
+        let (asks, merged_asks) = parse_levels(result.get("asks"), min_notional, duplicates);
+        self.full_bids = bids;
+        self.full_asks = asks;
+        self.sort_sides();
+        self.previous_bids = std::mem::take(&mut self.bids);
+        self.previous_asks = std::mem::take(&mut self.asks);
+        self.refresh_view();
 
-                // Process asks
-                if let Some(asks) = result.get("asks").and_then(|a| a.as_array()) {
-                    for ask in asks {
-                        if let (Some(price), Some(quantity)) = (ask[0].as_str(), ask[1].as_str()) {
-                            let total = price.parse::<f64>().unwrap_or(0.0)
-                                * quantity.parse::<f64>().unwrap_or(0.0);
-                            let entry = OrderBookEntry {
-                                price: price.to_string(),
-                                quantity: quantity.to_string(),
-                                total,
-                                is_likely_human: false, // Will be updated by analysis
-                                human_indicators: Vec::new(),
-                            };
-                            order_book.asks.push(entry);
-                        }
-                    }
-                }
+        // A snapshot says nothing about which levels were pulled or refilled since the last one
+        self.large_levels.clear();
+        self.level_tracks.clear();
+        self.new_spoofs = 0;
+        self.finish_update(now);
+        merged_bids + merged_asks
+    }
 
-                // Sort bids in descending order (highest price first)
-                order_book.bids.sort_by(|a, b| {
-                    b.price
-                        .parse::<f64>()
-                        .unwrap_or(0.0)
-                        .partial_cmp(&a.price.parse::<f64>().unwrap_or(0.0))
-                        .unwrap_or(std::cmp::Ordering::Equal)
-                });
+    // Applies a diff depth event: each listed level takes its new quantity, a quantity of 0
+    // (or a level under `min_notional`) removes it, and unlisted levels stay as they were.
+    // The full book is maintained; bids/asks then show its top `depth` levels.
+    // Returns how many duplicate price rows were merged
+    pub fn apply_diff(
+        &mut self,
+        result: &serde_json::Value,
+        min_notional: f64,
+        duplicates: DuplicatePolicy,
+        now: Instant,
+    ) -> usize {
+        let (bids, merged_bids) = parse_levels(result.get("bids"), 0.0, duplicates);
+        let (asks, merged_asks) = parse_levels(result.get("asks"), 0.0, duplicates);
+        self.previous_bids = std::mem::take(&mut self.bids);
+        self.previous_asks = std::mem::take(&mut self.asks);
 
-                // Sort asks in ascending order (lowest price first)
-                order_book.asks.sort_by(|a, b| {
-                    a.price
-                        .parse::<f64>()
-                        .unwrap_or(0.0)
-                        .partial_cmp(&b.price.parse::<f64>().unwrap_or(0.0))
-                        .unwrap_or(std::cmp::Ordering::Equal)
+        let mut updates = Vec::new();
+        for (side, levels) in [(OrderSide::Bid, bids), (OrderSide::Ask, asks)] {
+            let book = match side {
+                OrderSide::Bid => &mut self.full_bids,
+                OrderSide::Ask => &mut self.full_asks,
+            };
+            for level in levels {
+                let price = level.price.parse::<f64>().unwrap_or(0.0);
+                // Both sides are kept sorted best first
+                let index = book.binary_search_by(|o| {
+                    let other = o.price.parse::<f64>().unwrap_or(0.0);
+                    match side {
+                        OrderSide::Bid => price.total_cmp(&other),
+                        OrderSide::Ask => other.total_cmp(&price),
+                    }
                 });
+                let before = index.map_or(0.0, |i| book[i].quantity.parse().unwrap_or(0.0));
+                let after = level.quantity.parse::<f64>().unwrap_or(0.0);
+                let keep = after > 0.0 && level.total() >= min_notional;
+                match (index, keep) {
+                    (Ok(i), true) => book[i] = level.clone(),
+                    (Ok(i), false) => {
+                        book.remove(i);
+                    }
+                    (Err(i), true) => book.insert(i, level.clone()),
+                    (Err(_), false) => {}
+                }
+                updates.push(LevelUpdate {
+                    side,
+                    price: level.price,
+                    before,
+                    after,
+                });
+            }
+        }
+        self.full_bids.truncate(MAX_BOOK_LEVELS);
+        self.full_asks.truncate(MAX_BOOK_LEVELS);
+        self.refresh_view();
 
-                // Update last update time
-                order_book.last_update = std::time::Instant::now();
-
-                // Add to message history
-                let side = if !order_book.bids.is_empty() {
-                    OrderSide::Bid
-                } else {
-                    OrderSide::Ask
-                };
+        self.track_spoofs(&updates, now);
+        self.track_refills(&updates, now);
+        self.finish_update(now);
+        merged_bids + merged_asks
+    }
 
-                let entry = if !order_book.bids.is_empty() {
-                    &order_book.bids[0]
-                } else if !order_book.asks.is_empty() {
-                    &order_book.asks[0]
-                } else {
-                    return;
-                };
+    fn sort_sides(&mut self) {
+        let price = |order: &OrderBookEntry| order.price.parse::<f64>().unwrap_or(0.0);
+        // Bids highest first, asks lowest first
+        self.full_bids.sort_by(|a, b| price(b).total_cmp(&price(a)));
+        self.full_asks.sort_by(|a, b| price(a).total_cmp(&price(b)));
+    }
 
-                let message = OrderBookMessage {
-                    timestamp: std::time::Instant::now(),
-                    symbol: symbol.to_string(),
-                    is_human: entry.is_likely_human,
-                    price: entry.price.clone(),
-                    quantity: entry.quantity.clone(),
-                    side,
-                };
+    // Fresh copies of the top levels, so indicators describe the latest update only
+    fn refresh_view(&mut self) {
+        self.bids = self.full_bids.iter().take(self.depth).cloned().collect();
+        self.asks = self.full_asks.iter().take(self.depth).cloned().collect();
+    }
 
-                self.message_history.push(message);
+    // Records the update time and marks iceberg and spoof candidates on the new levels
+    fn finish_update(&mut self, now: Instant) {
+        self.last_update = now;
+        self.update_times.push_back(self.last_update);
+        while self
+            .update_times
+            .front()
+            .is_some_and(|&t| self.last_update.duration_since(t) > UPDATE_BASELINE_WINDOW)
+        {
+            self.update_times.pop_front();
+        }
+        while self
+            .spoofs
+            .front()
+            .is_some_and(|&(_, at)| now.duration_since(at) >= SPOOF_WINDOW)
+        {
+            self.spoofs.pop_front();
+        }
 
-                // Keep message history size reasonable
-                if self.message_history.len() > 10000 {
-                    self.message_history.drain(0..5000);
+        let candidates = self.iceberg_candidates();
+        let spoofed = self.spoof_candidates();
+        for order in self.bids.iter_mut().chain(self.asks.iter_mut()) {
+            if candidates.contains(&order.price) {
+                order.add_indicator(ICEBERG_INDICATOR, self.max_indicators);
+            }
+            // A level reposted where a spoof was just pulled
+            if spoofed.contains(&order.price) {
+                order.add_indicator(SPOOF_INDICATOR, self.max_indicators);
+            }
+        }
+    }
+
+    // A large level that appears away from the touch and is explicitly removed (or cut to
+    // under half its size) by a diff within SPOOF_MAX_LIFETIME, while the price never
+    // reached it, counts as a pulled spoof. Levels a diff doesn't mention are still resting.
+    fn track_spoofs(&mut self, updates: &[LevelUpdate], now: Instant) {
+        let (best_bid, best_ask) = (self.best_bid(), self.best_ask());
+        self.new_spoofs = 0;
+        for update in updates {
+            let Some(level) = self.large_levels.get(&update.price) else {
+                continue;
+            };
+            let Ok(value) = update.price.parse::<f64>() else {
+                continue;
+            };
+            if update.after * value >= level.notional / 2.0 {
+                continue;
+            }
+            // A level traded through leaves the touch at or beyond its price
+            let untouched = match level.side {
+                OrderSide::Bid => best_bid.is_some_and(|bid| bid > value),
+                OrderSide::Ask => best_ask.is_some_and(|ask| ask < value),
+            };
+            if untouched && now.duration_since(level.appeared) <= SPOOF_MAX_LIFETIME {
+                self.spoofs.push_back((update.price.clone(), now));
+                self.new_spoofs += 1;
+            }
+            self.large_levels.remove(&update.price);
+        }
+        // Still resting after SPOOF_MAX_LIFETIME: real liquidity, stop watching
+        self.large_levels
+            .retain(|_, level| now.duration_since(level.appeared) <= SPOOF_MAX_LIFETIME);
+
+        // The percentile is taken over the whole book, not just the levels in this diff
+        let Some(threshold) = self.large_notional() else {
+            return;
+        };
+        for update in updates {
+            let Ok(value) = update.price.parse::<f64>() else {
+                continue;
+            };
+            // Only levels that just became large, skipping the touch itself
+            let at_touch = match update.side {
+                OrderSide::Bid => best_bid.is_none_or(|bid| value >= bid),
+                OrderSide::Ask => best_ask.is_none_or(|ask| value <= ask),
+            };
+            let notional = update.after * value;
+            if notional >= threshold && update.before * value < threshold && !at_touch {
+                self.large_levels
+                    .entry(update.price.clone())
+                    .or_insert(LargeLevel {
+                        side: update.side,
+                        appeared: now,
+                        notional,
+                    });
+            }
+        }
+    }
+
+    // Notional at SPOOF_PERCENTILE across both sides, None for a thin book
+    fn large_notional(&self) -> Option<f64> {
+        let mut notionals: Vec<f64> = self
+            .bids
+            .iter()
+            .chain(&self.asks)
+            .map(|o| o.total())
+            .collect();
+        if notionals.len() < SPOOF_MIN_LEVELS {
+            return None;
+        }
+        notionals.sort_by(f64::total_cmp);
+        let index = ((SPOOF_PERCENTILE / 100.0) * (notionals.len() - 1) as f64).round() as usize;
+        Some(notionals[index])
+    }
+
+    // Prices of large levels pulled before the price reached them within SPOOF_WINDOW
+    pub fn spoof_candidates(&self) -> Vec<String> {
+        let mut candidates: Vec<String> =
+            self.spoofs.iter().map(|(price, _)| price.clone()).collect();
+        candidates.sort();
+        candidates.dedup();
+        candidates
+    }
+
+    // Spoofs detected by the latest update
+    pub fn new_spoofs(&self) -> usize {
+        self.new_spoofs
+    }
+
+    // A level that a diff depletes and a later diff restores to its previous size counts as
+    // a refill. Follows the diff quantities, so levels hidden by min_notional still count;
+    // a level removed with a quantity of 0 starts over if it is posted again.
+    fn track_refills(&mut self, updates: &[LevelUpdate], now: Instant) {
+        for update in updates {
+            if update.after <= 0.0 {
+                self.level_tracks.remove(&update.price);
+                continue;
+            }
+            let quantity = update.after;
+            let track = self
+                .level_tracks
+                .entry(update.price.clone())
+                .or_insert_with(|| LevelTrack {
+                    peak: if update.before > 0.0 {
+                        update.before
+                    } else {
+                        quantity
+                    },
+                    depleted: false,
+                    refills: Vec::new(),
+                    last_seen: now,
+                });
+            let tolerance = track.peak.abs() * 1e-9;
+            if quantity < track.peak - tolerance {
+                track.depleted = true;
+            } else if quantity > track.peak + tolerance {
+                // A new size, not a refill of the old one
+                track.peak = quantity;
+                track.depleted = false;
+                track.refills.clear();
+            } else if track.depleted {
+                track.depleted = false;
+                track.refills.push(now);
+            }
+            track.last_seen = now;
+        }
+
+        for track in self.level_tracks.values_mut() {
+            track
+                .refills
+                .retain(|&at| now.duration_since(at) < ICEBERG_WINDOW);
+        }
+        self.level_tracks
+            .retain(|_, track| now.duration_since(track.last_seen) < ICEBERG_WINDOW);
+    }
+
+    // Updates received in the second up to `now`
+    pub fn update_rate(&self, now: Instant) -> f64 {
+        self.update_times
+            .iter()
+            .rev()
+            .take_while(|&&t| now.duration_since(t) <= Duration::from_secs(1))
+            .count() as f64
+    }
+
+    // Average updates/s over the rest of UPDATE_BASELINE_WINDOW, excluding the last second
+    pub fn baseline_rate(&self, now: Instant) -> Option<f64> {
+        let oldest = *self.update_times.front()?;
+        let span = now.duration_since(oldest).as_secs_f64() - 1.0;
+        if span < 5.0 {
+            return None; // not enough history to call anything a spike
+        }
+        let earlier = self.update_times.len() as f64 - self.update_rate(now);
+        Some(earlier / span)
+    }
+
+    // Quote stuffing: the current rate far above this book's own recent baseline
+    pub fn is_update_burst(&self, multiplier: f64, now: Instant) -> bool {
+        let rate = self.update_rate(now);
+        rate >= MIN_BURST_RATE
+            && self
+                .baseline_rate(now)
+                .is_some_and(|baseline| rate > baseline * multiplier)
+    }
+
+    // Prices refilled to the same size at least ICEBERG_MIN_REFILLS times within ICEBERG_WINDOW
+    pub fn iceberg_candidates(&self) -> Vec<String> {
+        let mut candidates: Vec<String> = self
+            .level_tracks
+            .iter()
+            .filter(|(_, track)| track.refills.len() >= ICEBERG_MIN_REFILLS)
+            .map(|(price, _)| price.clone())
+            .collect();
+        candidates.sort();
+        candidates
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+
+    // Levels added, removed and resized going from `previous` to this book
+    pub fn diff(&self, previous: &OrderBook) -> BookDiff {
+        BookDiff {
+            bids: diff_levels(&self.bids, &previous.bids),
+            asks: diff_levels(&self.asks, &previous.asks),
+        }
+    }
+
+    // What the latest update changed, relative to the book it replaced
+    pub fn last_diff(&self) -> BookDiff {
+        BookDiff {
+            bids: diff_levels(&self.bids, &self.previous_bids),
+            asks: diff_levels(&self.asks, &self.previous_asks),
+        }
+    }
+
+    // Levels in `result` whose quantity dropped relative to this book and that `inference`
+    // places close enough to the top of book to be fills rather than cancels
+    pub fn inferred_fills(
+        &self,
+        result: &serde_json::Value,
+        inference: &FillInference,
+    ) -> Vec<(f64, f64, TradeSide)> {
+        let mut fills = Vec::new();
+        let sides = [
+            (&self.bids, "bids", OrderSide::Bid, self.best_bid()),
+            (&self.asks, "asks", OrderSide::Ask, self.best_ask()),
+        ];
+        for (levels, key, book_side, best) in sides {
+            let Some(best) = best else {
+                continue;
+            };
+            for update in parse_levels(result.get(key), 0.0, DuplicatePolicy::default()).0 {
+                let Some(previous) = levels.iter().find(|l| l.price == update.price) else {
+                    continue;
+                };
+                let before = previous.quantity.parse::<f64>().unwrap_or(0.0);
+                let after = update.quantity.parse::<f64>().unwrap_or(0.0);
+                let Ok(price) = update.price.parse::<f64>() else {
+                    continue;
+                };
+                if after < before && inference.is_fill(book_side, best, price) {
+                    fills.push((price, before - after, inference.aggressor(book_side)));
+                }
+            }
+        }
+        fills
+    }
+
+    pub fn best_bid(&self) -> Option<f64> {
+        self.bids.first().and_then(|b| b.price.parse().ok())
+    }
+
+    pub fn best_ask(&self) -> Option<f64> {
+        self.asks.first().and_then(|a| a.price.parse().ok())
+    }
+
+    pub fn mid_price(&self) -> Option<f64> {
+        Some((self.best_bid()? + self.best_ask()?) / 2.0)
+    }
+
+    pub fn spread(&self) -> Option<f64> {
+        Some(self.best_ask()? - self.best_bid()?)
+    }
+
+    // How much the top levels look like one passive market maker quoting both sides, 0-1:
+    // the mean of size symmetry across matching bid/ask levels, evenness of the price
+    // steps on each side, and spread tightness. None without enough levels on both sides.
+    pub fn mm_footprint(&self) -> Option<f64> {
+        let levels = |orders: &[OrderBookEntry]| -> Vec<(f64, f64)> {
+            orders
+                .iter()
+                .take(MM_FOOTPRINT_LEVELS)
+                .filter_map(|o| Some((o.price.parse().ok()?, o.quantity.parse().ok()?)))
+                .collect()
+        };
+        let (bids, asks) = (levels(&self.bids), levels(&self.asks));
+        if bids.len() < 2 || asks.len() < 2 {
+            return None;
+        }
+
+        let pairs = bids.len().min(asks.len());
+        let symmetry = bids
+            .iter()
+            .zip(&asks)
+            .map(|(&(_, bid), &(_, ask))| {
+                if bid.max(ask) > 0.0 {
+                    bid.min(ask) / bid.max(ask)
+                } else {
+                    0.0
                 }
+            })
+            .sum::<f64>()
+            / pairs as f64;
+
+        // 1 / (1 + coefficient of variation) of the gaps between consecutive prices
+        let evenness = |side: &[(f64, f64)]| {
+            let gaps: Vec<f64> = side.windows(2).map(|w| (w[0].0 - w[1].0).abs()).collect();
+            let mean = gaps.iter().sum::<f64>() / gaps.len() as f64;
+            if mean <= 0.0 {
+                return 0.0;
+            }
+            let variance = gaps.iter().map(|g| (g - mean).powi(2)).sum::<f64>() / gaps.len() as f64;
+            1.0 / (1.0 + variance.sqrt() / mean)
+        };
+        let spacing = (evenness(&bids) + evenness(&asks)) / 2.0;
+
+        let mid = (bids[0].0 + asks[0].0) / 2.0;
+        let spread_bps = (asks[0].0 - bids[0].0) / mid * 10_000.0;
+        let tightness = 1.0 / (1.0 + spread_bps.max(0.0));
+
+        Some((symmetry + spacing + tightness) / 3.0)
+    }
+
+    // Sums quantity per price bucket: bids floor to the bucket below, asks ceil to the one
+    // above, so a bucket never crosses the spread. Ordered like the side itself.
+    pub fn grouped(&self, side: OrderSide, bucket_size: f64) -> Vec<(f64, f64)> {
+        if bucket_size <= 0.0 || !bucket_size.is_finite() {
+            return Vec::new();
+        }
+        let orders = match side {
+            OrderSide::Bid => &self.bids,
+            OrderSide::Ask => &self.asks,
+        };
+
+        let mut buckets: Vec<(i64, f64)> = Vec::new();
+        for order in orders {
+            let (Ok(price), Ok(quantity)) =
+                (order.price.parse::<f64>(), order.quantity.parse::<f64>())
+            else {
+                continue;
+            };
+            // Rounded first so a price on a boundary stays there: 0.3 / 0.1 is 2.9999999999999996
+            let steps = (price / bucket_size * 1e9).round() / 1e9;
+            let bucket = match side {
+                OrderSide::Bid => steps.floor() as i64,
+                OrderSide::Ask => steps.ceil() as i64,
+            };
+            match buckets.last_mut() {
+                Some((last, total)) if *last == bucket => *total += quantity,
+                _ => buckets.push((bucket, quantity)),
+            }
+        }
+        buckets
+            .into_iter()
+            .map(|(bucket, quantity)| (bucket as f64 * bucket_size, quantity))
+            .collect()
+    }
+
+    // Roughly 1/1000th of the price as a power of ten, e.g. 10 for BTC, 0.0001 for XRP
+    pub fn default_bucket_size(&self) -> Option<f64> {
+        let mid = self.mid_price().or(self.best_bid()).or(self.best_ask())?;
+        if mid <= 0.0 {
+            return None;
+        }
+        Some(10f64.powi(mid.log10().floor() as i32 - 3))
+    }
+
+    // sum(price * qty) / sum(qty) over the top `levels` of each side
+    pub fn vwap(&self, levels: usize) -> Option<f64> {
+        let (notional, quantity) = self
+            .bids
+            .iter()
+            .take(levels)
+            .chain(self.asks.iter().take(levels))
+            .filter_map(|o| {
+                Some((
+                    o.price.parse::<f64>().ok()?,
+                    o.quantity.parse::<f64>().ok()?,
+                ))
+            })
+            .fold((0.0, 0.0), |(n, q), (price, qty)| {
+                (n + price * qty, q + qty)
+            });
+
+        if quantity > 0.0 {
+            Some(notional / quantity)
+        } else {
+            None
+        }
+    }
+
+    // Share of each side's quantity in its `near_levels` levels closest to the touch, as
+    // (bid, ask) in [0, 1]: near 1 is liquidity ready to move, near 0 a wall further out.
+    // A side no deeper than `near_levels` is all near; an empty side reads 0.
+    pub fn pressure(&self, near_levels: usize) -> (f64, f64) {
+        let near_share = |orders: &[OrderBookEntry]| -> f64 {
+            let quantities: Vec<f64> = orders
+                .iter()
+                .map(|o| o.quantity.parse::<f64>().unwrap_or(0.0))
+                .collect();
+            let total: f64 = quantities.iter().sum();
+            if total > 0.0 {
+                quantities.iter().take(near_levels).sum::<f64>() / total
+            } else {
+                0.0
             }
+        };
+        (near_share(&self.bids), near_share(&self.asks))
+    }
+
+    // Returns (bid_qty - ask_qty) / (bid_qty + ask_qty), in [-1, 1]
+    pub fn imbalance(&self) -> Option<f64> {
+        let sum = |orders: &[OrderBookEntry]| -> f64 {
+            orders
+                .iter()
+                .filter_map(|o| o.quantity.parse::<f64>().ok())
+                .sum()
+        };
+        let bid_qty = sum(&self.bids);
+        let ask_qty = sum(&self.asks);
+
+        if bid_qty + ask_qty > 0.0 {
+            Some((bid_qty - ask_qty) / (bid_qty + ask_qty))
+        } else {
+            None
+        }
+    }
+}
+
+// Levels whose notional (price * quantity) falls below the threshold are dropped
+#[derive(Debug, Clone, Default)]
+pub struct NotionalFilter {
+    pub min_notional: f64,
+    pub overrides: HashMap<String, f64>, // keyed by uppercase symbol
+}
+
+impl NotionalFilter {
+    pub fn threshold(&self, symbol: &str) -> f64 {
+        self.overrides
+            .get(symbol)
+            .copied()
+            .unwrap_or(self.min_notional)
+    }
+}
+
+// Restricts the heuristics to levels within a percentage of mid; far resting orders
+// that never trade would otherwise weigh on the classification
+#[derive(Debug, Clone, Default)]
+pub struct PriceBand {
+    pub pct: Option<f64>,
+    pub overrides: HashMap<String, f64>, // keyed by uppercase symbol
+}
+
+impl PriceBand {
+    pub fn pct_for(&self, symbol: &str) -> Option<f64> {
+        self.overrides.get(symbol).copied().or(self.pct)
+    }
+
+    // Inclusive (low, high) prices for `symbol`; None when unbanded or without a mid
+    pub fn bounds(&self, symbol: &str, mid: Option<f64>) -> Option<(f64, f64)> {
+        let half_width = mid? * self.pct_for(symbol)? / 100.0;
+        Some((mid? - half_width, mid? + half_width))
+    }
+
+    pub fn contains(bounds: Option<(f64, f64)>, price: &str) -> bool {
+        bounds.is_none_or(|(low, high)| {
+            price
+                .parse::<f64>()
+                .is_ok_and(|price| (low..=high).contains(&price))
+        })
+    }
+
+    // The levels of one side inside `bounds`, borrowed as-is when there is no band
+    pub fn levels(
+        orders: &[OrderBookEntry],
+        bounds: Option<(f64, f64)>,
+    ) -> Cow<'_, [OrderBookEntry]> {
+        match bounds {
+            Some(_) => orders
+                .iter()
+                .filter(|o| Self::contains(bounds, &o.price))
+                .cloned()
+                .collect(),
+            None => Cow::Borrowed(orders),
+        }
+    }
+}
+
+impl Default for OrderBook {
+    fn default() -> Self {
+        OrderBook::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LatencyStats {
+    pub average_ms: f64,
+    pub clock_skew: bool, // at least one sample arrived before its event time
+}
+
+// Bell and title flash when the current symbol's likely-human orders reach `threshold`,
+// at most once per `cooldown`
+#[derive(Debug, Clone)]
+pub struct HumanAlert {
+    pub enabled: bool,
+    pub threshold: usize,
+    pub cooldown: Duration,
+    last_fired: Option<Instant>,
+}
+
+impl Default for HumanAlert {
+    fn default() -> Self {
+        HumanAlert {
+            enabled: false,
+            threshold: DEFAULT_ALERT_HUMAN_ORDERS,
+            cooldown: DEFAULT_ALERT_COOLDOWN,
+            last_fired: None,
+        }
+    }
+}
+
+impl HumanAlert {
+    // True when the alert should go off now; firing starts the cooldown
+    pub fn check(&mut self, likely_human: usize, now: Instant) -> bool {
+        let cooling = self
+            .last_fired
+            .is_some_and(|at| now.duration_since(at) < self.cooldown);
+        if !self.enabled || likely_human < self.threshold || cooling {
+            return false;
+        }
+        self.last_fired = Some(now);
+        true
+    }
+
+    pub fn is_flashing(&self, now: Instant) -> bool {
+        self.enabled
+            && self
+                .last_fired
+                .is_some_and(|at| now.duration_since(at) < ALERT_FLASH_FOR)
+    }
+}
+
+// (total, human) order counts for one symbol, averaged into one stored record per
+// ANALYSIS_WRITE_INTERVAL. The first window starts with the first sample.
+#[derive(Debug, Clone)]
+pub struct AnalysisWindow {
+    started: Instant,
+    samples: Vec<(usize, usize)>,
+}
+
+impl AnalysisWindow {
+    pub fn new(now: Instant) -> Self {
+        AnalysisWindow {
+            started: now,
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, total_orders: usize, human_orders: usize) {
+        self.samples.push((total_orders, human_orders));
+    }
+
+    // Average (total, human) once the window is a full interval old, starting the next one.
+    // None while the window is still open or if it saw no samples.
+    pub fn close(&mut self, now: Instant) -> Option<(f64, f64)> {
+        if now.duration_since(self.started) < ANALYSIS_WRITE_INTERVAL || self.samples.is_empty() {
+            return None;
+        }
+        let count = self.samples.len() as f64;
+        let total: usize = self.samples.iter().map(|&(total, _)| total).sum();
+        let human: usize = self.samples.iter().map(|&(_, human)| human).sum();
+        self.samples.clear();
+        self.started = now;
+        Some((total as f64 / count, human as f64 / count))
+    }
+}
+
+// Advances the displayed symbol every `interval`, for unattended wall displays
+#[derive(Debug, Clone)]
+pub struct Rotation {
+    pub interval: Duration,
+    pub paused: bool,
+    last: Instant,
+}
+
+impl Rotation {
+    pub fn new(interval: Duration, now: Instant) -> Self {
+        Rotation {
+            interval: interval.max(MIN_ROTATE_INTERVAL),
+            paused: false,
+            last: now,
+        }
+    }
+
+    pub fn remaining(&self, now: Instant) -> Duration {
+        self.interval.saturating_sub(now.duration_since(self.last))
+    }
+
+    // True once per interval while running; the countdown restarts when it fires
+    pub fn due(&mut self, now: Instant) -> bool {
+        if self.paused || !self.remaining(now).is_zero() {
+            return false;
         }
+        self.last = now;
+        true
+    }
+
+    // Starts a full interval from `now`, e.g. after a manual symbol change
+    pub fn restart(&mut self, now: Instant) {
+        self.last = now;
+    }
+
+    pub fn toggle_pause(&mut self, now: Instant) {
+        self.paused = !self.paused;
+        self.restart(now);
     }
+
+    pub fn scale_interval(&mut self, factor: f64) {
+        self.interval = self.interval.mul_f64(factor).max(MIN_ROTATE_INTERVAL);
+    }
+}
+
+pub struct App {
+    pub order_books: HashMap<String, OrderBook>,
+    pub current_symbol: String,
+    pub message_history: Vec<OrderBookMessage>,
+    pub tape: VecDeque<Trade>, // most recent last
+    pub fill_inference: FillInference,
+    pub trades: VecDeque<Trade>, // aggTrade stream, most recent last
+    pub trade_stream: bool,
+    pub liquidations: VecDeque<Liquidation>, // tracked symbols only, most recent last
+    pub feed_error: Option<String>,          // set when the feed task gives up for good
+    pub rejected_symbols: Vec<String>,       // configured but unknown to the exchange, not tracked
+    pub last_stall: Option<(Duration, Instant)>, // (length, when noticed) of the latest main-loop stall
+    pub human_alert: HumanAlert,
+    pub capture: Option<capture::EventCapture>, // context bundles on notable events when set
+    pub rotation: Option<Rotation>,
+    pub playback: Option<recording::Playback>, // set when the dashboard replays a recording
+    pub no_color: bool,
+    pub markers: MarkerSet,
+    pub display_levels: usize, // per side in the book panel; analysis always uses the full book
+    pub max_indicators: usize,
+    pub book_depth: usize, // levels per side analyzed and shown; books maintain the rest
+    pub group_bucket: Option<f64>, // book panel shows price buckets of this size when set
+    pub tape_scroll: usize, // trades skipped from the newest end of the tape panel
+    pub tape_columns: Vec<TapeColumn>,
+    pub show_help: bool,
+    pub annotation: Option<String>, // note being typed, see save_annotation
+    pub annotation_db: Option<db::Database>, // notes go here instead of `db` when set, e.g. during a replay
+    pub duplicate_policy: DuplicatePolicy,
+    pub validate_book: bool, // warn about integrity problems such as duplicate price levels
+    pub snapshot_policy: Option<db::SnapshotPolicy>, // None stores no full books
+    pub db: db::Database,
+    write_buffer: db::WriteBuffer,
+    pub analysis_files: Option<file_store::CsvStore>, // replaces the market_analysis table when set
+    pub csv_sink: Option<file_store::CsvSink>,        // per-symbol tail files, alongside the store
+    pub signal_dump: Option<file_store::SignalDump>,  // per-level heuristic outputs, for tuning
+    pub write_gate: Option<db::WriteGate>,            // None writes every window
+    pub latest_cache_ttl: Duration,
+    pub warmup: Duration, // analysis is shown but neither stored nor calibrated this long after start
+    pub stale_after: Duration, // books quiet this long are greyed out and left out of market-wide figures
+    started: Instant,
+    pub session: session::SessionStats,
+    latest_cache: HashMap<String, (Instant, Option<db::MarketAnalysisRecord>)>,
+    history_cache: HashMap<String, (Instant, i64, Vec<db::MarketAnalysisRecord>)>, // (fetched, limit, newest first)
+    throughput: (Instant, u64, f64), // (window start, messages this window, last full-second rate)
+    pub movers_windows: usize,
+    pub imbalance_windows: usize, // windows shown in the imbalance chart
+    pub near_levels: usize,       // levels per side on the near side of the pressure split
+    pub analyze_every_frame: bool, // skip the per-symbol analysis cache
+    pub analysis_refresh: Duration, // minimum age of the displayed analysis before it is replaced
+    pub pinned_symbols: Vec<String>, // shown first in the overview, in this order
+    pub hidden_symbols: HashSet<String>, // tracked and stored but left out of the overview
+    pub ratio_palette: Vec<(u8, u8, u8)>,
+    pub fingerprints: fingerprints::FingerprintSet,
+    pub human_threshold: f64, // levels scoring above this count as human
+    pub tuning: bool, // human_threshold is being adjusted live: it wins over calibration, nothing is stored
+    pub round_notional: Option<RoundNotional>, // None leaves notional out of the score
+    pub burst_multiplier: f64, // update rate over baseline that flags quote stuffing
+    pub cutoffs: ConfidenceCutoffs, // three-way split used for book coloring and bucket counts
+    pub analysis_symbols: Option<HashSet<String>>, // None analyzes every symbol
+    pub calibration: Option<calibration::Calibration>, // per-symbol thresholds replace human_threshold once warmed up
+    pub symbol_filters: HashMap<String, exchange::SymbolFilters>,
+    pub notional_filter: NotionalFilter,
+    pub price_band: PriceBand,
+    pub paused: bool,
+    pub aliases: symbols::SymbolAliases,
+    pending_updates: HashMap<String, VecDeque<serde_json::Value>>, // held back while paused or resyncing, oldest first
+    awaiting_snapshot: HashSet<String>, // backlog overflowed; diffs wait for a fresh snapshot
+    pub resync_requests: Vec<String>,   // symbols needing a REST snapshot, drained by the feed
+    last_update_ids: HashMap<String, u64>, // last applied lastUpdateId per symbol
+    out_of_order: u64,
+    analysis_windows: HashMap<String, AnalysisWindow>,
+    clock: Arc<dyn Clock>,
+    latency_buffer: HashMap<String, Vec<(Instant, i64, bool)>>, // (received, latency_ms, clock_skew) per symbol
+    last_latency_write: HashMap<String, Instant>,
+    last_written: HashMap<String, db::LastWrite>, // last analysis row persisted per symbol
+    last_snapshot: HashMap<String, db::LastSnapshot>,
+    ratio_samples: HashMap<String, VecDeque<f64>>, // recent human_ratio readings per symbol
+    window_ratios: HashMap<String, VecDeque<(Instant, f64)>>, // averaged human_ratio per DB write window
+    window_imbalances: HashMap<String, VecDeque<f64>>, // book imbalance at the end of each window
+    dirty: HashSet<String>, // symbols updated since their cached analysis
+    analysis_cache: HashMap<String, MarketAnalysis>,
+    displayed_analyses: (Instant, HashMap<String, MarketAnalysis>), // (taken at, per symbol)
+}
+
+// Per-level outputs of the stateless heuristics
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelSignals {
+    pub round_price: bool,
+    pub human_size: bool,
+    pub human_spacing: Option<bool>, // None when the side has a single level
+    pub step_alignment: Option<bool>, // None without exchange step size
+    pub round_notional: Option<bool>, // None when the notional heuristic is off
+}
+
+// Humans often size in quote terms ("$1000 of BTC"), leaving a round notional behind a
+// ragged base quantity. A notional is round when it is 1-10 times a unit scaled by a
+// power of ten, e.g. with unit 250: 250, 750, 2500, 5000.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundNotional {
+    pub units: Vec<f64>,
+    pub tolerance: f64, // relative, absorbs quantities rounded to the lot step
 }
 
-impl Default for MarketAnalysis {
+impl Default for RoundNotional {
     fn default() -> Self {
-        MarketAnalysis {
-            total_orders: 0,
-            likely_human_orders: 0,
-            bot_patterns: Vec::new(),
-            human_patterns: Vec::new(),
-            confidence_scores: HashMap::new(),
+        RoundNotional {
+            units: vec![100.0, 250.0, 500.0],
+            tolerance: 0.001,
+        }
+    }
+}
+
+impl RoundNotional {
+    pub fn is_round(&self, notional: f64) -> bool {
+        if !notional.is_finite() || notional <= 0.0 {
+            return false;
+        }
+        self.units.iter().filter(|&&unit| unit > 0.0).any(|&unit| {
+            let mut scaled = unit;
+            while scaled <= notional * (1.0 + self.tolerance) {
+                let multiple = (notional / scaled).round();
+                if (1.0..=10.0).contains(&multiple)
+                    && (notional - multiple * scaled).abs() <= notional * self.tolerance
+                {
+                    return true;
+                }
+                scaled *= 10.0;
+            }
+            false
+        })
+    }
+}
+
+impl LevelSignals {
+    pub fn compute(
+        orders: &[OrderBookEntry],
+        index: usize,
+        filters: Option<&exchange::SymbolFilters>,
+        round_notional: Option<&RoundNotional>,
+    ) -> Option<LevelSignals> {
+        let order = orders.get(index)?;
+        // Exact decimals, so 49999.99 never reads as 50000 and 0.3 is a multiple of 0.1
+        let price = order.price.parse::<Decimal>().ok()?;
+        let quantity = order.quantity.parse::<Decimal>().ok()?;
+
+        // Spacing to the next level on the same side; the deepest level looks back instead
+        let neighbour = orders
+            .get(index + 1)
+            .or_else(|| index.checked_sub(1).and_then(|i| orders.get(i)));
+        let human_spacing = neighbour
+            .and_then(|n| n.price.parse::<Decimal>().ok())
+            .map(|neighbour_price| is_human_spacing((neighbour_price - price).abs()));
+
+        Some(LevelSignals {
+            round_price: is_round_price(price, filters.map(|f| f.tick_size)),
+            human_size: is_human_size(quantity),
+            human_spacing,
+            step_alignment: filters.and_then(|f| step_alignment(quantity, f.step_size)),
+            round_notional: round_notional.map(|r| r.is_round(order.total())),
+        })
+    }
+
+    pub fn human_score(&self) -> f64 {
+        let mut signals = 2;
+        let mut human = self.round_price as usize + self.human_size as usize;
+        // A ragged notional is what most levels have, so round notional only ever adds
+        let round_notional = self.round_notional.filter(|&round| round);
+        for signal in [self.human_spacing, self.step_alignment, round_notional]
+            .into_iter()
+            .flatten()
+        {
+            signals += 1;
+            human += signal as usize;
+        }
+        human as f64 / signals as f64
+    }
+}
+
+fn sort_patterns_by_price(mut patterns: Vec<(String, String)>) -> Vec<String> {
+    patterns.sort_by(|(a, _), (b, _)| {
+        let a_price = a.parse::<f64>().unwrap_or(0.0);
+        let b_price = b.parse::<f64>().unwrap_or(0.0);
+        b_price
+            .partial_cmp(&a_price)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.cmp(b))
+    });
+    patterns.into_iter().map(|(_, pattern)| pattern).collect()
+}
+
+const HALF: Decimal = Decimal::from_parts(5, 0, 0, false, 1);
+const QUARTER: Decimal = Decimal::from_parts(25, 0, 0, false, 2);
+const ROUND_PRICE_TICKS: Decimal = Decimal::ONE_HUNDRED;
+
+fn is_round_fraction(fraction: Decimal) -> bool {
+    fraction.is_zero() || fraction == HALF || fraction == QUARTER
+}
+
+// With a tick size, "round" means a multiple of ROUND_PRICE_TICKS ticks (so also 1000x and
+// beyond): whole dollars at a 0.01 tick, whole cents at 0.0001. Without one, fixed levels.
+pub fn is_round_price(price: Decimal, tick_size: Option<Decimal>) -> bool {
+    if let Some(tick_size) = tick_size.filter(|t| *t > Decimal::ZERO) {
+        return (price % (tick_size * ROUND_PRICE_TICKS)).is_zero();
+    }
+    let decimal_part = price.fract();
+    let whole_part = price.trunc();
+
+    let is_round = is_round_fraction(decimal_part);
+    let is_psychological = (whole_part % Decimal::ONE_THOUSAND).is_zero() || // e.g., 50000
+        (whole_part % Decimal::from(500)).is_zero() ||  // e.g., 49500
+        (whole_part % Decimal::ONE_HUNDRED).is_zero(); // e.g., 49100
+
+    is_round || is_psychological
+}
+
+fn is_human_size(quantity: Decimal) -> bool {
+    let whole_part = quantity.trunc();
+    let decimal_part = quantity.fract();
+
+    is_round_fraction(decimal_part) ||       // Whole, half and quarter units
+        whole_part <= Decimal::TEN ||        // Small round numbers
+        (whole_part % Decimal::from(5)).is_zero() // Multiples of 5
+}
+
+// Some(true) for coarse human increments (0.13, 2.5, 1200), Some(false) for quantities using
+// the full step precision (0.13742), None when the quantity isn't step-aligned at all
+pub fn step_alignment(quantity: Decimal, step_size: Decimal) -> Option<bool> {
+    if step_size <= Decimal::ZERO || quantity <= Decimal::ZERO {
+        return None;
+    }
+    let steps = quantity.checked_div(step_size)?;
+    if !steps.fract().is_zero() {
+        return None;
+    }
+
+    // Significant digits once trailing zeros (coarser increments) are stripped
+    let mut digits = steps.to_u64()?;
+    while digits > 0 && digits.is_multiple_of(10) {
+        digits /= 10;
+    }
+    let significant = digits.to_string().len();
+
+    Some(significant <= 2 || (significant == 3 && digits % 10 == 5))
+}
+
+fn is_human_spacing(diff: Decimal) -> bool {
+    diff > Decimal::new(1, 2) && // Not too precise
+        !diff.fract().is_zero() && // Not perfectly spaced
+        !(diff % Decimal::new(1, 1)).is_zero() // Not aligned to common intervals
+}
+
+// Outcome of App::backfill_analysis, counted in intervals except `inserted`
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BackfillReport {
+    pub missing: usize,  // intervals without analysis rows
+    pub filled: usize,   // of those, intervals that had snapshots to reanalyze
+    pub inserted: usize, // rows written to market_analysis
+}
+
+#[derive(Default, Clone, Serialize)]
+pub struct MarketAnalysis {
+    pub total_orders: usize,
+    pub likely_human_orders: usize,
+    pub bot_patterns: Vec<String>,
+    pub human_patterns: Vec<String>,
+    pub confidence_scores: HashMap<String, f64>,
+    pub buckets: BucketCounts,
+    pub mm_footprint: f64, // see OrderBook::mm_footprint; shown, not part of the human ratio
+}
+
+impl App {
+    pub fn new() -> Result<App, AppError> {
+        Ok(App::with_database(db::Database::new()?))
+    }
+
+    pub fn with_database(db: db::Database) -> App {
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        App {
+            order_books: crate::SYMBOLS
+                .iter()
+                .map(|&symbol| (symbol.to_uppercase(), OrderBook::new()))
+                .collect(),
+            current_symbol: "BTCUSDT".to_string(),
+            message_history: Vec::with_capacity(10000),
+            tape: VecDeque::with_capacity(TAPE_CAPACITY),
+            fill_inference: FillInference::default(),
+            trades: VecDeque::with_capacity(TAPE_CAPACITY),
+            liquidations: VecDeque::with_capacity(LIQUIDATION_CAPACITY),
+            trade_stream: false,
+            feed_error: None,
+            rejected_symbols: Vec::new(),
+            stale_after: FEED_STALE_AFTER,
+            last_stall: None,
+            human_alert: HumanAlert::default(),
+            capture: None,
+            rotation: None,
+            playback: None,
+            no_color: false,
+            markers: MarkerSet::default(),
+            display_levels: DEFAULT_DISPLAY_LEVELS,
+            max_indicators: DEFAULT_MAX_INDICATORS,
+            book_depth: DEFAULT_BOOK_DEPTH,
+            group_bucket: None,
+            tape_scroll: 0,
+            tape_columns: default_tape_columns(),
+            show_help: false,
+            annotation: None,
+            annotation_db: None,
+            duplicate_policy: DuplicatePolicy::default(),
+            validate_book: false,
+            snapshot_policy: None,
+            db,
+            write_buffer: db::WriteBuffer::new(WRITE_BUFFER_CAPACITY),
+            analysis_files: None,
+            csv_sink: None,
+            signal_dump: None,
+            write_gate: None,
+            latest_cache_ttl: DEFAULT_LATEST_CACHE_TTL,
+            latest_cache: HashMap::new(),
+            history_cache: HashMap::new(),
+            throughput: (clock.now(), 0, 0.0),
+            warmup: Duration::ZERO,
+            started: clock.now(),
+            session: session::SessionStats::default(),
+            displayed_analyses: (clock.now(), HashMap::new()),
+            movers_windows: DEFAULT_MOVERS_WINDOWS,
+            imbalance_windows: DEFAULT_IMBALANCE_WINDOWS,
+            near_levels: DEFAULT_NEAR_LEVELS,
+            analyze_every_frame: false,
+            analysis_refresh: Duration::ZERO,
+            pinned_symbols: Vec::new(),
+            hidden_symbols: HashSet::new(),
+            ratio_palette: DEFAULT_RATIO_PALETTE.to_vec(),
+            fingerprints: fingerprints::FingerprintSet::default(),
+            human_threshold: DEFAULT_HUMAN_THRESHOLD,
+            tuning: false,
+            round_notional: None,
+            burst_multiplier: DEFAULT_BURST_MULTIPLIER,
+            cutoffs: ConfidenceCutoffs::default(),
+            analysis_symbols: None,
+            calibration: None,
+            symbol_filters: HashMap::new(),
+            notional_filter: NotionalFilter::default(),
+            price_band: PriceBand::default(),
+            paused: false,
+            aliases: symbols::SymbolAliases::default(),
+            pending_updates: HashMap::new(),
+            awaiting_snapshot: HashSet::new(),
+            resync_requests: Vec::new(),
+            last_update_ids: HashMap::new(),
+            out_of_order: 0,
+            analysis_windows: HashMap::new(),
+            clock,
+            latency_buffer: HashMap::new(),
+            last_latency_write: HashMap::new(),
+            last_written: HashMap::new(),
+            last_snapshot: HashMap::new(),
+            ratio_samples: HashMap::new(),
+            window_ratios: HashMap::new(),
+            window_imbalances: HashMap::new(),
+            dirty: HashSet::new(),
+            analysis_cache: HashMap::new(),
+        }
+    }
+
+    fn record_ratio_sample(&mut self, symbol: &str, ratio: f64) {
+        let samples = self.ratio_samples.entry(symbol.to_string()).or_default();
+        if samples.len() == RATIO_SAMPLE_CAPACITY {
+            samples.pop_front();
+        }
+        samples.push_back(ratio);
+    }
+
+    fn record_window_ratio(&mut self, symbol: &str, ratio: f64) {
+        let windows = self.window_ratios.entry(symbol.to_string()).or_default();
+        if windows.len() == WINDOW_RATIO_CAPACITY {
+            windows.pop_front();
+        }
+        windows.push_back((self.clock.now(), ratio));
+    }
+
+    fn record_window_imbalance(&mut self, symbol: &str, imbalance: f64) {
+        let windows = self
+            .window_imbalances
+            .entry(symbol.to_string())
+            .or_default();
+        if windows.len() == WINDOW_RATIO_CAPACITY {
+            windows.pop_front();
+        }
+        windows.push_back(imbalance);
+    }
+
+    // Up to the last `windows` imbalance samples, oldest first
+    pub fn imbalance_history(&self, symbol: &str, windows: usize) -> Vec<f64> {
+        self.window_imbalances
+            .get(symbol)
+            .map_or_else(Vec::new, |samples| {
+                samples
+                    .iter()
+                    .skip(samples.len().saturating_sub(windows))
+                    .copied()
+                    .collect()
+            })
+    }
+
+    // Least-squares slope of the windowed human ratio over the last `window`, per minute.
+    // 0.0 until there are two windows to fit.
+    pub fn ratio_trend(&self, symbol: &str, window: Duration) -> f64 {
+        let Some(windows) = self.window_ratios.get(symbol) else {
+            return 0.0;
+        };
+        let now = self.clock.now();
+        let points: Vec<(f64, f64)> = windows
+            .iter()
+            .filter(|(at, _)| now.duration_since(*at) <= window)
+            .map(|&(at, ratio)| (-(now.duration_since(at).as_secs_f64() / 60.0), ratio))
+            .collect();
+        linear_slope(&points)
+    }
+
+    // Change in averaged human ratio over the last `windows` windows, largest moves first
+    // Pinned symbols lead in pin order; hidden symbols are left out
+    pub fn top_movers(&self, windows: usize) -> Vec<(String, f64)> {
+        let pin_rank = |symbol: &str| {
+            self.pinned_symbols
+                .iter()
+                .position(|p| p == symbol)
+                .unwrap_or(usize::MAX)
+        };
+        let mut movers: Vec<(String, f64)> = self
+            .window_ratios
+            .iter()
+            .filter(|(symbol, ratios)| ratios.len() >= 2 && !self.hidden_symbols.contains(*symbol))
+            .map(|(symbol, ratios)| {
+                let latest = ratios[ratios.len() - 1].1;
+                let baseline = ratios[ratios.len().saturating_sub(windows + 1)].1;
+                (symbol.clone(), latest - baseline)
+            })
+            .collect();
+
+        movers.sort_by(|a, b| {
+            pin_rank(&a.0)
+                .cmp(&pin_rank(&b.0))
+                .then_with(|| {
+                    b.1.abs()
+                        .partial_cmp(&a.1.abs())
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        movers
+    }
+
+    pub fn ratio_sample_count(&self, symbol: &str) -> usize {
+        self.ratio_samples.get(symbol).map_or(0, |s| s.len())
+    }
+
+    // Percentage (0-100) of recent samples at or below `value`; 0.0 with no samples
+    pub fn human_ratio_percentile(&self, symbol: &str, value: f64) -> f64 {
+        match self.ratio_samples.get(symbol) {
+            Some(samples) if !samples.is_empty() => {
+                let below = samples.iter().filter(|&&s| s <= value).count();
+                below as f64 / samples.len() as f64 * 100.0
+            }
+            _ => 0.0,
+        }
+    }
+
+    pub fn human_ratio_zscore(&self, symbol: &str, value: f64) -> Option<f64> {
+        let samples = self.ratio_samples.get(symbol)?;
+        if samples.len() < 2 {
+            return None;
+        }
+
+        let count = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / count;
+        let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / count;
+        let std_dev = variance.sqrt();
+
+        if std_dev > 0.0 {
+            Some((value - mean) / std_dev)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_ratio_anomaly(&self, symbol: &str, value: f64) -> bool {
+        self.ratio_sample_count(symbol) >= ANOMALY_MIN_SAMPLES
+            && self.human_ratio_percentile(symbol, value) > ANOMALY_PERCENTILE
+    }
+
+    fn record_latency(&mut self, symbol: &str, result: &serde_json::Value) {
+        let (Some(event_time), Some(received_at)) = (
+            result.get("eventTime").and_then(|e| e.as_i64()),
+            result.get("receivedAt").and_then(|r| r.as_i64()),
+        ) else {
+            return;
+        };
+
+        // Clock skew can put receipt before the event; clamp and flag it
+        let raw_latency = received_at - event_time;
+        let clock_skew = raw_latency < 0;
+        let latency_ms = raw_latency.max(0);
+
+        let now = self.clock.now();
+        let buffer = self.latency_buffer.entry(symbol.to_string()).or_default();
+        buffer.push((now, latency_ms, clock_skew));
+        telemetry::record_feed_latency(symbol, latency_ms);
+        buffer.retain(|(timestamp, _, _)| now.duration_since(*timestamp) < LATENCY_WINDOW);
+
+        let due = self
+            .last_latency_write
+            .get(symbol)
+            .is_none_or(|&last| now.duration_since(last) >= LATENCY_DB_INTERVAL);
+        if due {
+            let record = db::LatencyRecord {
+                symbol: symbol.to_string(),
+                timestamp: self.clock.unix_secs(),
+                latency_ms,
+                clock_skew,
+            };
+            if let Err(e) = self.db.insert_latency(&record) {
+                error!(symbol, error = %e, "Failed to store feed latency");
+            }
+            self.last_latency_write
+                .insert(symbol.to_string(), self.clock.now());
+        }
+    }
+
+    pub fn latency_stats(&self, symbol: &str) -> Option<LatencyStats> {
+        let buffer = self.latency_buffer.get(symbol)?;
+        if buffer.is_empty() {
+            return None;
+        }
+
+        let total: i64 = buffer.iter().map(|(_, latency, _)| latency).sum();
+        Some(LatencyStats {
+            average_ms: total as f64 / buffer.len() as f64,
+            clock_skew: buffer.iter().any(|(_, _, skew)| *skew),
+        })
+    }
+
+    pub fn analyze_market(&mut self) -> MarketAnalysis {
+        let current_symbol = self.current_symbol.clone();
+        self.analyze_symbol(&current_symbol)
+    }
+
+    // Analyzes every analysis-active symbol, keyed by symbol. Symbols with no update since
+    // their last analysis reuse the cached result unless analyze_every_frame is set.
+    pub fn analyze_all_symbols(&mut self) -> HashMap<String, MarketAnalysis> {
+        let symbols: Vec<String> = self
+            .order_books
+            .keys()
+            .filter(|symbol| self.is_analysis_active(symbol))
+            .cloned()
+            .collect();
+        symbols
+            .into_iter()
+            .map(|symbol| {
+                let stale = self.analyze_every_frame
+                    || self.dirty.contains(&symbol)
+                    || !self.analysis_cache.contains_key(&symbol);
+                if stale {
+                    let analysis = self.analyze_symbol(&symbol);
+                    self.analysis_cache.insert(symbol.clone(), analysis);
+                    self.dirty.remove(&symbol);
+                }
+                let analysis = self.analysis_cache[&symbol].clone();
+                (symbol, analysis)
+            })
+            .collect()
+    }
+
+    // Runs analyze_all_symbols at the feed rate but only replaces what the panels show once
+    // analysis_refresh has passed. Symbols new to the display are filled in straight away.
+    pub fn displayed_analyses(&mut self) -> HashMap<String, MarketAnalysis> {
+        let fresh = self.analyze_all_symbols();
+        let now = self.clock.now();
+        let (taken, shown) = &mut self.displayed_analyses;
+        if now.duration_since(*taken) >= self.analysis_refresh || shown.is_empty() {
+            *taken = now;
+            *shown = fresh;
+        } else {
+            shown.retain(|symbol, _| fresh.contains_key(symbol));
+            for (symbol, analysis) in fresh {
+                shown.entry(symbol).or_insert(analysis);
+            }
+        }
+        shown.clone()
+    }
+
+    // Latest analysis computed by analyze_all_symbols, if any
+    pub fn cached_analysis(&self, symbol: &str) -> Option<&MarketAnalysis> {
+        self.analysis_cache.get(symbol)
+    }
+
+    pub fn is_analysis_active(&self, symbol: &str) -> bool {
+        self.analysis_symbols
+            .as_ref()
+            .is_none_or(|active| active.contains(symbol))
+    }
+
+    // Turns the heuristics on or off for one symbol; the first toggle narrows "all" to a set
+    pub fn toggle_analysis(&mut self, symbol: &str) {
+        let all: HashSet<String> = self.order_books.keys().cloned().collect();
+        let active = self.analysis_symbols.get_or_insert(all);
+        if !active.remove(symbol) {
+            active.insert(symbol.to_string());
+        }
+        self.analysis_cache.remove(symbol);
+    }
+
+    // Calibrated cutoff when available, otherwise the fixed human_threshold
+    pub fn threshold_for(&self, symbol: &str) -> f64 {
+        if self.tuning {
+            return self.human_threshold;
+        }
+        self.calibration
+            .as_ref()
+            .and_then(|c| c.threshold(symbol))
+            .unwrap_or(self.human_threshold)
+    }
+
+    pub fn toggle_tuning(&mut self) {
+        self.tuning = !self.tuning;
+        self.reclassify();
+    }
+
+    // Moves human_threshold within 0-1 in whole hundredths
+    pub fn adjust_threshold(&mut self, delta: f64) {
+        self.human_threshold =
+            ((self.human_threshold + delta).clamp(0.0, 1.0) * 100.0).round() / 100.0;
+        self.reclassify();
+    }
+
+    // Drops cached and displayed results so the next frame reflects the new threshold
+    fn reclassify(&mut self) {
+        self.analysis_cache.clear();
+        self.displayed_analyses.1.clear();
+    }
+
+    pub fn reset_calibration(&mut self) {
+        if let Some(calibration) = &mut self.calibration {
+            calibration.reset();
+        }
+        self.analysis_cache.clear();
+    }
+
+    pub fn analyze_symbol(&mut self, symbol: &str) -> MarketAnalysis {
+        let current_symbol = symbol.to_string();
+        let threshold = self.threshold_for(symbol);
+        let analysis = if let Some(order_book) = self.order_books.get(&current_symbol) {
+            let bounds = self
+                .price_band
+                .bounds(&current_symbol, order_book.mid_price());
+            let fingerprint_matches: HashMap<String, String> = self
+                .known_fingerprints_for(&current_symbol)
+                .into_iter()
+                .filter(|(price, _)| PriceBand::contains(bounds, price))
+                .collect();
+            // Signals compare neighbouring levels, so the band is cut before computing them
+            let bids = PriceBand::levels(&order_book.bids, bounds);
+            let asks = PriceBand::levels(&order_book.asks, bounds);
+
+            let filters = self.symbol_filters.get(&current_symbol);
+            let (spread, imbalance) = (order_book.spread(), order_book.imbalance());
+            let level_count = bids.len() + asks.len();
+            let mut confidence_scores = HashMap::with_capacity(level_count);
+            let mut human_patterns = Vec::new();
+            let mut bot_patterns = Vec::new();
+            let mut scores = Vec::with_capacity(level_count);
+            let mut dump_rows = String::new();
+            let dump_ts = self.clock.unix_millis();
+
+            // Single pass over both sides computing every signal per level
+            for (side, orders) in [("bid", &bids), ("ask", &asks)] {
+                for index in 0..orders.len() {
+                    let price = &orders[index].price;
+                    if fingerprint_matches.contains_key(price) {
+                        continue;
+                    }
+                    let Some(signals) =
+                        LevelSignals::compute(orders, index, filters, self.round_notional.as_ref())
+                    else {
+                        continue;
+                    };
+
+                    let human_score = signals.human_score();
+                    if self.signal_dump.is_some() {
+                        dump_rows.push_str(&file_store::SignalDump::row(
+                            dump_ts,
+                            &current_symbol,
+                            side,
+                            price,
+                            &signals,
+                            human_score,
+                            if human_score > threshold {
+                                "human"
+                            } else {
+                                "bot"
+                            },
+                        ));
+                    }
+                    confidence_scores.insert(price.clone(), human_score);
+                    scores.push(human_score);
+
+                    if human_score > threshold {
+                        human_patterns.push((
+                            price.clone(),
+                            format!("Order at {} shows human behavior", price),
+                        ));
+                    } else {
+                        bot_patterns.push((
+                            price.clone(),
+                            format!("Order at {} likely automated", price),
+                        ));
+                    }
+                }
+            }
+
+            if let Some(dump) = self.signal_dump.as_mut().filter(|_| !dump_rows.is_empty()) {
+                if let Err(e) = dump.write(&dump_rows) {
+                    error!(symbol = %current_symbol, error = %e, "Failed to dump level signals");
+                }
+            }
+
+            // Known fingerprints override the statistical heuristics
+            for (price, indicator) in &fingerprint_matches {
+                confidence_scores.insert(price.clone(), 0.0);
+                bot_patterns.push((
+                    price.clone(),
+                    format!("Order at {} matches {}", price, indicator),
+                ));
+            }
+
+            // Order by price (highest first) so the output is stable frame-to-frame
+            let human_patterns = sort_patterns_by_price(human_patterns);
+            let mut bot_patterns = sort_patterns_by_price(bot_patterns);
+
+            // Market-level signals, so they follow the per-level patterns. They describe the
+            // book as a whole and are display-only: no level score or stored ratio moves.
+            let mm_footprint = order_book.mm_footprint().unwrap_or(0.0);
+            if mm_footprint >= MM_FOOTPRINT_THRESHOLD {
+                bot_patterns.push(format!(
+                    "Market maker footprint {:.2}: tight, symmetric two-sided quotes",
+                    mm_footprint
+                ));
+            }
+            let now = self.clock.now();
+            if order_book.is_update_burst(self.burst_multiplier, now) {
+                bot_patterns.push(format!(
+                    "Update burst: {:.0}/s vs {:.1}/s baseline (possible quote stuffing)",
+                    order_book.update_rate(now),
+                    order_book.baseline_rate(now).unwrap_or(0.0)
+                ));
+            }
+
+            let likely_human_orders = confidence_scores
+                .values()
+                .filter(|&&score| score > threshold)
+                .count();
+            let mut buckets = BucketCounts::default();
+            for &score in confidence_scores.values() {
+                buckets.add(self.cutoffs.classify(score));
+            }
+
+            let total_orders = level_count;
+
+            self.tag_fingerprinted_levels(&current_symbol, &fingerprint_matches);
+            // A half-built book right after startup would skew calibration and stored windows,
+            // as would a threshold that is being tuned or a feed that has gone quiet
+            let hold_back =
+                self.tuning || self.warmup_remaining().is_some() || self.is_stale(&current_symbol);
+            if let Some(calibration) = self.calibration.as_mut().filter(|_| !hold_back) {
+                calibration.observe(&current_symbol, scores);
+            }
+
+            let now = self.clock.now();
+            // A paused view is not what the market did; it stays out of the windows
+            let averages = if hold_back || self.paused {
+                None
+            } else {
+                let window = self
+                    .analysis_windows
+                    .entry(current_symbol.clone())
+                    .or_insert_with(|| AnalysisWindow::new(now));
+                window.push(total_orders, likely_human_orders);
+                window.close(now)
+            };
+            if total_orders > 0 {
+                let ratio = likely_human_orders as f64 / total_orders as f64;
+                self.record_ratio_sample(&current_symbol, ratio);
+                self.session.record_ratio(&current_symbol, ratio);
+                telemetry::record_human_ratio(&current_symbol, ratio);
+            }
+            telemetry::record_book_shape(&current_symbol, spread, imbalance);
+
+            // One averaged record per ANALYSIS_WRITE_INTERVAL
+            if let Some((avg_total, avg_human)) = averages {
+                let record = db::MarketAnalysisRecord::from_average(
+                    &current_symbol,
+                    self.clock.unix_secs(),
+                    avg_total,
+                    avg_human,
+                );
+
+                let human_ratio = record.human_ratio;
+                let write = self.write_gate.as_ref().is_none_or(|gate| {
+                    gate.should_write(self.last_written.get(&current_symbol), &record, now)
+                });
+                if write {
+                    self.last_written.insert(
+                        current_symbol.clone(),
+                        db::LastWrite {
+                            human_ratio,
+                            total_orders: record.total_orders,
+                            at: now,
+                        },
+                    );
+                    if let Some(sink) = &self.csv_sink {
+                        if let Err(e) = sink.append(&record, spread, imbalance) {
+                            error!(symbol = %current_symbol, error = %e, "Failed to append CSV row");
+                        }
+                    }
+                    self.persist_analysis(record);
+                }
+                self.record_window_ratio(&current_symbol, human_ratio);
+                if let Some(imbalance) = imbalance {
+                    self.record_window_imbalance(&current_symbol, imbalance);
+                }
+            }
+
+            MarketAnalysis {
+                total_orders,
+                likely_human_orders,
+                bot_patterns,
+                human_patterns,
+                confidence_scores,
+                buckets,
+                mm_footprint,
+            }
+        } else {
+            MarketAnalysis::default()
+        };
+
+        analysis
+    }
+
+    // Writes through the buffer so records survive transient DB failures (e.g. a full disk)
+    fn persist_analysis(&mut self, record: db::MarketAnalysisRecord) {
+        self.latest_cache.remove(&record.symbol);
+        self.history_cache.remove(&record.symbol);
+        let dropped_before = self.write_buffer.dropped();
+        self.write_buffer.push(record);
+        let dropped = self.write_buffer.dropped() - dropped_before;
+        if dropped > 0 {
+            warn!(
+                dropped,
+                total_dropped = self.write_buffer.dropped(),
+                "Write buffer full, discarded oldest analysis records"
+            );
+        }
+
+        let store = match &self.analysis_files {
+            Some(files) => files as &dyn db::AnalysisStore,
+            None => &self.db,
+        };
+        match self.write_buffer.flush(store) {
+            Ok(written) if written > 1 => {
+                info!(written, "Flushed buffered analysis records");
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!(
+                    error = %e,
+                    pending = self.write_buffer.len(),
+                    "Failed to store market analysis, will retry"
+                );
+            }
+        }
+    }
+
+    // Analysis records go to the CSV files when configured, otherwise to SQLite
+    pub fn analysis_store(&self) -> &dyn db::AnalysisStore {
+        match &self.analysis_files {
+            Some(files) => files,
+            None => &self.db,
+        }
+    }
+
+    // Latest stored record, re-queried at most once per TTL so per-frame reads stay off SQLite
+    pub fn latest_analysis(&mut self, symbol: &str) -> Option<db::MarketAnalysisRecord> {
+        if let Some((fetched, record)) = self.latest_cache.get(symbol) {
+            if self.clock.now().duration_since(*fetched) < self.latest_cache_ttl {
+                return record.clone();
+            }
+        }
+
+        let record = match self.analysis_store().get_latest_analysis(symbol) {
+            Ok(record) => record,
+            Err(e) => {
+                error!(symbol, error = %e, "Failed to read latest analysis");
+                None
+            }
+        };
+        self.latest_cache
+            .insert(symbol.to_string(), (self.clock.now(), record.clone()));
+        record
+    }
+
+    // Newest-first history for the chart, cached like latest_analysis; the CSV store
+    // re-reads its files on every query
+    pub fn analysis_history(&mut self, symbol: &str, limit: i64) -> Vec<db::MarketAnalysisRecord> {
+        if let Some((fetched, cached_limit, records)) = self.history_cache.get(symbol) {
+            if *cached_limit == limit
+                && self.clock.now().duration_since(*fetched) < self.latest_cache_ttl
+            {
+                return records.clone();
+            }
+        }
+
+        let records = match self.analysis_store().get_analysis_history(symbol, limit) {
+            Ok(records) => records,
+            Err(e) => {
+                error!(symbol, error = %e, "Failed to read analysis history");
+                Vec::new()
+            }
+        };
+        self.history_cache.insert(
+            symbol.to_string(),
+            (self.clock.now(), limit, records.clone()),
+        );
+        records
+    }
+
+    // Writes the newest MAX_PERSISTED_HISTORY messages as JSONL, replacing the file atomically
+    pub fn save_history<P: AsRef<Path>>(&self, path: P) -> Result<(), AppError> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        let mut writer = BufWriter::new(fs::File::create(&tmp)?);
+        let start = self
+            .message_history
+            .len()
+            .saturating_sub(MAX_PERSISTED_HISTORY);
+        for message in &self.message_history[start..] {
+            serde_json::to_writer(&mut writer, message)?;
+            writeln!(writer)?;
+        }
+        writer.flush()?;
+        drop(writer);
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    // Prepends the tail of a saved history; a missing file is not an error
+    pub fn load_history<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, AppError> {
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let mut loaded = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                loaded.push(serde_json::from_str::<OrderBookMessage>(&line)?);
+            }
+        }
+        let start = loaded.len().saturating_sub(MAX_PERSISTED_HISTORY);
+        let count = loaded.len() - start;
+        self.message_history.splice(0..0, loaded.drain(start..));
+        Ok(count)
+    }
+
+    pub fn pending_writes(&self) -> usize {
+        self.write_buffer.len()
+    }
+
+    pub fn dropped_writes(&self) -> u64 {
+        self.write_buffer.dropped()
+    }
+
+    pub fn analyze_known_fingerprints(&self) -> Vec<(String, String)> {
+        self.known_fingerprints_for(&self.current_symbol)
+    }
+
+    fn known_fingerprints_for(&self, symbol: &str) -> Vec<(String, String)> {
+        let mut results = Vec::new();
+        if self.fingerprints.is_empty() {
+            return results;
+        }
+        if let Some(order_book) = self.order_books.get(symbol) {
+            for order in order_book.bids.iter().chain(order_book.asks.iter()) {
+                if let Ok(quantity) = order.quantity.parse::<f64>() {
+                    if let Some(fingerprint) = self.fingerprints.find(quantity) {
+                        results.push((order.price.clone(), fingerprint.indicator()));
+                    }
+                }
+            }
+        }
+        results
+    }
+
+    // Market maker footprint of the current symbol's book, 0.0 when it can't be scored
+    pub fn analyze_mm_footprint(&self) -> f64 {
+        self.order_books
+            .get(&self.current_symbol)
+            .and_then(|book| book.mm_footprint())
+            .unwrap_or(0.0)
+    }
+
+    // (price, is_round) per level of the current symbol; empty with the heuristic off
+    pub fn analyze_round_notional(&self) -> Vec<(String, bool)> {
+        let (Some(order_book), Some(round_notional)) = (
+            self.order_books.get(&self.current_symbol),
+            self.round_notional.as_ref(),
+        ) else {
+            return Vec::new();
+        };
+        order_book
+            .bids
+            .iter()
+            .chain(order_book.asks.iter())
+            .map(|order| (order.price.clone(), round_notional.is_round(order.total())))
+            .collect()
+    }
+
+    // (quantity, is_human) per level of the current symbol; empty without a known step size
+    pub fn analyze_step_alignment(&self) -> Vec<(String, bool)> {
+        let mut results = Vec::new();
+        let (Some(order_book), Some(filters)) = (
+            self.order_books.get(&self.current_symbol),
+            self.symbol_filters.get(&self.current_symbol),
+        ) else {
+            return results;
+        };
+        for order in order_book.bids.iter().chain(order_book.asks.iter()) {
+            if let Ok(quantity) = order.quantity.parse::<Decimal>() {
+                if let Some(is_human) = step_alignment(quantity, filters.step_size) {
+                    results.push((order.quantity.clone(), is_human));
+                }
+            }
+        }
+        results
+    }
+
+    fn tag_fingerprinted_levels(&mut self, symbol: &str, matches: &HashMap<String, String>) {
+        if matches.is_empty() {
+            return;
+        }
+        if let Some(order_book) = self.order_books.get_mut(symbol) {
+            for order in order_book.bids.iter_mut().chain(order_book.asks.iter_mut()) {
+                if let Some(indicator) = matches.get(&order.price) {
+                    order.is_likely_human = false;
+                    order.add_indicator(indicator, self.max_indicators);
+                }
+            }
+        }
+    }
+
+    // Replaces the tracked symbols, e.g. with a list loaded from --symbols-file
+    pub fn set_symbols(&mut self, symbols: &[String]) {
+        if symbols.is_empty() {
+            return;
+        }
+        self.order_books = symbols
+            .iter()
+            .map(|symbol| (symbol.to_uppercase(), OrderBook::new()))
+            .collect();
+        if !self.order_books.contains_key(&self.current_symbol) {
+            self.current_symbol = symbols[0].to_uppercase();
+        }
+    }
+
+    // Stops tracking symbols the exchange doesn't list, remembering them for the UI
+    pub fn reject_symbols(&mut self, rejected: &[String]) {
+        for symbol in rejected {
+            let symbol = symbol.to_uppercase();
+            self.order_books.remove(&symbol);
+            if !self.rejected_symbols.contains(&symbol) {
+                self.rejected_symbols.push(symbol);
+            }
+        }
+        if !self.order_books.contains_key(&self.current_symbol) {
+            if let Some(first) = self.overview_symbols().first() {
+                self.current_symbol = first.clone();
+            }
+        }
+    }
+
+    pub fn display_name(&self, symbol: &str) -> String {
+        self.aliases.display(symbol)
+    }
+
+    // Switches the book panel between raw levels and price buckets
+    pub fn toggle_grouping(&mut self) {
+        self.group_bucket = match self.group_bucket {
+            Some(_) => None,
+            None => {
+                let book = self.order_books.get(&self.current_symbol);
+                let auto = book.and_then(|b| b.default_bucket_size());
+                // Buckets smaller than ten ticks add nothing over the raw levels
+                let min = self
+                    .symbol_filters
+                    .get(&self.current_symbol)
+                    .and_then(|f| (f.tick_size * Decimal::TEN).to_f64());
+                match (auto, min) {
+                    (Some(auto), Some(min)) => Some(auto.max(min)),
+                    (auto, _) => auto,
+                }
+            }
+        };
+    }
+
+    pub fn scale_group_bucket(&mut self, factor: f64) {
+        if let Some(bucket) = &mut self.group_bucket {
+            *bucket *= factor;
+        }
+    }
+
+    pub fn adjust_display_levels(&mut self, delta: isize) {
+        self.display_levels = self
+            .display_levels
+            .saturating_add_signed(delta)
+            .clamp(1, MAX_DISPLAY_LEVELS);
+    }
+
+    // Same classification as analysis: a known fingerprint is a bot, otherwise the score decides
+    pub fn is_human_level(&self, symbol: &str, orders: &[OrderBookEntry], index: usize) -> bool {
+        let Some(order) = orders.get(index) else {
+            return false;
+        };
+        if let Ok(quantity) = order.quantity.parse::<f64>() {
+            if self.fingerprints.find(quantity).is_some() {
+                return false;
+            }
+        }
+        LevelSignals::compute(
+            orders,
+            index,
+            self.symbol_filters.get(symbol),
+            self.round_notional.as_ref(),
+        )
+        .is_some_and(|signals| signals.human_score() > self.threshold_for(symbol))
+    }
+
+    // Three-way version of is_human_level; fingerprinted sizes are always likely bot
+    pub fn level_bucket(
+        &self,
+        symbol: &str,
+        orders: &[OrderBookEntry],
+        index: usize,
+    ) -> Option<ConfidenceBucket> {
+        let order = orders.get(index)?;
+        if let Ok(quantity) = order.quantity.parse::<f64>() {
+            if self.fingerprints.find(quantity).is_some() {
+                return Some(ConfidenceBucket::LikelyBot);
+            }
+        }
+        LevelSignals::compute(
+            orders,
+            index,
+            self.symbol_filters.get(symbol),
+            self.round_notional.as_ref(),
+        )
+        .map(|signals| {
+            let score = signals.human_score();
+            match self.tuning {
+                // Two-way while tuning, so the book shows exactly what the threshold counts
+                true if score > self.threshold_for(symbol) => ConfidenceBucket::LikelyHuman,
+                true => ConfidenceBucket::LikelyBot,
+                false => self.cutoffs.classify(score),
+            }
+        })
+    }
+
+    // Human score per displayed level from the cached analysis, in book panel order (asks
+    // from the top of the panel down to the best ask, then bids). None where unscored.
+    pub fn heat_row(&self, symbol: &str) -> Vec<Option<f64>> {
+        let Some(book) = self.order_books.get(symbol) else {
+            return Vec::new();
+        };
+        let scores = self.cached_analysis(symbol).map(|a| &a.confidence_scores);
+        let score = |order: &OrderBookEntry| scores.and_then(|s| s.get(&order.price)).copied();
+        let asks = book.asks.iter().take(self.display_levels).rev();
+        let bids = book.bids.iter().take(self.display_levels);
+        asks.chain(bids).map(score).collect()
+    }
+
+    pub fn select_symbol(&mut self, symbol: &str) {
+        if self.order_books.contains_key(symbol) {
+            self.current_symbol = symbol.to_string();
+            self.tape_scroll = 0;
+            self.group_bucket = None;
+        }
+    }
+
+    // Visible symbols in display order: pinned symbols in pin order, then by latest human
+    // ratio (highest first), then name. Hidden symbols are still tracked and stored.
+    pub fn overview_symbols(&self) -> Vec<String> {
+        let pin_rank = |symbol: &str| {
+            self.pinned_symbols
+                .iter()
+                .position(|p| p == symbol)
+                .unwrap_or(usize::MAX)
+        };
+        let latest_ratio = |symbol: &str| {
+            self.ratio_samples
+                .get(symbol)
+                .and_then(|s| s.back().copied())
+                .unwrap_or(-1.0)
+        };
+
+        let mut symbols: Vec<String> = self
+            .order_books
+            .keys()
+            .filter(|symbol| !self.hidden_symbols.contains(*symbol))
+            .cloned()
+            .collect();
+        symbols.sort_by(|a, b| {
+            pin_rank(a)
+                .cmp(&pin_rank(b))
+                .then_with(|| {
+                    latest_ratio(b)
+                        .partial_cmp(&latest_ratio(a))
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .then_with(|| a.cmp(b))
+        });
+        symbols
+    }
+
+    pub fn start_annotation(&mut self) {
+        self.annotation = Some(String::new());
+    }
+
+    // Stores the note being typed against the current symbol and its top of book. A blank
+    // note is dropped; either way the input line closes. During playback the note is
+    // stamped with the recording time being shown.
+    pub fn save_annotation(&mut self) -> Result<(), AppError> {
+        let Some(note) = self.annotation.take() else {
+            return Ok(());
+        };
+        let note = note.trim();
+        if note.is_empty() {
+            return Ok(());
+        }
+        let book = self.order_books.get(&self.current_symbol);
+        let top = |side: Option<&Vec<OrderBookEntry>>| {
+            side.and_then(|levels| levels.first())
+                .map(|level| (level.price.clone(), level.quantity.clone()))
+                .unzip()
+        };
+        let (bid_price, bid_quantity) = top(book.map(|b| &b.bids));
+        let (ask_price, ask_quantity) = top(book.map(|b| &b.asks));
+        let timestamp = match &self.playback {
+            Some(playback) => playback.position() / 1000,
+            None => self.clock.unix_secs(),
+        };
+        let db = self.annotation_db.as_ref().unwrap_or(&self.db);
+        db.insert_annotation(&db::AnnotationRecord {
+            symbol: self.current_symbol.clone(),
+            timestamp,
+            note: note.to_string(),
+            bid_price,
+            bid_quantity,
+            ask_price,
+            ask_quantity,
+        })?;
+        Ok(())
+    }
+
+    // Time left before analysis starts being stored, None once warmed up
+    pub fn warmup_remaining(&self) -> Option<Duration> {
+        let elapsed = self.clock.now().duration_since(self.started);
+        Some(self.warmup.saturating_sub(elapsed)).filter(|left| !left.is_zero())
+    }
+
+    // Exit summary of everything seen since the app was built
+    pub fn session_summary(&self) -> String {
+        self.session
+            .report(self.clock.now().duration_since(self.started))
+    }
+
+    // Replaces the system clock, e.g. with clock::MockClock in tests
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.throughput.0 = clock.now();
+        self.displayed_analyses.0 = clock.now();
+        self.started = clock.now();
+        self.clock = clock;
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    // Moves to the next symbol when the rotation timer is due
+    pub fn tick_rotation(&mut self, now: Instant) {
+        if self.rotation.as_mut().is_some_and(|r| r.due(now)) {
+            self.next_symbol();
+        }
+    }
+
+    pub fn next_symbol(&mut self) {
+        let symbols = self.overview_symbols();
+        if symbols.is_empty() {
+            return;
+        }
+        let next = match symbols.iter().position(|s| s == &self.current_symbol) {
+            Some(pos) => (pos + 1) % symbols.len(),
+            None => 0,
+        };
+        self.current_symbol = symbols[next].clone();
+        self.tape_scroll = 0;
+        self.group_bucket = None;
+    }
+
+    // Runs the human alert against the current symbol's latest analysis
+    pub fn check_human_alert(&mut self) -> bool {
+        let Some(likely_human) = self
+            .cached_analysis(&self.current_symbol)
+            .map(|analysis| analysis.likely_human_orders)
+        else {
+            return false;
+        };
+        self.human_alert.check(likely_human, self.clock.now())
+    }
+
+    // Human ratio across `symbols`, each weighted by its book's total notional so the most
+    // liquid pairs dominate. Symbols without analysis or book depth, or stale, are left out.
+    pub fn market_index(&self, symbols: &[String]) -> Option<f64> {
+        let (weighted, weight) = symbols
+            .iter()
+            .filter(|symbol| !self.is_stale(symbol))
+            .filter_map(|symbol| {
+                let analysis = self.cached_analysis(symbol)?;
+                let book = self.order_books.get(symbol)?;
+                let notional: f64 = book.bids.iter().chain(&book.asks).map(|o| o.total()).sum();
+                if analysis.total_orders == 0 || notional <= 0.0 {
+                    return None;
+                }
+                let ratio = analysis.likely_human_orders as f64 / analysis.total_orders as f64;
+                Some((ratio * notional, notional))
+            })
+            .fold((0.0, 0.0), |(sum, total), (value, notional)| {
+                (sum + value, total + notional)
+            });
+        (weight > 0.0).then(|| weighted / weight)
+    }
+
+    // Checks every symbol against the capture triggers and writes a bundle (book, recent
+    // messages, recent analysis) for each that fires. Returns the files written.
+    pub fn capture_events(&mut self) -> Result<Vec<std::path::PathBuf>, AppError> {
+        use capture::CaptureTrigger;
+
+        let Some(capture) = &self.capture else {
+            return Ok(Vec::new());
+        };
+        let mut fired = Vec::new();
+        for (symbol, book) in &self.order_books {
+            for &trigger in &capture.triggers {
+                let met = match trigger {
+                    CaptureTrigger::HumanSpike => self
+                        .cached_analysis(symbol)
+                        .is_some_and(|a| a.likely_human_orders >= self.human_alert.threshold),
+                    CaptureTrigger::ImbalanceSwing => match self.imbalance_history(symbol, 2)[..] {
+                        [before, after] => (after - before).abs() >= capture.imbalance_swing,
+                        _ => false,
+                    },
+                    CaptureTrigger::CrossedBook => matches!(
+                        (book.best_bid(), book.best_ask()),
+                        (Some(bid), Some(ask)) if bid >= ask
+                    ),
+                };
+                if met {
+                    fired.push((symbol.clone(), trigger));
+                }
+            }
+        }
+        fired.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let now = self.clock.now();
+        let cooldown = self.human_alert.cooldown;
+        let mut written = Vec::new();
+        for (symbol, trigger) in fired {
+            let Some(capture) = self.capture.as_mut() else {
+                break;
+            };
+            if !capture.ready(&symbol, trigger, cooldown, now) {
+                continue;
+            }
+            let ts = self.clock.unix_millis();
+            let path = capture.bundle_path(&symbol, trigger, ts);
+            let bundle = self.capture_bundle(&symbol, trigger, ts)?;
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(&path, serde_json::to_string_pretty(&bundle)?)?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+
+    fn capture_bundle(
+        &self,
+        symbol: &str,
+        trigger: capture::CaptureTrigger,
+        ts: u64,
+    ) -> Result<serde_json::Value, AppError> {
+        let levels = |orders: &[OrderBookEntry]| -> Vec<[String; 2]> {
+            orders
+                .iter()
+                .map(|o| [o.price.clone(), o.quantity.clone()])
+                .collect()
+        };
+        let book = self.order_books.get(symbol);
+        let mut messages: Vec<&OrderBookMessage> = self
+            .message_history
+            .iter()
+            .rev()
+            .filter(|m| m.symbol == symbol)
+            .take(capture::CAPTURE_MESSAGES)
+            .collect();
+        messages.reverse();
+        let recent_analysis = self
+            .analysis_store()
+            .get_analysis_history(symbol, capture::CAPTURE_ANALYSIS_ROWS as i64)?;
+
+        Ok(serde_json::json!({
+            "symbol": symbol,
+            "trigger": trigger.name(),
+            "capturedAt": ts,
+            "bids": book.map(|b| levels(&b.bids)).unwrap_or_default(),
+            "asks": book.map(|b| levels(&b.asks)).unwrap_or_default(),
+            "messages": messages,
+            "analysis": self.cached_analysis(symbol),
+            "recentAnalysis": recent_analysis,
+            "imbalance": self.imbalance_history(symbol, DEFAULT_IMBALANCE_WINDOWS),
+        }))
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        if !self.paused {
+            // Windows open before the pause would close at once and span it; start afresh
+            self.analysis_windows.clear();
+            // Replay every update received while paused, in arrival order, so the book ends
+            // up where the feed is. A symbol whose backlog overflowed waits for a snapshot.
+            let symbols: Vec<String> = self.pending_updates.keys().cloned().collect();
+            for symbol in symbols {
+                if self.awaiting_snapshot.contains(&symbol) {
+                    self.request_resync(symbol);
+                } else {
+                    self.replay_pending(&symbol);
+                }
+            }
+        }
+    }
+
+    pub fn receive_update(&mut self, result: serde_json::Value) {
+        if let Some(error) = fatal_error(&result) {
+            self.feed_error = Some(error.to_string());
+            return;
+        }
+        if result.get("e").and_then(|e| e.as_str()) == Some("aggTrade") {
+            if let Some(mut trade) = Trade::from_agg_trade(&result) {
+                trade.timestamp = self.clock.now();
+                self.record_trade(trade);
+            }
+            return;
+        }
+        if result.get("e").and_then(|e| e.as_str()) == Some("forceOrder") {
+            if let Some(mut liquidation) = Liquidation::from_force_order(&result) {
+                liquidation.timestamp = self.clock.now();
+                self.record_liquidation(liquidation);
+            }
+            return;
+        }
+
+        let Some(symbol) = result.get("symbol").and_then(|s| s.as_str()) else {
+            self.update_orders(&result);
+            return;
+        };
+        let symbol = symbol.to_string();
+        let snapshot = result.get("eventTime").is_none();
+        if !self.paused && self.awaiting_snapshot.contains(&symbol) && snapshot {
+            // Diffs held back since the overflow that predate the snapshot are dropped as
+            // out of order; the rest bring it up to date
+            self.awaiting_snapshot.remove(&symbol);
+            self.update_orders(&result);
+            self.replay_pending(&symbol);
+        } else if self.paused || self.awaiting_snapshot.contains(&symbol) {
+            self.hold_back(symbol, result);
+        } else {
+            self.update_orders(&result);
+        }
+    }
+
+    // Past MAX_PENDING_UPDATES the backlog is dropped and the symbol resyncs from a REST
+    // snapshot, requested now unless paused (toggle_pause defers it to the resume)
+    fn hold_back(&mut self, symbol: String, result: serde_json::Value) {
+        let pending = self.pending_updates.entry(symbol.clone()).or_default();
+        if pending.len() < MAX_PENDING_UPDATES {
+            pending.push_back(result);
+            return;
+        }
+        pending.clear();
+        warn!(
+            symbol,
+            "Too many book updates held back; resyncing from a snapshot"
+        );
+        self.awaiting_snapshot.insert(symbol.clone());
+        if !self.paused {
+            self.request_resync(symbol);
+        }
+    }
+
+    fn request_resync(&mut self, symbol: String) {
+        if !self.resync_requests.contains(&symbol) {
+            self.resync_requests.push(symbol);
+        }
+    }
+
+    fn replay_pending(&mut self, symbol: &str) {
+        let pending = self.pending_updates.remove(symbol).unwrap_or_default();
+        for result in &pending {
+            self.update_orders(result);
+        }
+    }
+
+    // The combined stream gives no ordering across frames, so a delayed update can arrive
+    // after a newer one for the same symbol; only strictly newer ids are applied. REST
+    // snapshots (no event time) are authoritative and restart the sequence, which also
+    // covers ids starting over after a reconnect. Messages without an id always apply.
+    fn in_sequence(&mut self, symbol: &str, result: &serde_json::Value) -> bool {
+        let Some(id) = result.get("lastUpdateId").and_then(|id| id.as_u64()) else {
+            return true;
+        };
+        let snapshot = result.get("eventTime").is_none();
+        match self.last_update_ids.get(symbol) {
+            Some(&last) if !snapshot && id <= last => {
+                self.out_of_order += 1;
+                warn!(symbol, id, last, "Dropped out-of-order book update");
+                false
+            }
+            _ => {
+                self.last_update_ids.insert(symbol.to_string(), id);
+                true
+            }
+        }
+    }
+
+    pub fn last_update_id(&self, symbol: &str) -> Option<u64> {
+        self.last_update_ids.get(symbol).copied()
+    }
+
+    // Book updates dropped for arriving after a newer one
+    pub fn out_of_order_updates(&self) -> u64 {
+        self.out_of_order
+    }
+
+    fn count_message(&mut self) {
+        let now = self.clock.now();
+        let (start, count, rate) = &mut self.throughput;
+        let elapsed = now.duration_since(*start);
+        if elapsed >= Duration::from_secs(1) {
+            *rate = *count as f64 / elapsed.as_secs_f64();
+            *start = now;
+            *count = 0;
+        }
+        *count += 1;
+    }
+
+    // Book updates processed per second over the last full window
+    pub fn messages_per_sec(&self) -> f64 {
+        let (start, _, rate) = self.throughput;
+        // Report zero once updates stop rather than freezing the last rate
+        if self.clock.now().duration_since(start) >= Duration::from_secs(2) {
+            0.0
+        } else {
+            rate
+        }
+    }
+
+    // Symbols whose book has levels and was updated within stale_after
+    pub fn active_feeds(&self) -> usize {
+        self.order_books
+            .values()
+            .filter(|book| !book.is_empty() && self.since_update(book) < self.stale_after)
+            .count()
+    }
+
+    // A book that had levels but hasn't been updated within stale_after. Its levels are
+    // kept for display but left out of stored analysis and the market-wide figures.
+    pub fn is_stale(&self, symbol: &str) -> bool {
+        self.order_books
+            .get(symbol)
+            .is_some_and(|book| !book.is_empty() && self.since_update(book) >= self.stale_after)
+    }
+
+    // Age of a book by the app clock
+    pub fn since_update(&self, book: &OrderBook) -> Duration {
+        self.clock.now().saturating_duration_since(book.last_update)
+    }
+
+    // The all-market stream covers every futures symbol; only tracked ones are kept
+    pub fn record_liquidation(&mut self, liquidation: Liquidation) {
+        if !self.order_books.contains_key(&liquidation.symbol) {
+            return;
+        }
+        if self.liquidations.len() == LIQUIDATION_CAPACITY {
+            self.liquidations.pop_front();
+        }
+        let symbol = liquidation.symbol.clone();
+        self.liquidations.push_back(liquidation);
+        self.mark_liquidations(&symbol);
+    }
+
+    // Indicators are reset on every update, so recent liquidations are re-marked each time.
+    // A sell liquidation hits the bids and a buy hits the asks; the closest price is marked.
+    fn mark_liquidations(&mut self, symbol: &str) {
+        let now = self.clock.now();
+        let Some(book) = self.order_books.get_mut(symbol) else {
+            return;
+        };
+        for liquidation in self.liquidations.iter().rev() {
+            if now.duration_since(liquidation.timestamp) > LIQUIDATION_MARK_FOR {
+                break;
+            }
+            if liquidation.symbol != symbol {
+                continue;
+            }
+            let levels = match liquidation.side {
+                TradeSide::Sell => &mut book.bids,
+                TradeSide::Buy => &mut book.asks,
+            };
+            let distance = |level: &OrderBookEntry| {
+                (level.price.parse::<f64>().unwrap_or(f64::INFINITY) - liquidation.price).abs()
+            };
+            if let Some(level) = levels
+                .iter_mut()
+                .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            {
+                level.add_indicator(LIQUIDATION_INDICATOR, book.max_indicators);
+            }
+        }
+    }
+
+    // Moves the tape panel by `delta` trades, stopping once the oldest shown trade for the
+    // current symbol reaches the bottom of the `visible` rows
+    pub fn scroll_tape(&mut self, delta: isize, visible: usize) {
+        let trades = if self.trade_stream {
+            &self.trades
+        } else {
+            &self.tape
+        };
+        let shown = trades
+            .iter()
+            .filter(|trade| trade.symbol == self.current_symbol)
+            .count();
+        self.tape_scroll = self
+            .tape_scroll
+            .saturating_add_signed(delta)
+            .min(shown.saturating_sub(visible));
+    }
+
+    pub fn record_trade(&mut self, trade: Trade) {
+        if self.trades.len() == TAPE_CAPACITY {
+            self.trades.pop_front();
+        }
+        self.trades.push_back(trade);
+    }
+
+    // Re-runs the current classifier config over stored snapshots with timestamps in
+    // [start_ts, end_ts] (seconds), one record per snapshot. Live state is untouched.
+    pub fn reanalyze_snapshots(
+        &self,
+        symbol: &str,
+        start_ts: u64,
+        end_ts: u64,
+    ) -> Result<Vec<db::MarketAnalysisRecord>, AppError> {
+        let snapshots =
+            self.db
+                .get_snapshots_between(symbol, start_ts * 1000, end_ts * 1000 + 999)?;
+        let min_notional = self.notional_filter.threshold(symbol);
+        let mut records = Vec::with_capacity(snapshots.len());
+        for snapshot in snapshots {
+            let levels = serde_json::json!({
+                "bids": serde_json::from_str::<serde_json::Value>(&snapshot.bids)?,
+                "asks": serde_json::from_str::<serde_json::Value>(&snapshot.asks)?,
+            });
+            let mut book = OrderBook::new();
+            book.replace_levels(
+                &levels,
+                min_notional,
+                self.duplicate_policy,
+                self.clock.now(),
+            );
+
+            let total_orders = book.bids.len() + book.asks.len();
+            let human_orders = [&book.bids, &book.asks]
+                .into_iter()
+                .map(|orders| {
+                    (0..orders.len())
+                        .filter(|&index| self.is_human_level(symbol, orders, index))
+                        .count()
+                })
+                .sum::<usize>();
+            records.push(db::MarketAnalysisRecord {
+                symbol: symbol.to_string(),
+                timestamp: snapshot.timestamp / 1000,
+                total_orders: total_orders as i64,
+                human_orders: human_orders as i64,
+                bot_orders: (total_orders - human_orders) as i64,
+                human_ratio: if total_orders > 0 {
+                    human_orders as f64 / total_orders as f64
+                } else {
+                    0.0
+                },
+            });
+        }
+        Ok(records)
+    }
+
+    // Splits [start_ts, end_ts] (seconds) into `interval` buckets and, for each bucket with
+    // no market_analysis rows, reanalyzes its stored snapshots into market_analysis.
+    // Buckets that already have analysis are left alone.
+    pub fn backfill_analysis(
+        &self,
+        symbol: &str,
+        start_ts: u64,
+        end_ts: u64,
+        interval: Duration,
+    ) -> Result<BackfillReport, AppError> {
+        let step = interval.as_secs().max(1);
+        let existing: Vec<u64> = self
+            .analysis_store()
+            .get_analysis_between(symbol, start_ts, end_ts)?
+            .into_iter()
+            .map(|record| record.timestamp)
+            .collect();
+
+        let mut report = BackfillReport::default();
+        let mut bucket_start = start_ts;
+        while bucket_start <= end_ts {
+            let bucket_end = bucket_start.saturating_add(step - 1).min(end_ts);
+            if !existing
+                .iter()
+                .any(|&ts| (bucket_start..=bucket_end).contains(&ts))
+            {
+                report.missing += 1;
+                let records = self.reanalyze_snapshots(symbol, bucket_start, bucket_end)?;
+                if !records.is_empty() {
+                    report.filled += 1;
+                }
+                for record in &records {
+                    self.analysis_store().insert_analysis(record)?;
+                    report.inserted += 1;
+                }
+            }
+            bucket_start = match bucket_end.checked_add(1) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        Ok(report)
+    }
+
+    // Writes the full book when the snapshot policy allows it
+    fn store_snapshot(&mut self, symbol: &str) {
+        let (Some(policy), Some(book)) = (self.snapshot_policy, self.order_books.get(symbol))
+        else {
+            return;
+        };
+        let (Some(best_bid), Some(best_ask)) = (book.best_bid(), book.best_ask()) else {
+            return;
+        };
+        let now = self.clock.now();
+        if !policy.should_store(self.last_snapshot.get(symbol), best_bid, best_ask, now) {
+            return;
+        }
+        let levels = |orders: &[OrderBookEntry]| {
+            let pairs: Vec<[&str; 2]> = orders
+                .iter()
+                .map(|o| [o.price.as_str(), o.quantity.as_str()])
+                .collect();
+            serde_json::to_string(&pairs).unwrap_or_default()
+        };
+        let record = db::BookSnapshotRecord {
+            symbol: symbol.to_string(),
+            timestamp: self.clock.unix_millis(),
+            bids: levels(&book.bids),
+            asks: levels(&book.asks),
+        };
+        match self.db.insert_snapshot(&record) {
+            Ok(()) => {
+                self.last_snapshot.insert(
+                    symbol.to_string(),
+                    db::LastSnapshot {
+                        best_bid,
+                        best_ask,
+                        at: now,
+                    },
+                );
+            }
+            Err(e) => error!(symbol, error = %e, "Failed to store book snapshot"),
+        }
+    }
+
+    pub fn update_orders(&mut self, result: &serde_json::Value) {
+        if let Some(symbol) = result.get("symbol").and_then(|s| s.as_str()) {
+            if !self.in_sequence(symbol, result) {
+                return;
+            }
+            let min_notional = self.notional_filter.threshold(symbol);
+            self.count_message();
+            if let Some(order_book) = self.order_books.get_mut(symbol) {
+                self.dirty.insert(symbol.to_string());
+                // REST snapshots carry no event time; diffing them against a stale book
+                // would report a burst of fake fills after every reconnect
+                if result.get("eventTime").is_some() {
+                    let fills = order_book.inferred_fills(result, &self.fill_inference);
+                    for (price, quantity, side) in fills {
+                        if self.tape.len() == TAPE_CAPACITY {
+                            self.tape.pop_front();
+                        }
+                        self.tape.push_back(Trade {
+                            timestamp: self.clock.now(),
+                            symbol: symbol.to_string(),
+                            price,
+                            quantity,
+                            side,
+                            inferred: true,
+                            trade_time: None,
+                        });
+                    }
+                }
+                order_book.max_indicators = self.max_indicators;
+                order_book.depth = self.book_depth;
+                let now = self.clock.now();
+                let merged = order_book.apply(result, min_notional, self.duplicate_policy, now);
+                let spoofs = order_book.new_spoofs();
+                self.session
+                    .record_update(symbol, order_book.spread(), order_book.imbalance());
+                if merged > 0 && self.validate_book {
+                    warn!(
+                        symbol,
+                        merged,
+                        policy = ?self.duplicate_policy,
+                        "Book update contained duplicate price levels"
+                    );
+                }
+
+                // Add one message per side so the history reflects both bids and asks
+                if let Some(bid) = order_book.bids.first() {
+                    self.message_history.push(OrderBookMessage::new(
+                        symbol,
+                        bid,
+                        OrderSide::Bid,
+                        now,
+                        self.clock.unix_millis(),
+                    ));
+                }
+                if let Some(ask) = order_book.asks.first() {
+                    self.message_history.push(OrderBookMessage::new(
+                        symbol,
+                        ask,
+                        OrderSide::Ask,
+                        now,
+                        self.clock.unix_millis(),
+                    ));
+                }
+
+                if spoofs > 0 {
+                    if let Err(e) = self.db.add_spoofs(symbol, spoofs as i64) {
+                        error!(symbol, error = %e, "Failed to count spoofs");
+                    }
+                }
+                self.mark_liquidations(symbol);
+                self.record_latency(symbol, result);
+                self.store_snapshot(symbol);
+
+                // Keep message history size reasonable
+                if self.message_history.len() > 10000 {
+                    self.message_history.drain(0..5000);
+                }
+            }
         }
     }
 }
@@ -0,0 +1,79 @@
+use binance_ws::{fatal_error, App, AppError};
+use serde_json::Value;
+use std::io::{self, IsTerminal, Write};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+pub async fn run(
+    mut app: App,
+    mut rx: mpsc::Receiver<Value>,
+    symbol: &str,
+    interval: Duration,
+) -> Result<(), AppError> {
+    let symbol = symbol.to_uppercase();
+    if !app.order_books.contains_key(&symbol) {
+        return Err(AppError::UnknownSymbol(symbol));
+    }
+    app.current_symbol = symbol;
+
+    // Overwrite the line in place on a terminal, emit one line per tick when piped
+    let overwrite = io::stdout().is_terminal();
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = ticker.tick() => {
+                while let Ok(result) = rx.try_recv() {
+                    app.update_orders(&result);
+                    if let Some(error) = fatal_error(&result) {
+                        eprintln!("\nFeed stopped: {}", error);
+                        return Ok(());
+                    }
+                }
+
+                let line = summary_line(&mut app);
+                let mut stdout = io::stdout().lock();
+                if overwrite {
+                    write!(stdout, "\r\x1b[2K{}", line)?;
+                } else {
+                    writeln!(stdout, "{}", line)?;
+                }
+                stdout.flush()?;
+            }
+        }
+    }
+
+    if overwrite {
+        println!();
+    }
+    Ok(())
+}
+
+pub fn summary_line(app: &mut App) -> String {
+    let analysis = app.analyze_market();
+    let human_ratio = if analysis.total_orders > 0 {
+        format!(
+            "{:.1}%",
+            (analysis.likely_human_orders as f64 / analysis.total_orders as f64) * 100.0
+        )
+    } else {
+        "n/a".to_string()
+    };
+
+    let order_book = app.order_books.get(&app.current_symbol);
+    let spread = order_book
+        .and_then(|book| book.spread())
+        .map_or("n/a".to_string(), |spread| format!("{:.4}", spread));
+    let imbalance = order_book
+        .and_then(|book| book.imbalance())
+        .map_or("n/a".to_string(), |imbalance| format!("{:+.2}", imbalance));
+
+    format!(
+        "{} human {} | spread {} | imbalance {}",
+        app.display_name(&app.current_symbol),
+        human_ratio,
+        spread,
+        imbalance
+    )
+}
@@ -1,27 +1,70 @@
+mod cli;
+mod compare;
+mod keys;
+mod line;
+mod replay;
 mod ui;
 
-use binance_ws::App;
+use binance_ws::{
+    capture::EventCapture,
+    config::DEFAULT_CONFIG,
+    db::{get_current_timestamp_millis, AnalysisStore, Database},
+    depth_chart,
+    exchange::{
+        is_invalid_symbol_error, parse_depth_snapshot, parse_exchange_info, SymbolFilters,
+        UpdateSpeed,
+    },
+    file_store::{CsvSink, CsvStore, SignalDump, StoreBackend},
+    fingerprints::FingerprintSet,
+    reconnect_delay, reconnect_limit_reached,
+    recording::{Recorder, Replayer, DEFAULT_SNAPSHOT_INTERVAL_MS},
+    telemetry, transform_depth_message,
+    watchdog::{self, Heartbeat},
+    App, AppError, ConfidenceCutoffs, FillInference, OrderBook, Rotation, MAX_DISPLAY_LEVELS,
+    THRESHOLD_STEP,
+};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, MouseButton, MouseEvent,
+        MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use futures_util::StreamExt;
+use keys::Action;
 use ratatui::{backend::CrosstermBackend, Terminal};
 use serde_json::{json, Value};
 use std::{
-    io,
+    collections::HashMap,
+    io::{self, Write},
+    path::Path,
     time::{Duration, Instant},
 };
 use tokio::sync::mpsc;
 use tokio::time::sleep;
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use tracing::{debug, error, info, instrument, warn};
+use tracing_subscriber::EnvFilter;
 use url::Url;
 
 const RECONNECT_DELAY: Duration = Duration::from_secs(5);
-const SYMBOLS: &[&str] = &["btcusdt", "ethusdt", "bnbusdt", "xrpusdt"];
-const UPDATE_SPEED: &str = "100ms"; // Options: 100ms, 1000ms
-const DEPTH_LEVELS: u32 = 20; // Options: 5, 10, 20
+const STALE_TIMEOUT: Duration = Duration::from_secs(10);
+const DEPTH_LEVELS: u32 = 1000; // snapshot depth diffs build on; the app shows fewer
+const HISTORY_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+struct Endpoints {
+    rest: Url,
+    ws: Url,
+    symbols: Vec<String>,
+    update_speed: UpdateSpeed,
+    trades: bool,
+    client: reqwest::Client, // shared so REST calls reuse connections
+    snapshot_concurrency: usize,
+    max_reconnect_attempts: Option<u32>,
+    max_reconnect_delay: Duration,
+}
 
 #[derive(Debug)]
 struct WebSocketState {
@@ -32,58 +75,826 @@ struct WebSocketState {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Terminal setup
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    let cli = cli::Cli::load()?;
+
+    // stdout belongs to the TUI, so diagnostics go to a file filtered by RUST_LOG
+    let log_file = tracing_appender::rolling::never(".", &cli.log_file);
+    let (log_writer, _log_guard) = tracing_appender::non_blocking(log_file);
+    tracing_subscriber::fmt()
+        .with_writer(log_writer)
+        .with_ansi(false)
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .init();
+
+    if cli.metrics_addr.is_some() || cli.pushgateway_url.is_some() {
+        let handle = telemetry::install(cli.metrics_addr)
+            .map_err(|e| format!("Failed to start metrics: {}", e))?;
+        if let Some(addr) = cli.metrics_addr {
+            info!(%addr, "Serving OpenMetrics");
+        }
+        if let Some(gateway) = cli.pushgateway_url.clone() {
+            info!(%gateway, "Pushing metrics to Pushgateway");
+            let interval = Duration::from_secs(cli.push_interval_secs.max(1));
+            tokio::spawn(telemetry::push_loop(handle, gateway, interval));
+        }
+    }
+
+    // Replays read from disk only, so skip the network entirely
+    if let Some(cli::Command::Replay { ref file, at, play }) = cli.command {
+        let app = build_app(&cli, Database::open_in_memory()?)?;
+        if play {
+            replay::play(app, file, at, &cli)?;
+        } else {
+            replay::run(app, file, at)?;
+        }
+        return Ok(());
+    }
+
+    if let Some(cli::Command::Snapshot {
+        ref symbol,
+        depth,
+        store,
+    }) = cli.command
+    {
+        print_snapshot(&cli, symbol, depth, store).await?;
+        return Ok(());
+    }
+
+    if let Some(cli::Command::Export {
+        ref depth_chart,
+        depth,
+        ref recording,
+        at,
+    }) = cli.command
+    {
+        let (symbol, out) = (&depth_chart[0], Path::new(&depth_chart[1]));
+        export_depth_chart(&cli, symbol, out, depth, recording.as_deref().zip(at)).await?;
+        println!("Wrote {}", out.display());
+        return Ok(());
+    }
+
+    if let Some(cli::Command::Config {
+        action: cli::ConfigCommand::Generate { ref out },
+    }) = cli.command
+    {
+        match out {
+            Some(path) => {
+                std::fs::write(path, DEFAULT_CONFIG)?;
+                println!("Wrote {}", path.display());
+            }
+            None => print!("{}", DEFAULT_CONFIG),
+        }
+        return Ok(());
+    }
+
+    if let Some(cli::Command::Reanalyze {
+        ref symbol,
+        from,
+        to,
+        store,
+    }) = cli.command
+    {
+        reanalyze(&cli, symbol, from, to, store)?;
+        return Ok(());
+    }
+
+    if let Some(cli::Command::Find {
+        ref symbol,
+        min_ratio,
+        max_ratio,
+        from,
+        to,
+        limit,
+    }) = cli.command
+    {
+        let to = to.unwrap_or_else(binance_ws::db::get_current_timestamp);
+        find_by_ratio(&cli, symbol, min_ratio, max_ratio, from, to, limit)?;
+        return Ok(());
+    }
+
+    if let Some(cli::Command::Annotations { ref symbol }) = cli.command {
+        list_annotations(symbol.as_deref())?;
+        return Ok(());
+    }
+
+    if let Some(cli::Command::Backfill {
+        ref symbol,
+        from,
+        to,
+        interval_secs,
+    }) = cli.command
+    {
+        let app = open_app(&cli)?;
+        let symbol = symbol.to_uppercase();
+        let report =
+            app.backfill_analysis(&symbol, from, to, Duration::from_secs(interval_secs))?;
+        println!(
+            "Filled {} of {} missing intervals for {} ({} records written)",
+            report.filled, report.missing, symbol, report.inserted
+        );
+        return Ok(());
+    }
+
+    if let Some(cli::Command::Stats) = cli.command {
+        print_stats(&cli)?;
+        return Ok(());
+    }
+
+    if let Some(cli::Command::Vacuum) = cli.command {
+        vacuum()?;
+        return Ok(());
+    }
+
+    let symbol_list = cli
+        .symbol_list()
+        .map_err(|e| format!("Failed to load symbols: {}", e))?;
+    if !symbol_list.invalid.is_empty() {
+        eprintln!(
+            "Skipping invalid symbols: {}",
+            symbol_list.invalid.join(", ")
+        );
+        warn!(invalid = ?symbol_list.invalid, "Skipped invalid symbols");
+    }
+    let symbols = symbol_list.symbols;
+
+    for (alias, clashing) in cli.aliases().collisions(&symbols) {
+        eprintln!(
+            "Display name {} is shared by {}",
+            alias,
+            clashing.join(", ")
+        );
+        warn!(%alias, symbols = ?clashing, "Symbols share a display name");
+    }
+
+    let mut endpoints = Endpoints {
+        rest: cli.rest_url(),
+        ws: cli.ws_url(),
+        symbols: symbols.clone(),
+        update_speed: cli.update_speed,
+        trades: cli.trades,
+        client: reqwest::Client::new(),
+        snapshot_concurrency: cli.snapshot_concurrency,
+        max_reconnect_attempts: cli.max_reconnect_attempts,
+        max_reconnect_delay: Duration::from_secs(cli.max_reconnect_delay_secs),
+    };
+
+    // Step/tick sizes feed the step-alignment heuristic; without them it is skipped
+    let (symbol_filters, rejected) = match fetch_symbol_filters(&endpoints).await {
+        Ok(result) => result,
+        Err(e) => {
+            warn!(error = %e, "Failed to fetch exchangeInfo, step alignment disabled");
+            (HashMap::new(), Vec::new())
+        }
+    };
+    // An unknown symbol would break the combined stream for every other symbol
+    if !rejected.is_empty() {
+        eprintln!(
+            "Dropping symbols unknown to Binance: {}",
+            rejected.join(", ")
+        );
+        warn!(rejected = ?rejected, "Dropping symbols unknown to Binance");
+        endpoints
+            .symbols
+            .retain(|s| !rejected.contains(&s.to_uppercase()));
+        if endpoints.symbols.is_empty() {
+            return Err("none of the configured symbols are listed on Binance".into());
+        }
+    }
 
     // Create channels for communication
     let (tx, mut rx) = mpsc::channel(32);
-    let tx_clone = tx.clone();
 
-    // Spawn WebSocket handler
+    // Tee the feed through the recorder before it reaches the app
+    if let Some(path) = &cli.record {
+        let mut recorder = Recorder::create(path, DEFAULT_SNAPSHOT_INTERVAL_MS)
+            .map_err(|e| format!("Failed to create recording {}: {}", path.display(), e))?;
+        let (recorded_tx, recorded_rx) = mpsc::channel(32);
+        let mut feed = std::mem::replace(&mut rx, recorded_rx);
+        tokio::spawn(async move {
+            while let Some(message) = feed.recv().await {
+                if let Err(e) = recorder.record(&message) {
+                    error!(error = %e, "Failed to record message");
+                }
+                if recorded_tx.send(message).await.is_err() {
+                    break;
+                }
+            }
+            if let Err(e) = recorder.flush() {
+                error!(error = %e, "Failed to flush recording");
+            }
+        });
+    }
+
+    // Liquidations come from a separate futures connection and never end the session
+    if let Some(url) = cli.liquidations_url() {
+        tokio::spawn(run_liquidations(tx.clone(), url));
+    }
+
+    // Symbols whose paused backlog overflowed get a fresh snapshot through the same channel
+    let (resync_tx, resync_rx) = mpsc::unbounded_channel();
+    tokio::spawn(run_resyncs(
+        resync_rx,
+        tx.clone(),
+        endpoints.client.clone(),
+        endpoints.rest.clone(),
+    ));
+
+    // Spawn WebSocket handler; a fatal error is forwarded so the UI can show it
     tokio::spawn(async move {
-        if let Err(e) = run_websocket(tx_clone).await {
-            eprintln!("WebSocket error: {}", e);
+        if let Err(e) = run_websocket(tx.clone(), endpoints).await {
+            error!(error = %e, "WebSocket task exited");
+            let _ = tx.send(json!({ "fatal": e.to_string() })).await;
         }
     });
 
-    // Create app state
-    let mut app = match App::new() {
-        Ok(app) => app,
-        Err(e) => {
-            disable_raw_mode()?;
-            execute!(
-                terminal.backend_mut(),
-                LeaveAlternateScreen,
-                DisableMouseCapture
-            )?;
-            terminal.show_cursor()?;
-            return Err(format!("Failed to initialize application: {}", e).into());
+    match cli.command {
+        Some(cli::Command::Line {
+            ref symbol,
+            interval_ms,
+        }) => {
+            let mut app = open_app(&cli)?;
+            app.symbol_filters = symbol_filters;
+            app.reject_symbols(&rejected);
+            line::run(app, rx, symbol, Duration::from_millis(interval_ms)).await?;
+        }
+        Some(cli::Command::Compare {
+            ref threshold_b,
+            ref fingerprints_b,
+            ref csv,
+            interval_ms,
+        }) => {
+            // Both sides analyze in memory so the comparison never touches the real database
+            let mut app_a = build_app(&cli, Database::open_in_memory()?)?;
+            let mut app_b = build_app(&cli, Database::open_in_memory()?)?;
+            app_a.symbol_filters = symbol_filters.clone();
+            app_b.symbol_filters = symbol_filters;
+            app_a.reject_symbols(&rejected);
+            app_b.reject_symbols(&rejected);
+            if let Some(threshold) = threshold_b {
+                app_b.human_threshold = *threshold;
+            }
+            if let Some(path) = fingerprints_b {
+                app_b.fingerprints = load_fingerprints(path)?;
+            }
+            compare::run(app_a, app_b, rx, csv, Duration::from_millis(interval_ms)).await?;
+        }
+        None => {
+            let mut app = open_app(&cli)?;
+            app.symbol_filters = symbol_filters;
+            app.reject_symbols(&rejected);
+            if let Some(path) = &cli.history_file {
+                match app.load_history(path) {
+                    Ok(count) => info!(count, path = %path.display(), "Reloaded message history"),
+                    Err(e) => {
+                        warn!(error = %e, path = %path.display(), "Failed to reload message history")
+                    }
+                }
+            }
+            run_tui(
+                app,
+                rx,
+                Some(resync_tx),
+                !cli.no_mouse,
+                cli.history_file.as_deref(),
+                Duration::from_secs(cli.stall_threshold_secs.max(1)),
+                Duration::from_secs(cli.checkpoint_minutes.max(1) * 60),
+            )?
+        }
+        Some(cli::Command::Replay { .. })
+        | Some(cli::Command::Stats)
+        | Some(cli::Command::Vacuum)
+        | Some(cli::Command::Config { .. })
+        | Some(cli::Command::Reanalyze { .. })
+        | Some(cli::Command::Backfill { .. })
+        | Some(cli::Command::Find { .. })
+        | Some(cli::Command::Annotations { .. })
+        | Some(cli::Command::Snapshot { .. })
+        | Some(cli::Command::Export { .. }) => {
+            unreachable!("handled before connecting")
+        }
+    }
+
+    Ok(())
+}
+
+// One-shot path for scripts: no streaming, and no DB write unless `store` is set
+async fn print_snapshot(
+    cli: &cli::Cli,
+    symbol: &str,
+    depth: u32,
+    store: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let symbol = symbol.to_lowercase();
+    let client = reqwest::Client::new();
+    let snapshot = fetch_initial_snapshot(&client, &cli.rest_url(), &symbol, depth).await?;
+
+    let mut app = build_app(cli, Database::open_in_memory()?)?;
+    // Analysis lands in the in-memory DB; only `store` writes to the configured backend
+    let analysis_files = app.analysis_files.take();
+    app.csv_sink = None;
+    app.signal_dump = None;
+    app.set_symbols(std::slice::from_ref(&symbol));
+    app.book_depth = depth as usize;
+    app.update_orders(&snapshot);
+    let analysis = app.analyze_market();
+
+    let human_ratio = if analysis.total_orders > 0 {
+        analysis.likely_human_orders as f64 / analysis.total_orders as f64
+    } else {
+        0.0
+    };
+    if store {
+        let record = binance_ws::db::MarketAnalysisRecord {
+            symbol: app.current_symbol.clone(),
+            timestamp: binance_ws::db::get_current_timestamp(),
+            total_orders: analysis.total_orders as i64,
+            human_orders: analysis.likely_human_orders as i64,
+            bot_orders: (analysis.total_orders - analysis.likely_human_orders) as i64,
+            human_ratio,
+        };
+        match analysis_files {
+            Some(files) => files.insert_analysis(&record)?,
+            None => Database::new()?.insert_analysis(&record)?,
+        }
+    }
+
+    let output = json!({
+        "symbol": app.current_symbol,
+        "bids": snapshot["bids"],
+        "asks": snapshot["asks"],
+        "lastUpdateId": snapshot["lastUpdateId"],
+        "human_ratio": human_ratio,
+        "analysis": analysis,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+// Book from a recording at a point in time, or a fresh REST snapshot
+async fn export_depth_chart(
+    cli: &cli::Cli,
+    symbol: &str,
+    out: &Path,
+    depth: u32,
+    recorded: Option<(&Path, u64)>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let symbol = symbol.to_uppercase();
+    let book = match recorded {
+        Some((path, at)) => {
+            let mut replayer = Replayer::open(path)?;
+            let mut book = OrderBook::new();
+            book.depth = depth as usize;
+            for message in replayer.seek(at)? {
+                if message["symbol"] == symbol.as_str() {
+                    book.apply(&message, 0.0, cli.duplicate_levels, Instant::now());
+                }
+            }
+            book
+        }
+        None => {
+            let client = reqwest::Client::new();
+            OrderBook::from_json(
+                &fetch_initial_snapshot(&client, &cli.rest_url(), &symbol, depth).await?,
+            )
+        }
+    };
+    depth_chart::render_svg(&book, &symbol, out)?;
+    Ok(())
+}
+
+// Counts come from the configured analysis store; the size is SQLite's only
+fn print_stats(cli: &cli::Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let store = open_analysis_store(cli)?;
+    for symbol in cli.symbol_list()?.symbols {
+        let symbol = symbol.to_uppercase();
+        println!(
+            "{:<12} {:>10} rows",
+            symbol,
+            store.count_records(Some(&symbol))?
+        );
+    }
+    println!("{:<12} {:>10} rows", "total", store.count_records(None)?);
+    if cli.store_backend == StoreBackend::Sqlite {
+        println!(
+            "{:<12} {:>10.1} MiB",
+            "size",
+            Database::new()?.db_file_size()? as f64 / (1024.0 * 1024.0)
+        );
+    }
+    Ok(())
+}
+
+fn vacuum() -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::new()?;
+    let before = db.db_file_size()?;
+    db.vacuum()?;
+    let mib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+    println!(
+        "Vacuumed: {:.1} MiB -> {:.1} MiB",
+        mib(before),
+        mib(db.db_file_size()?)
+    );
+    Ok(())
+}
+
+fn reanalyze(
+    cli: &cli::Cli,
+    symbol: &str,
+    from: u64,
+    to: u64,
+    store: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let app = open_app(cli)?;
+    let symbol = symbol.to_uppercase();
+    let records = app.reanalyze_snapshots(&symbol, from, to)?;
+    println!(
+        "{:>12} {:>7} {:>7} {:>7}",
+        "timestamp", "total", "human", "ratio"
+    );
+    for record in &records {
+        println!(
+            "{:>12} {:>7} {:>7} {:>6.1}%",
+            record.timestamp,
+            record.total_orders,
+            record.human_orders,
+            record.human_ratio * 100.0
+        );
+        if store {
+            app.db.insert_reanalysis(record)?;
+        }
+    }
+    if records.is_empty() {
+        eprintln!("No snapshots stored for {} in that range", symbol);
+    }
+    Ok(())
+}
+
+fn list_annotations(symbol: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let db = Database::new()?;
+    let symbol = symbol.map(str::to_uppercase);
+    let records = db.get_annotations(symbol.as_deref())?;
+    println!(
+        "{:>12} {:<12} {:>24} {:>24}  note",
+        "timestamp", "symbol", "bid", "ask"
+    );
+    let level = |price: &Option<String>, quantity: &Option<String>| match (price, quantity) {
+        (Some(price), Some(quantity)) => format!("{} x {}", quantity, price),
+        _ => "-".to_string(),
+    };
+    for record in &records {
+        println!(
+            "{:>12} {:<12} {:>24} {:>24}  {}",
+            record.timestamp,
+            record.symbol,
+            level(&record.bid_price, &record.bid_quantity),
+            level(&record.ask_price, &record.ask_quantity),
+            record.note
+        );
+    }
+    if records.is_empty() {
+        eprintln!("No annotations stored");
+    }
+    Ok(())
+}
+
+fn find_by_ratio(
+    cli: &cli::Cli,
+    symbol: &str,
+    min_ratio: f64,
+    max_ratio: f64,
+    from: u64,
+    to: u64,
+    limit: i64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for ratio in [min_ratio, max_ratio] {
+        if !(0.0..=1.0).contains(&ratio) {
+            return Err(format!("human ratios run from 0.0 to 1.0, got {}", ratio).into());
         }
+    }
+    if min_ratio > max_ratio {
+        return Err(format!(
+            "--min-ratio {} is above --max-ratio {}",
+            min_ratio, max_ratio
+        )
+        .into());
+    }
+    if from > to {
+        return Err(format!("--from {} is after --to {}", from, to).into());
+    }
+    let symbol = symbol.to_uppercase();
+    let records =
+        open_analysis_store(cli)?.find_by_ratio(&symbol, min_ratio, max_ratio, from, to, limit)?;
+    println!(
+        "{:>12} {:>7} {:>7} {:>7}",
+        "timestamp", "total", "human", "ratio"
+    );
+    for record in &records {
+        println!(
+            "{:>12} {:>7} {:>7} {:>6.1}%",
+            record.timestamp,
+            record.total_orders,
+            record.human_orders,
+            record.human_ratio * 100.0
+        );
+    }
+    if records.is_empty() {
+        eprintln!(
+            "No analysis for {} with a human ratio between {} and {} from {} to {}",
+            symbol, min_ratio, max_ratio, from, to
+        );
+    }
+    Ok(())
+}
+
+fn load_fingerprints(path: &Path) -> Result<FingerprintSet, Box<dyn std::error::Error>> {
+    FingerprintSet::load(path)
+        .map_err(|e| format!("Failed to load fingerprints from {}: {}", path.display(), e).into())
+}
+
+// Just the configured analysis store, for read-only commands: building an App would also
+// create the CSV sink and signal dump files
+fn open_analysis_store(
+    cli: &cli::Cli,
+) -> Result<Box<dyn AnalysisStore>, Box<dyn std::error::Error>> {
+    Ok(match cli.store_backend {
+        StoreBackend::Sqlite => Box::new(Database::new()?),
+        StoreBackend::Csv => Box::new(CsvStore::open(&cli.data_dir)?),
+    })
+}
+
+// App state backed by the on-disk database
+fn open_app(cli: &cli::Cli) -> Result<App, Box<dyn std::error::Error>> {
+    let db = Database::new().map_err(|e| format!("Failed to initialize application: {}", e))?;
+    build_app(cli, db)
+}
+
+fn build_app(cli: &cli::Cli, db: Database) -> Result<App, Box<dyn std::error::Error>> {
+    let mut app = App::with_database(db);
+    app.set_symbols(&cli.symbol_list()?.symbols);
+    app.trade_stream = cli.trades;
+    app.fill_inference = FillInference {
+        max_distance_bps: cli.fill_window_bps,
+    };
+    app.write_gate = cli.write_gate();
+    if cli.store_backend == StoreBackend::Csv {
+        app.analysis_files = Some(CsvStore::open(&cli.data_dir)?);
+    }
+    if let Some(dir) = &cli.csv_dir {
+        app.csv_sink = Some(CsvSink::open(dir)?);
+    }
+    if let Some(path) = &cli.dump_signals {
+        app.signal_dump = Some(SignalDump::open(path)?);
+    }
+    app.aliases = cli.aliases();
+    app.display_levels = cli.display_levels.clamp(1, MAX_DISPLAY_LEVELS);
+    app.max_indicators = cli.max_indicators.max(1);
+    app.latest_cache_ttl = Duration::from_millis(cli.latest_cache_ms);
+    app.warmup = cli.warmup.unwrap_or_default();
+    app.stale_after = Duration::from_secs(cli.stale_after_secs.max(1));
+    if let Some(path) = &cli.fingerprints {
+        app.fingerprints = load_fingerprints(path)?;
+    }
+    app.human_threshold = cli.human_threshold;
+    app.round_notional = cli.round_notional();
+    app.burst_multiplier = cli.burst_multiplier;
+    app.human_alert.cooldown = Duration::from_secs(cli.alert_cooldown_secs);
+    app.rotation = cli
+        .rotate
+        .map(|interval| Rotation::new(interval, Instant::now()));
+    if let Some(dir) = &cli.capture_dir {
+        let mut capture = EventCapture::new(dir.clone());
+        capture.triggers = cli.capture_on.clone();
+        capture.imbalance_swing = cli.capture_imbalance_swing;
+        app.capture = Some(capture);
+    }
+    if let Some(threshold) = cli.alert_human_orders {
+        app.human_alert.threshold = threshold;
+        app.human_alert.enabled = true;
+    }
+    if cli.bot_cutoff > cli.human_cutoff {
+        return Err("--bot-cutoff must not exceed --human-cutoff".into());
+    }
+    app.cutoffs = ConfidenceCutoffs {
+        human: cli.human_cutoff,
+        bot: cli.bot_cutoff,
     };
+    app.calibration = cli.calibration();
+    if !cli.analyze.is_empty() {
+        app.analysis_symbols = Some(cli.analyze.iter().map(|s| s.to_uppercase()).collect());
+    }
+    app.no_color = cli.plain_output();
+    app.markers = cli.markers();
+    app.duplicate_policy = cli.duplicate_levels;
+    app.validate_book = cli.validate_book;
+    app.snapshot_policy = cli.snapshot_policy();
+    if !cli.tape_columns.is_empty() {
+        app.tape_columns = cli.tape_columns.clone();
+    }
+    app.notional_filter = cli.notional_filter();
+    app.price_band = cli.price_band();
+    app.movers_windows = cli.movers_windows;
+    app.imbalance_windows = cli.imbalance_windows.max(1);
+    app.near_levels = cli.near_levels.max(1);
+    app.analyze_every_frame = cli.analyze_every_frame;
+    app.analysis_refresh = Duration::from_millis(cli.analysis_refresh_ms);
+    app.pinned_symbols = cli.pin.iter().map(|s| s.to_uppercase()).collect();
+    app.hidden_symbols = cli.hide.iter().map(|s| s.to_uppercase()).collect();
+    match cli.ratio_palette.len() {
+        0 => {}
+        1 => return Err("--ratio-palette needs at least two colors".into()),
+        _ => app.ratio_palette = cli.ratio_palette.clone(),
+    }
+    Ok(app)
+}
+
+pub(crate) fn run_tui(
+    mut app: App,
+    mut rx: mpsc::Receiver<Value>,
+    resync: Option<mpsc::UnboundedSender<String>>,
+    mouse: bool,
+    history_file: Option<&Path>,
+    stall_threshold: Duration,
+    checkpoint_interval: Duration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Watchdog runs on the runtime's other workers, so it keeps ticking if this loop hangs
+    let heartbeat = Heartbeat::new();
+    tokio::spawn(watchdog::watch(heartbeat.clone(), stall_threshold));
+
+    // Terminal setup
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    if mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+    let mut regions = ui::Regions::default();
+    let mut last_history_save = Instant::now();
+    let mut last_checkpoint = Instant::now();
 
     loop {
+        heartbeat.beat();
+        if let Some(stall) = heartbeat.take_stall() {
+            app.last_stall = Some((stall, Instant::now()));
+        }
+
         // Check for user input
         if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') => break,
-                    KeyCode::Char('n') => app.next_symbol(),
+            match event::read()? {
+                // A note being typed takes every key until it's saved or cancelled
+                Event::Key(key) if app.annotation.is_some() => match key.code {
+                    KeyCode::Enter => {
+                        if let Err(e) = app.save_annotation() {
+                            error!(error = %e, "Failed to save annotation");
+                        }
+                    }
+                    KeyCode::Esc => app.annotation = None,
+                    KeyCode::Backspace => {
+                        if let Some(note) = &mut app.annotation {
+                            note.pop();
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        if let Some(note) = &mut app.annotation {
+                            note.push(c);
+                        }
+                    }
                     _ => {}
-                }
+                },
+                Event::Key(key) => match keys::action_for(key.code) {
+                    Some(Action::Quit) => break,
+                    Some(Action::NextSymbol) => {
+                        app.next_symbol();
+                        if let Some(rotation) = &mut app.rotation {
+                            rotation.restart(Instant::now());
+                        }
+                    }
+                    Some(Action::TogglePause) => match &mut app.playback {
+                        Some(playback) => playback.toggle_pause(Instant::now()),
+                        None => app.toggle_pause(),
+                    },
+                    Some(Action::StepReplay) => {
+                        let stepped = match &mut app.playback {
+                            Some(playback) => playback.step(Instant::now())?,
+                            None => None,
+                        };
+                        if let Some(message) = stepped {
+                            app.receive_update(message);
+                        }
+                    }
+                    Some(Action::FasterReplay) => {
+                        if let Some(playback) = &mut app.playback {
+                            playback.faster(Instant::now());
+                        }
+                    }
+                    Some(Action::SlowerReplay) => {
+                        if let Some(playback) = &mut app.playback {
+                            playback.slower(Instant::now());
+                        }
+                    }
+                    Some(Action::MoreLevels) => app.adjust_display_levels(1),
+                    Some(Action::FewerLevels) => app.adjust_display_levels(-1),
+                    Some(Action::ResetCalibration) => app.reset_calibration(),
+                    Some(Action::ToggleGrouping) => app.toggle_grouping(),
+                    Some(Action::FinerBuckets) => app.scale_group_bucket(0.5),
+                    Some(Action::CoarserBuckets) => app.scale_group_bucket(2.0),
+                    Some(Action::ToggleAnalysis) => {
+                        let symbol = app.current_symbol.clone();
+                        app.toggle_analysis(&symbol);
+                    }
+                    Some(Action::ToggleTuning) => app.toggle_tuning(),
+                    Some(Action::LowerThreshold) if app.tuning => {
+                        app.adjust_threshold(-THRESHOLD_STEP)
+                    }
+                    Some(Action::RaiseThreshold) if app.tuning => {
+                        app.adjust_threshold(THRESHOLD_STEP)
+                    }
+                    Some(Action::LowerThreshold | Action::RaiseThreshold) => {}
+                    Some(Action::Annotate) => app.start_annotation(),
+                    Some(Action::ToggleHelp) => app.show_help = !app.show_help,
+                    Some(Action::ToggleAlert) => app.human_alert.enabled = !app.human_alert.enabled,
+                    Some(Action::ToggleRotation) => {
+                        if let Some(rotation) = &mut app.rotation {
+                            rotation.toggle_pause(Instant::now());
+                        }
+                    }
+                    Some(Action::SlowerRotation) => {
+                        if let Some(rotation) = &mut app.rotation {
+                            rotation.scale_interval(2.0);
+                        }
+                    }
+                    Some(Action::FasterRotation) => {
+                        if let Some(rotation) = &mut app.rotation {
+                            rotation.scale_interval(0.5);
+                        }
+                    }
+                    None => {}
+                },
+                Event::Mouse(mouse) => handle_mouse(&mut app, &regions, mouse),
+                // Repaint everything so cells from the old size don't linger
+                Event::Resize(_, _) => terminal.clear()?,
+                _ => {}
             }
         }
 
+        app.tick_rotation(Instant::now());
+
         // Check for new order book updates
         while let Ok(result) = rx.try_recv() {
-            app.update_orders(&result);
+            app.receive_update(result);
+        }
+        let replayed = match &mut app.playback {
+            Some(playback) => playback.due(Instant::now())?,
+            None => Vec::new(),
+        };
+        for message in replayed {
+            app.receive_update(message);
+        }
+        for symbol in std::mem::take(&mut app.resync_requests) {
+            if let Some(resync) = &resync {
+                let _ = resync.send(symbol);
+            }
         }
 
         // Draw UI
-        terminal.draw(|f| ui::draw(f, &mut app))?;
+        terminal.draw(|f| regions = ui::draw(f, &mut app))?;
+        if app.check_human_alert() {
+            // BEL; most terminals beep or flash the window
+            terminal.backend_mut().write_all(b"\x07")?;
+            terminal.backend_mut().flush()?;
+        }
+        match app.capture_events() {
+            Ok(paths) => {
+                for path in paths {
+                    info!(path = %path.display(), "Captured event context");
+                }
+            }
+            Err(e) => warn!(error = %e, "Failed to write event capture"),
+        }
+
+        if last_history_save.elapsed() >= HISTORY_SAVE_INTERVAL {
+            save_history(&app, history_file);
+            last_history_save = Instant::now();
+        }
+
+        // Keeps the WAL from growing without bound over long sessions, after handing
+        // back pages freed since the last one
+        if last_checkpoint.elapsed() >= checkpoint_interval {
+            if let Err(e) = app.db.incremental_vacuum() {
+                warn!(error = %e, "Incremental vacuum failed");
+            }
+            if let Err(e) = app.db.checkpoint() {
+                warn!(error = %e, "Database checkpoint failed");
+            }
+            last_checkpoint = Instant::now();
+        }
+    }
+    save_history(&app, history_file);
+    if let Err(e) = app.db.optimize() {
+        warn!(error = %e, "Database optimize failed");
     }
 
     // Restore terminal
@@ -95,10 +906,40 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     )?;
     terminal.show_cursor()?;
 
+    // Printed after leaving the alternate screen so it stays in the scrollback
+    print!("{}", app.session_summary());
+
     Ok(())
 }
 
-async fn run_websocket(tx: mpsc::Sender<Value>) -> Result<(), Box<dyn std::error::Error>> {
+fn save_history(app: &App, path: Option<&Path>) {
+    if let Some(path) = path {
+        if let Err(e) = app.save_history(path) {
+            error!(error = %e, path = %path.display(), "Failed to save message history");
+        }
+    }
+}
+
+// Click a top mover to switch to it; scroll the tape with the wheel
+fn handle_mouse(app: &mut App, regions: &ui::Regions, mouse: MouseEvent) {
+    let (column, row) = (mouse.column, mouse.row);
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            if let Some(symbol) = regions.mover_at(column, row) {
+                app.select_symbol(symbol);
+            }
+        }
+        MouseEventKind::ScrollDown if regions.in_tape(column, row) => {
+            app.scroll_tape(1, regions.tape_rows());
+        }
+        MouseEventKind::ScrollUp if regions.in_tape(column, row) => {
+            app.scroll_tape(-1, regions.tape_rows());
+        }
+        _ => {}
+    }
+}
+
+async fn run_websocket(tx: mpsc::Sender<Value>, endpoints: Endpoints) -> Result<(), AppError> {
     let mut state = WebSocketState {
         last_update: Instant::now(),
         reconnect_attempts: 0,
@@ -106,48 +947,147 @@ async fn run_websocket(tx: mpsc::Sender<Value>) -> Result<(), Box<dyn std::error
     };
 
     loop {
-        match connect_and_stream(&tx, &mut state).await {
+        match connect_and_stream(&tx, &endpoints, &mut state).await {
             Ok(_) => {
                 // Successful completion (probably disconnect)
                 state.reconnect_attempts = 0;
-                println!("WebSocket disconnected, attempting to reconnect...");
+                info!("WebSocket disconnected, attempting to reconnect");
             }
+            // Reconnecting fetches snapshots again, which only extends the ban
+            Err(e @ AppError::Banned(_)) => return Err(e),
             Err(e) => {
-                eprintln!("WebSocket error: {}", e);
                 state.reconnect_attempts += 1;
+                warn!(
+                    error = %e,
+                    transient = e.is_transient(),
+                    reconnect_attempt = state.reconnect_attempts,
+                    "WebSocket connection failed"
+                );
+                if reconnect_limit_reached(
+                    state.reconnect_attempts,
+                    endpoints.max_reconnect_attempts,
+                ) {
+                    return Err(AppError::ReconnectLimit(state.reconnect_attempts));
+                }
             }
         }
 
         // Exponential backoff for reconnection
-        let delay = RECONNECT_DELAY.mul_f64(1.5f64.powi(state.reconnect_attempts as i32));
+        let delay = reconnect_delay(
+            RECONNECT_DELAY,
+            state.reconnect_attempts,
+            endpoints.max_reconnect_delay,
+        );
+        info!(
+            delay_ms = delay.as_millis() as u64,
+            "Waiting before reconnect"
+        );
         sleep(delay).await;
     }
 }
 
+// Forwards !forceOrder events until the app goes away, reconnecting after any failure
+async fn run_liquidations(tx: mpsc::Sender<Value>, base: Url) {
+    loop {
+        match stream_liquidations(&tx, &base).await {
+            Ok(()) => info!("Liquidation stream disconnected, attempting to reconnect"),
+            Err(AppError::ChannelClosed) => return,
+            Err(e) => warn!(error = %e, "Liquidation stream failed"),
+        }
+        sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn stream_liquidations(tx: &mpsc::Sender<Value>, base: &Url) -> Result<(), AppError> {
+    let url = Url::parse(&format!(
+        "{}/ws/!forceOrder@arr",
+        base.as_str().trim_end_matches('/')
+    ))?;
+    info!(%url, "Connecting to liquidation stream");
+    let (ws_stream, _) = connect_async(&url).await?;
+    let (_write, mut read) = ws_stream.split();
+    while let Some(msg) = read.next().await {
+        match msg? {
+            Message::Text(text) => {
+                let event: Value = serde_json::from_str(&text)?;
+                if event["e"] == "forceOrder" {
+                    tx.send(event).await?;
+                }
+            }
+            Message::Close(frame) => {
+                info!(?frame, "Liquidation stream closed by server");
+                break;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[instrument(skip_all, fields(reconnect_attempt = state.reconnect_attempts))]
 async fn connect_and_stream(
     tx: &mpsc::Sender<Value>,
+    endpoints: &Endpoints,
     state: &mut WebSocketState,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), AppError> {
     // Create combined stream for multiple symbols - using regular WebSocket stream
-    let streams: Vec<String> = SYMBOLS
+    let mut streams: Vec<String> = endpoints
+        .symbols
         .iter()
-        .map(|&symbol| format!("{}@depth@{}", symbol, UPDATE_SPEED))
+        .map(|symbol| endpoints.update_speed.depth_stream(symbol))
         .collect();
+    if endpoints.trades {
+        streams.extend(
+            endpoints
+                .symbols
+                .iter()
+                .map(|symbol| format!("{}@aggTrade", symbol)),
+        );
+    }
 
     // Use the regular WebSocket stream URL
     let url = Url::parse(&format!(
-        "wss://stream.binance.com:9443/stream?streams={}",
+        "{}/stream?streams={}",
+        endpoints.ws.as_str().trim_end_matches('/'),
         streams.join("/")
     ))?;
 
     // Connect to WebSocket
+    info!(%url, "Connecting to WebSocket");
     let (ws_stream, _) = connect_async(&url).await?;
     let (_write, mut read) = ws_stream.split();
 
-    // Get initial snapshots for all symbols
-    for &symbol in SYMBOLS {
-        let snapshot = fetch_initial_snapshot(symbol).await?;
-        tx.send(snapshot).await?;
+    // Get initial snapshots for all symbols, a bounded number at a time. One symbol failing
+    // doesn't hold up the rest; only when every fetch fails is the connection retried.
+    // Owned per-task clones; borrowing endpoints here makes the spawned feed task non-Send
+    let mut snapshots = futures_util::stream::iter(endpoints.symbols.clone())
+        .map(|symbol| {
+            let (client, rest) = (endpoints.client.clone(), endpoints.rest.clone());
+            async move {
+                let result = fetch_initial_snapshot(&client, &rest, &symbol, DEPTH_LEVELS).await;
+                (symbol, result)
+            }
+        })
+        .buffer_unordered(endpoints.snapshot_concurrency.max(1));
+    let mut first_error = None;
+    let mut received = 0;
+    while let Some((symbol, result)) = snapshots.next().await {
+        match result {
+            Ok(snapshot) => {
+                debug!(%symbol, "Received initial snapshot");
+                tx.send(snapshot).await?;
+                received += 1;
+            }
+            // Further requests while banned only extend the ban
+            Err(e @ AppError::Banned(_)) => return Err(e),
+            Err(e) => {
+                warn!(%symbol, error = %e, "Failed to fetch initial snapshot");
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    if let (0, Some(e)) = (received, first_error) {
+        return Err(e);
     }
     state.snapshot_received = true;
 
@@ -157,43 +1097,114 @@ async fn connect_and_stream(
 
         match msg? {
             Message::Text(text) => {
+                let received_at = get_current_timestamp_millis();
                 let response: Value = serde_json::from_str(&text)?;
 
-                if let Some(data) = response.get("data") {
-                    let transformed = json!({
-                        "symbol": data["s"].as_str().unwrap_or("UNKNOWN").to_uppercase(),
-                        "bids": data["b"],
-                        "asks": data["a"],
-                        "lastUpdateId": data["u"]
-                    });
-                    tx.send(transformed).await?;
+                // Trades are forwarded as-is and parsed by the app
+                if let Some(data) = response.get("data").filter(|d| d["e"] == "aggTrade") {
+                    tx.send(data.clone()).await?;
+                    continue;
                 }
+
+                match transform_depth_message(&response) {
+                    Some(mut transformed) => {
+                        transformed["receivedAt"] = json!(received_at);
+                        tx.send(transformed).await?;
+                    }
+                    None => debug!(%text, "Ignoring unrecognized stream message"),
+                }
+            }
+            Message::Close(frame) => {
+                info!(?frame, "WebSocket closed by server");
+                break;
             }
-            Message::Close(_) => break,
             _ => {}
         }
 
         // Check for stale connection (no updates for 10 seconds)
-        if state.last_update.elapsed() > Duration::from_secs(10) {
-            return Err("Connection stale".into());
+        if state.last_update.elapsed() > STALE_TIMEOUT {
+            warn!("No updates received, treating connection as stale");
+            return Err(AppError::Stale(STALE_TIMEOUT));
         }
     }
 
     Ok(())
 }
 
-async fn fetch_initial_snapshot(symbol: &str) -> Result<Value, Box<dyn std::error::Error>> {
+// Fetches a snapshot per requested symbol until the dashboard goes away
+async fn run_resyncs(
+    mut requests: mpsc::UnboundedReceiver<String>,
+    tx: mpsc::Sender<Value>,
+    client: reqwest::Client,
+    rest: Url,
+) {
+    while let Some(symbol) = requests.recv().await {
+        match fetch_initial_snapshot(&client, &rest, &symbol, DEPTH_LEVELS).await {
+            Ok(snapshot) => {
+                if tx.send(snapshot).await.is_err() {
+                    break;
+                }
+            }
+            Err(e) => warn!(%symbol, error = %e, "Failed to fetch resync snapshot"),
+        }
+    }
+}
+
+async fn fetch_initial_snapshot(
+    client: &reqwest::Client,
+    rest_url: &Url,
+    symbol: &str,
+    depth: u32,
+) -> Result<Value, AppError> {
     let url = format!(
-        "https://api.binance.com/api/v3/depth?symbol={}&limit={}",
+        "{}/api/v3/depth?symbol={}&limit={}",
+        rest_url.as_str().trim_end_matches('/'),
         symbol.to_uppercase(),
-        DEPTH_LEVELS
+        depth
     );
 
-    let response = reqwest::get(&url).await?.json::<Value>().await?;
-    Ok(json!({
-        "symbol": symbol.to_uppercase(),
-        "bids": response["bids"],
-        "asks": response["asks"],
-        "lastUpdateId": response["lastUpdateId"]
-    }))
+    let response = client.get(&url).send().await?;
+    let status = response.status().as_u16();
+    parse_depth_snapshot(symbol, status, &response.json::<Value>().await?)
+}
+
+// Filters keyed by symbol, plus the configured symbols Binance doesn't know
+async fn fetch_symbol_filters(
+    endpoints: &Endpoints,
+) -> Result<(HashMap<String, SymbolFilters>, Vec<String>), AppError> {
+    let symbols: Vec<String> = endpoints.symbols.iter().map(|s| s.to_uppercase()).collect();
+    let response = fetch_exchange_info(endpoints, &symbols).await?;
+    if !is_invalid_symbol_error(&response) {
+        return Ok((parse_exchange_info(&response), Vec::new()));
+    }
+
+    // One unknown symbol fails the whole batch, so ask one at a time to find it
+    let mut filters = HashMap::new();
+    let mut rejected = Vec::new();
+    for symbol in symbols {
+        let response = fetch_exchange_info(endpoints, std::slice::from_ref(&symbol)).await?;
+        if is_invalid_symbol_error(&response) {
+            rejected.push(symbol);
+        } else {
+            filters.extend(parse_exchange_info(&response));
+        }
+    }
+    Ok((filters, rejected))
+}
+
+async fn fetch_exchange_info(endpoints: &Endpoints, symbols: &[String]) -> Result<Value, AppError> {
+    let url = Url::parse_with_params(
+        &format!(
+            "{}/api/v3/exchangeInfo",
+            endpoints.rest.as_str().trim_end_matches('/')
+        ),
+        &[("symbols", serde_json::to_string(symbols)?)],
+    )?;
+    Ok(endpoints
+        .client
+        .get(url)
+        .send()
+        .await?
+        .json::<Value>()
+        .await?)
 }
@@ -0,0 +1,297 @@
+use crate::{AppError, DuplicatePolicy, OrderBook, OrderBookEntry};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+pub const DEFAULT_SNAPSHOT_INTERVAL_MS: u64 = 60_000;
+pub const REPLAY_SPEEDS: [f64; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 10.0];
+const NORMAL_SPEED: usize = 2; // index of 1x in REPLAY_SPEEDS
+
+// Index of snapshot markers lives next to the recording, e.g. session.jsonl.idx
+pub fn index_path(path: &Path) -> PathBuf {
+    let mut index = path.as_os_str().to_owned();
+    index.push(".idx");
+    PathBuf::from(index)
+}
+
+fn message_time(message: &Value, fallback: u64) -> u64 {
+    message
+        .get("receivedAt")
+        .and_then(|t| t.as_u64())
+        .unwrap_or(fallback)
+}
+
+fn levels(orders: &[OrderBookEntry]) -> Value {
+    orders
+        .iter()
+        .map(|o| json!([o.price, o.quantity]))
+        .collect()
+}
+
+// Writes feed messages as JSONL. Every `snapshot_interval_ms` a marker holding the full
+// book (or latest other message) per symbol is written and its byte offset recorded in the
+// index, so a replay can start from the nearest marker instead of the beginning of the file.
+pub struct Recorder {
+    log: BufWriter<File>,
+    index: BufWriter<File>,
+    offset: u64,
+    latest: HashMap<String, Value>,
+    books: HashMap<String, OrderBook>, // diffs applied as they are recorded, for the markers
+    snapshot_interval_ms: u64,
+    last_snapshot: Option<u64>,
+}
+
+impl Recorder {
+    pub fn create<P: AsRef<Path>>(path: P, snapshot_interval_ms: u64) -> Result<Self, AppError> {
+        let path = path.as_ref();
+        Ok(Recorder {
+            log: BufWriter::new(File::create(path)?),
+            index: BufWriter::new(File::create(index_path(path))?),
+            offset: 0,
+            latest: HashMap::new(),
+            books: HashMap::new(),
+            snapshot_interval_ms,
+            last_snapshot: None,
+        })
+    }
+
+    pub fn record(&mut self, message: &Value) -> Result<(), AppError> {
+        let ts = message_time(message, crate::db::get_current_timestamp_millis());
+        if let Some(symbol) = message.get("symbol").and_then(|s| s.as_str()) {
+            if message.get("bids").is_some() || message.get("asks").is_some() {
+                self.books.entry(symbol.to_string()).or_default().apply(
+                    message,
+                    0.0,
+                    DuplicatePolicy::default(),
+                    Instant::now(),
+                );
+            }
+            self.latest.insert(symbol.to_string(), message.clone());
+        }
+
+        let due = self
+            .last_snapshot
+            .is_none_or(|last| ts.saturating_sub(last) >= self.snapshot_interval_ms);
+        if due {
+            self.write_snapshot(ts)?;
+        } else {
+            self.write_line(&json!({"ts": ts, "kind": "update", "message": message}))?;
+        }
+        Ok(())
+    }
+
+    // The marker already contains `message`, so no separate update line is needed
+    fn write_snapshot(&mut self, ts: u64) -> Result<(), AppError> {
+        let mut symbols: Vec<&String> = self.latest.keys().collect();
+        symbols.sort();
+        // Books are written without an eventTime so a replay applies them as snapshots
+        let messages: Vec<Value> = symbols
+            .into_iter()
+            .map(|symbol| match self.books.get(symbol) {
+                Some(book) => json!({
+                    "symbol": symbol,
+                    "bids": levels(&book.bids),
+                    "asks": levels(&book.asks),
+                    "lastUpdateId": self.latest[symbol]["lastUpdateId"],
+                    "receivedAt": ts,
+                }),
+                None => self.latest[symbol].clone(),
+            })
+            .collect();
+        let line = json!({"ts": ts, "kind": "snapshot", "messages": messages});
+
+        writeln!(self.index, "{} {}", ts, self.offset)?;
+        self.write_line(&line)?;
+        self.log.flush()?;
+        self.index.flush()?;
+        self.last_snapshot = Some(ts);
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &Value) -> Result<(), AppError> {
+        let mut encoded = serde_json::to_string(line)?;
+        encoded.push('\n');
+        self.log.write_all(encoded.as_bytes())?;
+        self.offset += encoded.len() as u64;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), AppError> {
+        self.log.flush()?;
+        self.index.flush()?;
+        Ok(())
+    }
+}
+
+pub struct Replayer {
+    reader: BufReader<File>,
+    index: Vec<(u64, u64)>, // (timestamp, byte offset) of each snapshot marker
+    pending: Vec<(u64, Value)>, // read past a seek target, returned by next()
+}
+
+impl Replayer {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, AppError> {
+        let path = path.as_ref();
+        let index = std::fs::read_to_string(index_path(path))?
+            .lines()
+            .filter_map(|line| {
+                let (ts, offset) = line.split_once(' ')?;
+                Some((ts.parse().ok()?, offset.parse().ok()?))
+            })
+            .collect();
+        Ok(Replayer {
+            reader: BufReader::new(File::open(path)?),
+            index,
+            pending: Vec::new(),
+        })
+    }
+
+    // Next (timestamp, message) in recording order; snapshot markers expand to their messages
+    pub fn next_message(&mut self) -> Result<Option<(u64, Value)>, AppError> {
+        loop {
+            if !self.pending.is_empty() {
+                return Ok(Some(self.pending.remove(0)));
+            }
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            self.pending = Self::expand(serde_json::from_str(&line)?);
+        }
+    }
+
+    fn expand(entry: Value) -> Vec<(u64, Value)> {
+        let ts = entry["ts"].as_u64().unwrap_or(0);
+        match entry["kind"].as_str() {
+            Some("snapshot") => entry["messages"]
+                .as_array()
+                .map(|messages| messages.iter().map(|m| (ts, m.clone())).collect())
+                .unwrap_or_default(),
+            Some("update") => vec![(ts, entry["message"].clone())],
+            _ => Vec::new(),
+        }
+    }
+
+    // Repositions at the last snapshot marker at or before `ts` and returns the messages
+    // needed to rebuild book state at `ts`, in order. Replay continues after `ts`.
+    pub fn seek(&mut self, ts: u64) -> Result<Vec<Value>, AppError> {
+        let position = self.index.partition_point(|&(marker, _)| marker <= ts);
+        let offset = match position {
+            0 => 0,
+            n => self.index[n - 1].1,
+        };
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.pending.clear();
+
+        let mut messages = Vec::new();
+        while let Some((message_ts, message)) = self.next_message()? {
+            if message_ts > ts {
+                self.pending.insert(0, (message_ts, message));
+                break;
+            }
+            messages.push(message);
+        }
+        Ok(messages)
+    }
+}
+
+// Feeds a recording to the dashboard in recorded time, scaled by the playback speed.
+// Paused, it only moves when stepped, one message at a time.
+pub struct Playback {
+    replayer: Replayer,
+    speed: usize, // index into REPLAY_SPEEDS
+    pub paused: bool,
+    position: f64,   // recording time (ms) played up to
+    anchor: Instant, // when `position` was last brought up to date
+    next: Option<(u64, Value)>,
+    finished: bool,
+}
+
+impl Playback {
+    // Starts at `position`, typically the timestamp the replayer was seeked to
+    pub fn new(replayer: Replayer, position: u64, now: Instant) -> Self {
+        Playback {
+            replayer,
+            speed: NORMAL_SPEED,
+            paused: false,
+            position: position as f64,
+            anchor: now,
+            next: None,
+            finished: false,
+        }
+    }
+
+    pub fn speed(&self) -> f64 {
+        REPLAY_SPEEDS[self.speed]
+    }
+
+    pub fn position(&self) -> u64 {
+        self.position as u64
+    }
+
+    // The whole recording has been played
+    pub fn is_finished(&self) -> bool {
+        self.finished && self.next.is_none()
+    }
+
+    fn peek(&mut self) -> Result<Option<u64>, AppError> {
+        if self.next.is_none() && !self.finished {
+            self.next = self.replayer.next_message()?;
+            self.finished = self.next.is_none();
+        }
+        Ok(self.next.as_ref().map(|(ts, _)| *ts))
+    }
+
+    fn catch_up(&mut self, now: Instant) {
+        if !self.paused {
+            let elapsed = now.saturating_duration_since(self.anchor).as_secs_f64() * 1000.0;
+            self.position += elapsed * self.speed();
+        }
+        self.anchor = now;
+    }
+
+    // Messages recorded up to the current playback position, in order
+    pub fn due(&mut self, now: Instant) -> Result<Vec<Value>, AppError> {
+        self.catch_up(now);
+        let mut messages = Vec::new();
+        if self.paused {
+            return Ok(messages);
+        }
+        while self.peek()?.is_some_and(|ts| ts as f64 <= self.position) {
+            messages.extend(self.next.take().map(|(_, message)| message));
+        }
+        Ok(messages)
+    }
+
+    // Pauses and plays exactly one message, moving the position to its timestamp
+    pub fn step(&mut self, now: Instant) -> Result<Option<Value>, AppError> {
+        self.paused = true;
+        self.anchor = now;
+        self.peek()?;
+        Ok(self.next.take().map(|(ts, message)| {
+            self.position = self.position.max(ts as f64);
+            message
+        }))
+    }
+
+    pub fn toggle_pause(&mut self, now: Instant) {
+        self.catch_up(now);
+        self.paused = !self.paused;
+    }
+
+    pub fn faster(&mut self, now: Instant) {
+        self.catch_up(now);
+        self.speed = (self.speed + 1).min(REPLAY_SPEEDS.len() - 1);
+    }
+
+    pub fn slower(&mut self, now: Instant) {
+        self.catch_up(now);
+        self.speed = self.speed.saturating_sub(1);
+    }
+}
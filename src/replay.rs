@@ -0,0 +1,54 @@
+use crate::cli::Cli;
+use crate::line::summary_line;
+use binance_ws::{
+    db::Database,
+    recording::{Playback, Replayer},
+    App, AppError,
+};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+// Rebuilds every book as of `at` (unix ms) and prints one summary line per symbol
+pub fn run(mut app: App, path: &Path, at: u64) -> Result<(), AppError> {
+    let mut replayer = Replayer::open(path)?;
+    for message in replayer.seek(at)? {
+        app.update_orders(&message);
+    }
+
+    let mut symbols: Vec<String> = app.order_books.keys().cloned().collect();
+    symbols.sort();
+    for symbol in symbols {
+        app.current_symbol = symbol;
+        println!("{}", summary_line(&mut app));
+    }
+    Ok(())
+}
+
+// Rebuilds the books as of `at`, then plays the recording on in the dashboard. Nothing
+// is fed live, and the replayed session leaves --history-file alone; notes taken with
+// 'm' still go to the on-disk database.
+pub fn play(
+    mut app: App,
+    path: &Path,
+    at: u64,
+    cli: &Cli,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut replayer = Replayer::open(path)?;
+    for message in replayer.seek(at)? {
+        app.update_orders(&message);
+    }
+    app.playback = Some(Playback::new(replayer, at, Instant::now()));
+    app.annotation_db = Some(Database::new()?);
+
+    let (_tx, rx) = mpsc::channel(1);
+    crate::run_tui(
+        app,
+        rx,
+        None,
+        !cli.no_mouse,
+        None,
+        Duration::from_secs(cli.stall_threshold_secs.max(1)),
+        Duration::from_secs(cli.checkpoint_minutes.max(1) * 60),
+    )
+}
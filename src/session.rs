@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+// Running per-symbol figures for the exit summary; cheap enough to update on every message
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SymbolSession {
+    pub updates: u64,
+    pub ratio_sum: f64,
+    pub ratio_samples: u64,
+    pub max_spread: Option<f64>,
+    pub peak_imbalance: Option<f64>, // signed, largest in magnitude
+}
+
+impl SymbolSession {
+    pub fn average_ratio(&self) -> Option<f64> {
+        (self.ratio_samples > 0).then(|| self.ratio_sum / self.ratio_samples as f64)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SessionStats {
+    symbols: BTreeMap<String, SymbolSession>,
+}
+
+impl SessionStats {
+    pub fn record_update(&mut self, symbol: &str, spread: Option<f64>, imbalance: Option<f64>) {
+        let entry = self.entry(symbol);
+        entry.updates += 1;
+        if let Some(spread) = spread {
+            entry.max_spread = Some(entry.max_spread.map_or(spread, |max| max.max(spread)));
+        }
+        if let Some(imbalance) = imbalance {
+            if entry
+                .peak_imbalance
+                .is_none_or(|peak| imbalance.abs() > peak.abs())
+            {
+                entry.peak_imbalance = Some(imbalance);
+            }
+        }
+    }
+
+    pub fn record_ratio(&mut self, symbol: &str, ratio: f64) {
+        let entry = self.entry(symbol);
+        entry.ratio_sum += ratio;
+        entry.ratio_samples += 1;
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&SymbolSession> {
+        self.symbols.get(symbol)
+    }
+
+    fn entry(&mut self, symbol: &str) -> &mut SymbolSession {
+        self.symbols.entry(symbol.to_string()).or_default()
+    }
+
+    // Plain-text table printed once the terminal is restored
+    pub fn report(&self, runtime: Duration) -> String {
+        let optional = |value: Option<f64>, precision: usize| {
+            value.map_or_else(|| "-".to_string(), |v| format!("{:.*}", precision, v))
+        };
+        let secs = runtime.as_secs();
+        let mut out = format!(
+            "Session summary ({:02}:{:02}:{:02})\n{:<12} {:>10} {:>10} {:>12} {:>10}\n",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60,
+            "symbol",
+            "updates",
+            "avg human",
+            "max spread",
+            "peak imb"
+        );
+        for (symbol, stats) in &self.symbols {
+            let _ = writeln!(
+                out,
+                "{:<12} {:>10} {:>10} {:>12} {:>10}",
+                symbol,
+                stats.updates,
+                stats
+                    .average_ratio()
+                    .map_or_else(|| "-".to_string(), |r| format!("{:.1}%", r * 100.0)),
+                optional(stats.max_spread, 8),
+                optional(stats.peak_imbalance, 3)
+            );
+        }
+        out
+    }
+}
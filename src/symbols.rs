@@ -0,0 +1,90 @@
+use crate::AppError;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Default)]
+pub struct SymbolList {
+    pub symbols: Vec<String>,
+    pub invalid: Vec<String>,
+}
+
+// Binance stream names are lowercase alphanumerics, e.g. btcusdt
+pub fn is_valid_symbol(symbol: &str) -> bool {
+    !symbol.is_empty()
+        && symbol
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+}
+
+// One symbol per line; blank lines and anything after '#' are ignored
+pub fn parse_symbols_file(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn load_symbols_file<P: AsRef<Path>>(path: P) -> Result<Vec<String>, AppError> {
+    Ok(parse_symbols_file(&fs::read_to_string(path)?))
+}
+
+// Lowercases and validates symbols, dropping duplicates but keeping first-seen order
+pub fn normalize<I, S>(raw: I) -> SymbolList
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut list = SymbolList::default();
+    for symbol in raw {
+        let symbol = symbol.as_ref().trim().to_lowercase();
+        if !is_valid_symbol(&symbol) {
+            list.invalid.push(symbol);
+        } else if !list.symbols.contains(&symbol) {
+            list.symbols.push(symbol);
+        }
+    }
+    list
+}
+
+// Display names for symbols; streams and the DB keep the canonical uppercase symbol
+#[derive(Debug, Clone, Default)]
+pub struct SymbolAliases {
+    pub quote_suffix: Option<String>, // stripped when no explicit alias exists, e.g. USDT
+    pub overrides: HashMap<String, String>, // keyed by uppercase symbol
+}
+
+impl SymbolAliases {
+    pub fn display(&self, symbol: &str) -> String {
+        let symbol = symbol.to_uppercase();
+        if let Some(alias) = self.overrides.get(&symbol) {
+            return alias.clone();
+        }
+        match &self.quote_suffix {
+            Some(suffix) => match symbol.strip_suffix(suffix.to_uppercase().as_str()) {
+                Some(base) if !base.is_empty() => base.to_string(),
+                _ => symbol,
+            },
+            None => symbol,
+        }
+    }
+
+    // Aliases shared by more than one symbol, with the symbols that map to each
+    pub fn collisions<S: AsRef<str>>(&self, symbols: &[S]) -> Vec<(String, Vec<String>)> {
+        let mut by_alias: HashMap<String, Vec<String>> = HashMap::new();
+        for symbol in symbols {
+            by_alias
+                .entry(self.display(symbol.as_ref()))
+                .or_default()
+                .push(symbol.as_ref().to_uppercase());
+        }
+        let mut collisions: Vec<_> = by_alias
+            .into_iter()
+            .filter(|(_, symbols)| symbols.len() > 1)
+            .collect();
+        collisions.sort();
+        collisions
+    }
+}
@@ -0,0 +1,76 @@
+use metrics::{gauge, histogram};
+use metrics_exporter_prometheus::{BuildError, Matcher, PrometheusBuilder, PrometheusHandle};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tracing::{debug, warn};
+use url::Url;
+
+pub const HUMAN_RATIO_METRIC: &str = "binance_human_ratio";
+pub const FEED_LATENCY_METRIC: &str = "binance_feed_latency_ms";
+pub const HUMAN_RATIO_GAUGE: &str = "binance_human_ratio_current";
+pub const SPREAD_GAUGE: &str = "binance_spread";
+pub const IMBALANCE_GAUGE: &str = "binance_imbalance";
+pub const PUSH_JOB: &str = "binance-ws";
+
+const HUMAN_RATIO_BUCKETS: &[f64] = &[0.0, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0];
+
+// Installs the global recorder; human ratio is bucketed, latency is a summary. With
+// `listen` OpenMetrics is also served there. Must run inside the Tokio runtime.
+pub fn install(listen: Option<SocketAddr>) -> Result<PrometheusHandle, BuildError> {
+    let builder = PrometheusBuilder::new().set_buckets_for_metric(
+        Matcher::Full(HUMAN_RATIO_METRIC.to_string()),
+        HUMAN_RATIO_BUCKETS,
+    )?;
+    match listen {
+        Some(addr) => {
+            let (recorder, exporter) = builder.with_http_listener(addr).build()?;
+            let handle = recorder.handle();
+            metrics::set_global_recorder(recorder)?;
+            tokio::spawn(exporter);
+            Ok(handle)
+        }
+        None => builder.install_recorder(),
+    }
+}
+
+// Pushes the same metrics the scrape endpoint serves to a Pushgateway every `interval`.
+// Failures are logged and retried on the next tick.
+pub async fn push_loop(handle: PrometheusHandle, gateway: Url, interval: Duration) {
+    let url = format!(
+        "{}/metrics/job/{}",
+        gateway.as_str().trim_end_matches('/'),
+        PUSH_JOB
+    );
+    let client = reqwest::Client::new();
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        handle.run_upkeep();
+        match client.put(&url).body(handle.render()).send().await {
+            Ok(response) if response.status().is_success() => debug!(%url, "Pushed metrics"),
+            Ok(response) => {
+                warn!(%url, status = %response.status(), "Pushgateway rejected metrics")
+            }
+            Err(e) => warn!(%url, error = %e, "Failed to push metrics"),
+        }
+    }
+}
+
+// All are no-ops until a recorder is installed
+pub fn record_human_ratio(symbol: &str, ratio: f64) {
+    histogram!(HUMAN_RATIO_METRIC, "symbol" => symbol.to_string()).record(ratio);
+    gauge!(HUMAN_RATIO_GAUGE, "symbol" => symbol.to_string()).set(ratio);
+}
+
+pub fn record_book_shape(symbol: &str, spread: Option<f64>, imbalance: Option<f64>) {
+    if let Some(spread) = spread {
+        gauge!(SPREAD_GAUGE, "symbol" => symbol.to_string()).set(spread);
+    }
+    if let Some(imbalance) = imbalance {
+        gauge!(IMBALANCE_GAUGE, "symbol" => symbol.to_string()).set(imbalance);
+    }
+}
+
+pub fn record_feed_latency(symbol: &str, latency_ms: i64) {
+    histogram!(FEED_LATENCY_METRIC, "symbol" => symbol.to_string()).record(latency_ms as f64);
+}
@@ -1,34 +1,170 @@
-use binance_ws::{App, OrderBookMessage, OrderSide};
+use crate::keys::BINDINGS;
+use binance_ws::{
+    db::get_current_timestamp, marker_width, App, ConfidenceBucket, MarkerSet, MarketAnalysis,
+    OrderBook, OrderSide, TapeField, TradeSide, MM_FOOTPRINT_THRESHOLD, RATIO_TREND_FLAT,
+    RATIO_TREND_WINDOW, UI_STALL_SHOWN_FOR,
+};
 use ratatui::{
+    buffer::Buffer,
+    layout::Position,
     prelude::*,
     symbols,
-    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph},
+    widgets::{Axis, Block, Borders, Chart, Clear, Dataset, GraphType, Paragraph},
 };
-use std::time::Duration;
+use std::time::Instant;
+
+// Where clickable content landed in the last frame, for mapping mouse events
+#[derive(Debug, Default)]
+pub struct Regions {
+    pub tape: Rect,
+    pub movers: Rect,
+    pub mover_symbols: Vec<String>, // one per row inside the movers border
+}
+
+impl Regions {
+    pub fn mover_at(&self, column: u16, row: u16) -> Option<&str> {
+        let inner = inner(self.movers);
+        if !inner.contains(Position { x: column, y: row }) {
+            return None;
+        }
+        self.mover_symbols
+            .get((row - inner.y) as usize)
+            .map(String::as_str)
+    }
+
+    pub fn in_tape(&self, column: u16, row: u16) -> bool {
+        self.tape.contains(Position { x: column, y: row })
+    }
+
+    // Trade rows inside the tape border, below its header
+    pub fn tape_rows(&self) -> usize {
+        self.tape.height.saturating_sub(3) as usize
+    }
+}
+
+fn inner(area: Rect) -> Rect {
+    Block::default().borders(Borders::ALL).inner(area)
+}
+
+// Below this nothing useful fits; between it and the compact sizes panels collapse
+const MIN_WIDTH: u16 = 60;
+const MIN_HEIGHT: u16 = 16;
+const COMPACT_HEIGHT: u16 = 30; // shorter stats block, no imbalance chart
+const NARROW_WIDTH: u16 = 100; // no top movers panel, book gets half the width
+
+pub fn draw(f: &mut Frame, app: &mut App) -> Regions {
+    let size = f.size();
+    if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+        let message = format!(
+            "Terminal too small ({}x{}, need {}x{})",
+            size.width, size.height, MIN_WIDTH, MIN_HEIGHT
+        );
+        f.render_widget(centered(&message, size), size);
+        return Regions::default();
+    }
+    let compact = size.height < COMPACT_HEIGHT;
+    let narrow = size.width < NARROW_WIDTH;
 
-pub fn draw(f: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(1),      // Title
-            Constraint::Percentage(70), // Graph
-            Constraint::Length(10),     // Stats
+            Constraint::Length(1),                            // Title
+            Constraint::Min(0),                               // Graph
+            Constraint::Length(if compact { 7 } else { 10 }), // Stats
+            Constraint::Length(1),                            // Footer
         ])
-        .split(f.size());
+        .split(size);
 
     // Title
+    let latency = match app.latency_stats(&app.current_symbol) {
+        Some(stats) if stats.clock_skew => format!("~{:.0}ms (clock skew)", stats.average_ms),
+        Some(stats) => format!("{:.0}ms", stats.average_ms),
+        None => "n/a".to_string(),
+    };
+    let book = app.order_books.get(&app.current_symbol);
+    let burst = match book {
+        Some(book) if book.is_update_burst(app.burst_multiplier, app.clock().now()) => {
+            format!(" [BURST {:.0}/s]", book.update_rate(app.clock().now()))
+        }
+        _ => String::new(),
+    };
+    let rotation = match &app.rotation {
+        Some(rotation) if rotation.paused => " [ROTATE paused]".to_string(),
+        Some(rotation) => format!(
+            " [ROTATE {}s]",
+            rotation.remaining(Instant::now()).as_secs_f64().ceil()
+        ),
+        None => String::new(),
+    };
+    let replay = match &app.playback {
+        Some(playback) if playback.is_finished() => " [REPLAY ended]".to_string(),
+        Some(playback) => format!(
+            " [REPLAY {} {}]",
+            if playback.paused {
+                "paused".to_string()
+            } else {
+                format!("{}x", playback.speed())
+            },
+            utc_time_of_day(playback.position())
+        ),
+        None => String::new(),
+    };
+    let stale = match book {
+        Some(book) if app.is_stale(&app.current_symbol) => {
+            format!(" [STALE {}s]", app.since_update(book).as_secs())
+        }
+        _ => String::new(),
+    };
+    let warmup = match app.warmup_remaining() {
+        Some(left) => format!(" [WARMING UP {}s]", left.as_secs_f64().ceil()),
+        None => String::new(),
+    };
+    let symbols: Vec<String> = app.order_books.keys().cloned().collect();
+    let market = match app.market_index(&symbols) {
+        Some(index) => format!("{:.1}%", index * 100.0),
+        None => "n/a".to_string(),
+    };
     let title = Paragraph::new(format!(
-        "Market Analysis - {} (Press 'q' to quit, 'n' for next symbol)",
-        app.current_symbol
+        "Market Analysis - {}{}{}{}{}{}{} | Market human: {} | Latency: {} (Press '?' for keys, 'q' to quit)",
+        app.display_name(&app.current_symbol),
+        if app.paused { " [PAUSED]" } else { "" },
+        stale,
+        replay,
+        warmup,
+        rotation,
+        burst,
+        market,
+        latency
     ))
-    .style(Style::default().fg(Color::White));
-    f.render_widget(title, chunks[0]);
+    .style(if app.human_alert.is_flashing(Instant::now()) {
+        Style::default().fg(Color::White).bg(Color::Magenta)
+    } else if app.paused {
+        Style::default().fg(Color::Black).bg(Color::Yellow)
+    } else {
+        Style::default().fg(Color::White)
+    });
+    match &app.feed_error {
+        Some(error) => f.render_widget(
+            Paragraph::new(format!("Feed stopped: {} (press 'q' to quit)", error))
+                .style(Style::default().fg(Color::White).bg(Color::Red)),
+            chunks[0],
+        ),
+        None => match app.last_stall {
+            Some((stall, noticed)) if noticed.elapsed() < UI_STALL_SHOWN_FOR => f.render_widget(
+                Paragraph::new(format!(
+                    "UI STALLED for {:.1}s - main loop recovered, see log",
+                    stall.as_secs_f64()
+                ))
+                .style(Style::default().fg(Color::Black).bg(Color::Yellow)),
+                chunks[0],
+            ),
+            _ => f.render_widget(title, chunks[0]),
+        },
+    }
 
     // Get historical data for the current symbol
-    let mut history = app
-        .db
-        .get_analysis_history(&app.current_symbol, 100)
-        .unwrap_or_default();
+    let current_symbol = app.current_symbol.clone();
+    let mut history = app.analysis_history(&current_symbol, size.width.clamp(50, 500) as i64);
 
     // Reverse history so oldest is first
     history.reverse();
@@ -101,25 +237,240 @@ pub fn draw(f: &mut Frame, app: &mut App) {
                 .labels(vec![Span::raw("0%"), Span::raw("50%"), Span::raw("100%")]),
         );
 
-    f.render_widget(chart, chunks[1]);
-
-    // Current stats
-    let analysis = app.analyze_market();
-    let stats_text = vec![
-        format!("Current Statistics for {}:", app.current_symbol),
-        format!("Total Orders: {}", analysis.total_orders),
-        format!("Human Orders: {}", analysis.likely_human_orders),
-        format!(
-            "Current Human Ratio: {:.1}%",
-            if analysis.total_orders > 0 {
-                (analysis.likely_human_orders as f64 / analysis.total_orders as f64) * 100.0
+    // Shown instead of empty borders and zeroed stats until the book has levels
+    let placeholder = app
+        .order_books
+        .get(&app.current_symbol)
+        .is_none_or(|book| book.is_empty())
+        .then(|| {
+            if app.message_history.is_empty() {
+                "Waiting for data…".to_string()
             } else {
-                0.0
+                format!("No data for {}", app.display_name(&app.current_symbol))
             }
+        });
+
+    let main = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(if narrow {
+            [Constraint::Percentage(50), Constraint::Percentage(50)]
+        } else {
+            [Constraint::Percentage(65), Constraint::Percentage(35)]
+        })
+        .split(chunks[1]);
+    let charts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(if compact { 0 } else { 8 }),
+        ])
+        .split(main[0]);
+
+    match &placeholder {
+        Some(message) if history.is_empty() => {
+            let block = Block::default()
+                .title("Trading Activity")
+                .borders(Borders::ALL);
+            let area = block.inner(charts[0]);
+            f.render_widget(block, charts[0]);
+            f.render_widget(centered(message, area), area);
+        }
+        _ => f.render_widget(chart, charts[0]),
+    }
+    if !compact {
+        draw_imbalance(f, app, charts[1]);
+    }
+
+    // VWAP of the displayed levels, and how far mid sits from it
+    let book = app.order_books.get(&app.current_symbol);
+    let vwap = match (
+        book.and_then(|b| b.vwap(app.display_levels)),
+        book.and_then(|b| b.mid_price()),
+    ) {
+        (Some(vwap), Some(mid)) => format!(" | VWAP {:.2} (mid {:+.2})", vwap, mid - vwap),
+        (Some(vwap), None) => format!(" | VWAP {:.2}", vwap),
+        _ => String::new(),
+    };
+    let book_title = match app.group_bucket {
+        Some(bucket) => format!(
+            "Order Book ({} buckets, [/] to resize, g for levels){}",
+            format_bucket(bucket),
+            vwap
         ),
-        format!("Data Points: {}", history.len()),
-    ]
-    .join("\n");
+        None => format!(
+            "Order Book (top {}, +/- to change, g to group){}",
+            app.display_levels, vwap
+        ),
+    };
+    let book_block = Block::default().title(book_title).borders(Borders::ALL);
+    let book_area = book_block.inner(main[1]);
+    f.render_widget(book_block, main[1]);
+    match &placeholder {
+        Some(message) => f.render_widget(centered(message, book_area), book_area),
+        None => {
+            let lines = match app.group_bucket {
+                Some(bucket) => grouped_book_lines(app, bucket),
+                None => book_lines(app),
+            };
+            f.render_widget(Paragraph::new(lines), book_area)
+        }
+    }
+
+    // Current stats
+    let mut analyses = app.displayed_analyses();
+
+    // Footer: aggregates across every tracked symbol
+    let (all_orders, all_human) = analyses
+        .iter()
+        .filter(|(symbol, _)| !app.is_stale(symbol))
+        .fold((0, 0), |(total, human), (_, a)| {
+            (total + a.total_orders, human + a.likely_human_orders)
+        });
+    let overall_ratio = if all_orders > 0 {
+        format!("{:.1}%", all_human as f64 / all_orders as f64 * 100.0)
+    } else {
+        "n/a".to_string()
+    };
+    let rejected = if app.rejected_symbols.is_empty() {
+        String::new()
+    } else {
+        format!(" | unknown to Binance: {}", app.rejected_symbols.join(", "))
+    };
+    let footer = Paragraph::new(format!(
+        "All symbols: {} orders | human {} | feeds {}/{} active | {:.1} msg/s{}",
+        all_orders,
+        overall_ratio,
+        app.active_feeds(),
+        app.order_books.len(),
+        app.messages_per_sec(),
+        rejected
+    ))
+    .style(Style::default().fg(Color::Black).bg(Color::Gray));
+    f.render_widget(footer, chunks[3]);
+
+    let analysis = analyses.remove(&app.current_symbol).unwrap_or_default();
+    let human_ratio = if analysis.total_orders > 0 {
+        analysis.likely_human_orders as f64 / analysis.total_orders as f64
+    } else {
+        0.0
+    };
+
+    // Relative position of the current ratio within its own recent distribution
+    let relative = match app.human_ratio_zscore(&app.current_symbol, human_ratio) {
+        Some(z) => format!(
+            "Ratio Percentile: {:.0} (z {:+.2})",
+            app.human_ratio_percentile(&app.current_symbol, human_ratio),
+            z
+        ),
+        None => "Ratio Percentile: n/a".to_string(),
+    };
+    let relative_line = if app.is_ratio_anomaly(&app.current_symbol, human_ratio) {
+        Line::styled(
+            format!("{} - ANOMALY", relative),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )
+    } else {
+        Line::raw(relative)
+    };
+
+    let slope = app.ratio_trend(&app.current_symbol, RATIO_TREND_WINDOW);
+    let trend = if slope > RATIO_TREND_FLAT {
+        format!("↑ {:+.1}/min", slope * 100.0)
+    } else if slope < -RATIO_TREND_FLAT {
+        format!("↓ {:+.1}/min", slope * 100.0)
+    } else {
+        "→".to_string()
+    };
+    let mm_footprint = if analysis.mm_footprint >= MM_FOOTPRINT_THRESHOLD {
+        Span::styled(
+            format!("MM Footprint: {:.2} (market maker)", analysis.mm_footprint),
+            Style::default().fg(Color::Red),
+        )
+    } else {
+        Span::raw(format!("MM Footprint: {:.2}", analysis.mm_footprint))
+    };
+    let mut stats_text = vec![
+        Line::raw(format!(
+            "Current Statistics for {}:",
+            app.display_name(&app.current_symbol)
+        )),
+        Line::from(vec![
+            Span::raw(format!("Total Orders: {} | ", analysis.total_orders)),
+            mm_footprint,
+        ]),
+        Line::raw(format!("Human Orders: {}", analysis.likely_human_orders)),
+        Line::raw(format!(
+            "Buckets: {} human / {} uncertain / {} bot",
+            analysis.buckets.likely_human, analysis.buckets.uncertain, analysis.buckets.likely_bot
+        )),
+        Line::styled(
+            format!("Current Human Ratio: {:.1}% {}", human_ratio * 100.0, trend),
+            Style::default()
+                .fg(ratio_color(human_ratio, &app.ratio_palette))
+                .add_modifier(Modifier::BOLD),
+        ),
+        relative_line,
+        Line::raw(format!(
+            "Data Points: {} | {}",
+            history.len(),
+            threshold_label(app)
+        )),
+    ];
+    if let Some(book) = app.order_books.get(&app.current_symbol) {
+        stats_text.push(pressure_line(book, app.near_levels));
+    }
+    if let Some(latest) = app.latest_analysis(&current_symbol) {
+        stats_text.push(Line::raw(format!(
+            "Last Stored: {:.1}% ({}s ago)",
+            latest.human_ratio * 100.0,
+            get_current_timestamp().saturating_sub(latest.timestamp)
+        )));
+    }
+    if app.pending_writes() > 0 || app.dropped_writes() > 0 {
+        stats_text.push(Line::styled(
+            format!(
+                "DB Backlog: {} pending, {} dropped",
+                app.pending_writes(),
+                app.dropped_writes()
+            ),
+            Style::default().fg(Color::Red),
+        ));
+    }
+
+    if !app.is_analysis_active(&app.current_symbol) {
+        let book = app.order_books.get(&app.current_symbol);
+        let spread = book
+            .and_then(|b| b.spread())
+            .map_or("n/a".to_string(), |s| format!("{:.4}", s));
+        let imbalance = book
+            .and_then(|b| b.imbalance())
+            .map_or("n/a".to_string(), |i| format!("{:+.2}", i));
+        stats_text = vec![
+            Line::raw(format!(
+                "Current Statistics for {}:",
+                app.display_name(&app.current_symbol)
+            )),
+            Line::raw("Analysis off for this symbol ('a' to enable)"),
+            Line::raw(format!("Spread: {}", spread)),
+            Line::raw(format!("Imbalance: {}", imbalance)),
+        ];
+        if let Some(book) = book {
+            stats_text.push(pressure_line(book, app.near_levels));
+        }
+    }
+
+    if let Some(message) = &placeholder {
+        stats_text = vec![
+            Line::raw(format!(
+                "Current Statistics for {}:",
+                app.display_name(&app.current_symbol)
+            )),
+            Line::raw(format!(
+                "{} - analysis starts with the first update",
+                message
+            )),
+        ];
+    }
 
     let stats = Paragraph::new(stats_text)
         .block(
@@ -129,5 +480,536 @@ pub fn draw(f: &mut Frame, app: &mut App) {
         )
         .style(Style::default().fg(Color::Yellow));
 
-    f.render_widget(stats, chunks[2]);
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(if narrow {
+            [
+                Constraint::Percentage(60),
+                Constraint::Percentage(40),
+                Constraint::Length(0),
+            ]
+        } else {
+            [
+                Constraint::Percentage(45),
+                Constraint::Percentage(30),
+                Constraint::Percentage(25),
+            ]
+        })
+        .split(chunks[2]);
+    f.render_widget(stats, bottom[0]);
+
+    // Time & sales, newest first: real trades when subscribed, otherwise inferred from quantity drops
+    let (trades, tape_title) = if app.trade_stream {
+        (&app.trades, "Trades")
+    } else {
+        (&app.tape, "Tape (inferred)")
+    };
+    let header = app
+        .tape_columns
+        .iter()
+        .map(|column| {
+            format!(
+                "{:>width$.width$}",
+                column.field.title(),
+                width = column.width
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut tape = vec![Line::styled(
+        header,
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+    tape.extend(
+        trades
+            .iter()
+            .rev()
+            .filter(|trade| trade.symbol == app.current_symbol)
+            .skip(app.tape_scroll)
+            .take(bottom[1].height.saturating_sub(3) as usize)
+            .map(|trade| {
+                let color = match trade.side {
+                    TradeSide::Buy => Color::Green,
+                    TradeSide::Sell => Color::Red,
+                };
+                let mut spans = Vec::with_capacity(app.tape_columns.len() * 2);
+                for (i, column) in app.tape_columns.iter().enumerate() {
+                    if i > 0 {
+                        spans.push(Span::raw(" "));
+                    }
+                    let style = match column.field {
+                        TapeField::Symbol => Style::default().fg(symbol_color(&trade.symbol)),
+                        _ => Style::default().fg(color),
+                    };
+                    spans.push(Span::styled(column.cell(trade), style));
+                }
+                Line::from(spans)
+            }),
+    );
+    let tape = Paragraph::new(tape).block(Block::default().title(tape_title).borders(Borders::ALL));
+    f.render_widget(tape, bottom[1]);
+
+    // Top movers by change in human ratio
+    let top_movers = app.top_movers(app.movers_windows);
+    let regions = Regions {
+        tape: bottom[1],
+        movers: bottom[2],
+        mover_symbols: top_movers
+            .iter()
+            .map(|(symbol, _)| symbol.clone())
+            .collect(),
+    };
+    let movers: Vec<Line> = top_movers
+        .into_iter()
+        .map(|(symbol, change)| {
+            let color = if change >= 0.0 {
+                Color::Green
+            } else {
+                Color::Red
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<10}", app.display_name(&symbol)),
+                    Style::default().fg(symbol_color(&symbol)),
+                ),
+                Span::styled(
+                    format!("{:+.1}pp", change * 100.0),
+                    Style::default().fg(color),
+                ),
+            ])
+        })
+        .collect();
+    let movers = Paragraph::new(movers).block(
+        Block::default()
+            .title(format!("Top Movers ({} windows)", app.movers_windows))
+            .borders(Borders::ALL),
+    );
+    f.render_widget(movers, bottom[2]);
+
+    if app.tuning {
+        draw_tuning(f, app, &analysis);
+    }
+    if let Some(note) = &app.annotation {
+        draw_annotation(f, app, note);
+    }
+    if app.show_help {
+        draw_help(f);
+    }
+
+    if app.no_color {
+        strip_styles(f.buffer_mut());
+    }
+
+    regions
+}
+
+const IMBALANCE_BAR_STEP: f64 = 0.02;
+
+// Near/far split of each side's quantity, filled for the near part
+fn pressure_line(book: &OrderBook, near_levels: usize) -> Line<'static> {
+    const WIDTH: usize = 10;
+    let bar = |share: f64| {
+        let filled = (share * WIDTH as f64).round() as usize;
+        format!(
+            "{}{} {:>3.0}%",
+            "█".repeat(filled),
+            "░".repeat(WIDTH - filled),
+            share * 100.0
+        )
+    };
+    let (bid, ask) = book.pressure(near_levels);
+    Line::raw(format!(
+        "Near {}: bid {} | ask {}",
+        near_levels,
+        bar(bid),
+        bar(ask)
+    ))
+}
+
+// Book imbalance per window as bars above (bid-heavy, cyan) or below (ask-heavy, red) zero
+fn draw_imbalance(f: &mut Frame, app: &App, area: Rect) {
+    let history = app.imbalance_history(&app.current_symbol, app.imbalance_windows);
+    // Chart has no bar type, so each bar is a column of points from zero to the value
+    let bars = |keep: fn(f64) -> bool| -> Vec<(f64, f64)> {
+        let mut points = Vec::new();
+        for (i, &value) in history.iter().enumerate().filter(|&(_, &v)| keep(v)) {
+            let steps = (value.abs() / IMBALANCE_BAR_STEP).round() as usize;
+            points.extend(
+                (0..=steps).map(|k| (i as f64, value.signum() * k as f64 * IMBALANCE_BAR_STEP)),
+            );
+        }
+        points
+    };
+    let bid_heavy = bars(|v| v >= 0.0);
+    let ask_heavy = bars(|v| v < 0.0);
+    let x_max = app.imbalance_windows.saturating_sub(1).max(1) as f64;
+    let zero = [(0.0, 0.0), (x_max, 0.0)];
+
+    let datasets = vec![
+        Dataset::default()
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::DarkGray))
+            .data(&zero),
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().fg(Color::Cyan))
+            .data(&bid_heavy),
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().fg(Color::Red))
+            .data(&ask_heavy),
+    ];
+    let title = match history.last() {
+        Some(latest) => format!("Imbalance ({} windows, now {:+.2})", history.len(), latest),
+        None => "Imbalance (no windows yet)".to_string(),
+    };
+    let chart = Chart::new(datasets)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .x_axis(Axis::default().bounds([0.0, x_max]))
+        .y_axis(Axis::default().bounds([-1.0, 1.0]).labels(vec![
+            Span::raw("-1"),
+            Span::raw("0"),
+            Span::raw("+1"),
+        ]));
+    f.render_widget(chart, area);
+}
+
+const TUNING_SLIDER_WIDTH: usize = 40;
+
+// Threshold slider along the bottom edge with the counts it currently produces
+fn draw_tuning(f: &mut Frame, app: &App, analysis: &MarketAnalysis) {
+    let threshold = app.human_threshold;
+    let knob = (threshold * TUNING_SLIDER_WIDTH as f64).round() as usize;
+    let slider = format!(
+        "0 {}|{} 1",
+        "=".repeat(knob),
+        "-".repeat(TUNING_SLIDER_WIDTH - knob)
+    );
+    let human = analysis.likely_human_orders;
+    let bot = analysis.total_orders - human;
+    let lines = vec![
+        Line::from(vec![
+            Span::raw(slider),
+            Span::styled(
+                format!("  {:.2}", threshold),
+                Style::default().fg(Color::Yellow),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                format!("human {}", human),
+                Style::default().fg(Color::Green),
+            ),
+            Span::raw(" | "),
+            Span::styled(format!("bot {}", bot), Style::default().fg(Color::Red)),
+            Span::raw(format!(" | of {} levels", analysis.total_orders)),
+        ]),
+    ];
+
+    let screen = f.size();
+    let width = 60.min(screen.width);
+    let height = 4.min(screen.height);
+    let area = Rect::new(
+        screen.x + (screen.width - width) / 2,
+        screen.y + screen.height - height,
+        width,
+        height,
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title("Tune threshold (left/right adjust, t to close)")
+                .borders(Borders::ALL),
+        ),
+        area,
+    );
+}
+
+// Input line for a note, pinned to the bottom like the tuning panel
+fn draw_annotation(f: &mut Frame, app: &App, note: &str) {
+    let screen = f.size();
+    let width = 60.min(screen.width);
+    let height = 3.min(screen.height);
+    let area = Rect::new(
+        screen.x + (screen.width - width) / 2,
+        screen.y + screen.height - height,
+        width,
+        height,
+    );
+    // Keep the end of a long note in view
+    let visible = width.saturating_sub(3) as usize;
+    let skip = note.chars().count().saturating_sub(visible);
+    let text: String = note
+        .chars()
+        .skip(skip)
+        .chain(std::iter::once('_'))
+        .collect();
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(text).block(
+            Block::default()
+                .title(format!(
+                    "Note for {} (enter to save, esc to cancel)",
+                    app.display_name(&app.current_symbol)
+                ))
+                .borders(Borders::ALL),
+        ),
+        area,
+    );
+}
+
+// Keybindings over a cleared box in the middle of the screen
+fn draw_help(f: &mut Frame) {
+    let mut lines: Vec<Line> = BINDINGS
+        .iter()
+        .map(|binding| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:>7}  ", binding.label),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(binding.description),
+            ])
+        })
+        .collect();
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        "Mouse: click a top mover to select it, scroll the tape",
+        Style::default().fg(Color::DarkGray),
+    ));
+
+    let screen = f.size();
+    let width = 60.min(screen.width);
+    let height = (lines.len() as u16 + 2).min(screen.height);
+    let area = Rect::new(
+        screen.x + (screen.width - width) / 2,
+        screen.y + (screen.height - height) / 2,
+        width,
+        height,
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title("Keys (? to close)")
+                .borders(Borders::ALL),
+        ),
+        area,
+    );
+}
+
+// Asks above bids, best prices meeting in the middle; likely-human levels in green, uncertain in
+// yellow, everything grey once the book is stale
+fn book_lines(app: &App) -> Vec<Line<'static>> {
+    let Some(book) = app.order_books.get(&app.current_symbol) else {
+        return Vec::new();
+    };
+    let symbol = &app.current_symbol;
+    // Custom markers are kept as configured; the emoji defaults fall back to ASCII without color
+    let markers = if app.no_color && app.markers == MarkerSet::default() {
+        MarkerSet::ascii()
+    } else {
+        app.markers.clone()
+    };
+    let heat = app.heat_row(symbol);
+    let stale = app.is_stale(symbol);
+    let level =
+        |orders: &[binance_ws::OrderBookEntry], index: usize, side: OrderSide, row: usize| {
+            let order = &orders[index];
+            let bucket = app
+                .level_bucket(symbol, orders, index)
+                .unwrap_or(ConfidenceBucket::LikelyBot);
+            let (side_marker, side_color) = match side {
+                OrderSide::Bid => (&markers.bid, Color::Cyan),
+                OrderSide::Ask => (&markers.ask, Color::Red),
+            };
+            let (class_marker, color) = match bucket {
+                ConfidenceBucket::LikelyHuman => (&markers.human, Color::Green),
+                ConfidenceBucket::Uncertain => (&markers.uncertain, Color::Yellow),
+                ConfidenceBucket::LikelyBot => (&markers.bot, side_color),
+            };
+            // A stalled feed's levels stay visible but greyed out
+            let style = Style::default().fg(if stale { Color::DarkGray } else { color });
+            let iceberg = if order
+                .human_indicators
+                .iter()
+                .any(|i| i == binance_ws::ICEBERG_INDICATOR)
+            {
+                " iceberg?"
+            } else {
+                ""
+            };
+            Line::from(vec![
+                heat_cell(heat.get(row).copied().flatten(), app),
+                Span::raw(" "),
+                Span::styled(
+                    format!(
+                        "{}{}{:>14} {:>14}{}",
+                        pad_marker(side_marker),
+                        pad_marker(class_marker),
+                        order.price,
+                        order.quantity,
+                        iceberg
+                    ),
+                    style,
+                ),
+            ])
+        };
+
+    let asks = book.asks.len().min(app.display_levels);
+    let bids = book.bids.len().min(app.display_levels);
+    let mut lines: Vec<Line> = (0..asks)
+        .rev()
+        .map(|i| level(&book.asks, i, OrderSide::Ask, asks - 1 - i))
+        .collect();
+    lines.push(Line::raw("-".repeat(38)));
+    lines.extend((0..bids).map(|i| level(&book.bids, i, OrderSide::Bid, asks + i)));
+    lines
+}
+
+// Heat strip cell: the level's human score through the ratio palette, neutral when the
+// level has no score. Without color the score is drawn as a shade instead.
+fn heat_cell(score: Option<f64>, app: &App) -> Span<'static> {
+    match (score, app.no_color) {
+        (None, true) => Span::raw("  "),
+        (None, false) => Span::styled("  ", Style::default().bg(Color::DarkGray)),
+        (Some(score), true) => {
+            let shades = ['░', '▒', '▓', '█'];
+            let shade = shades[((score.clamp(0.0, 1.0) * 3.0).round()) as usize];
+            Span::raw(shade.to_string().repeat(2))
+        }
+        (Some(score), false) => Span::styled(
+            "  ",
+            Style::default().bg(ratio_color(score, &app.ratio_palette)),
+        ),
+    }
+}
+
+// Pads to two columns plus a separator so one- and two-column markers align
+fn pad_marker(marker: &str) -> String {
+    format!(
+        "{}{} ",
+        marker,
+        " ".repeat(2usize.saturating_sub(marker_width(marker)))
+    )
+}
+
+// Aggregated quantity per price bucket, asks above bids like the raw view
+fn grouped_book_lines(app: &App, bucket: f64) -> Vec<Line<'static>> {
+    let Some(book) = app.order_books.get(&app.current_symbol) else {
+        return Vec::new();
+    };
+    let decimals = (-bucket.log10().floor()).max(0.0) as usize;
+    let row = |(price, quantity): (f64, f64), color: Color| {
+        Line::styled(
+            format!("{:>14.*} {:>14.4}", decimals, price, quantity),
+            Style::default().fg(color),
+        )
+    };
+
+    let mut asks = book.grouped(OrderSide::Ask, bucket);
+    asks.truncate(app.display_levels);
+    let mut bids = book.grouped(OrderSide::Bid, bucket);
+    bids.truncate(app.display_levels);
+
+    let mut lines: Vec<Line> = asks
+        .into_iter()
+        .rev()
+        .map(|level| row(level, Color::Red))
+        .collect();
+    lines.push(Line::raw("-".repeat(29)));
+    lines.extend(bids.into_iter().map(|level| row(level, Color::Cyan)));
+    lines
+}
+
+fn format_bucket(bucket: f64) -> String {
+    let decimals = (-bucket.log10().floor()).max(0.0) as usize;
+    format!("{:.*}", decimals, bucket)
+}
+
+fn threshold_label(app: &App) -> String {
+    let symbol = &app.current_symbol;
+    match &app.calibration {
+        Some(calibration) => match calibration.threshold(symbol) {
+            Some(threshold) => format!(
+                "Threshold {:.2} (p{:.0}, 'c' to recalibrate)",
+                threshold, calibration.percentile
+            ),
+            None => format!(
+                "Threshold {:.2} (calibrating {:.0}%)",
+                app.human_threshold,
+                calibration.progress(symbol) * 100.0
+            ),
+        },
+        None => format!("Threshold {:.2}", app.human_threshold),
+    }
+}
+
+// Resets every cell to the terminal defaults: no colors, no BOLD/REVERSED
+fn strip_styles(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        cell.set_style(Style::reset());
+    }
+}
+
+// Single line of text, vertically and horizontally centered in `area`
+fn centered(message: &str, area: Rect) -> Paragraph<'static> {
+    let padding = area.height.saturating_sub(1) / 2;
+    let mut lines = vec![Line::raw(""); padding as usize];
+    lines.push(Line::styled(
+        message.to_string(),
+        Style::default().fg(Color::DarkGray),
+    ));
+    Paragraph::new(lines).alignment(Alignment::Center)
+}
+
+// Linearly interpolates across the palette; 0.0 maps to the first color, 1.0 to the last
+pub fn ratio_color(ratio: f64, palette: &[(u8, u8, u8)]) -> Color {
+    match palette {
+        [] => Color::Reset,
+        [(r, g, b)] => Color::Rgb(*r, *g, *b),
+        _ => {
+            let position = ratio.clamp(0.0, 1.0) * (palette.len() - 1) as f64;
+            let index = (position.floor() as usize).min(palette.len() - 2);
+            let t = position - index as f64;
+            let (from, to) = (palette[index], palette[index + 1]);
+            let lerp = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+            Color::Rgb(lerp(from.0, to.0), lerp(from.1, to.1), lerp(from.2, to.2))
+        }
+    }
+}
+
+// HH:MM:SS.mmm (UTC) of a unix timestamp in milliseconds
+fn utc_time_of_day(ts_ms: u64) -> String {
+    let ms = ts_ms % 86_400_000;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+// No greens or reds, which already mean bid/ask and buy/sell
+const SYMBOL_PALETTE: &[Color] = &[
+    Color::Cyan,
+    Color::Magenta,
+    Color::Yellow,
+    Color::Blue,
+    Color::LightCyan,
+    Color::LightMagenta,
+    Color::LightYellow,
+    Color::LightBlue,
+];
+
+// Hashed (FNV-1a) rather than assigned, so a symbol keeps its color across sessions
+pub fn symbol_color(symbol: &str) -> Color {
+    let hash = symbol.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    SYMBOL_PALETTE[(hash % SYMBOL_PALETTE.len() as u64) as usize]
 }
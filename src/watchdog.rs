@@ -0,0 +1,74 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+// Main-loop liveness, shared with a watchdog task. Times are ms since `start`.
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    start: Instant,
+    last_beat_ms: Arc<AtomicU64>,
+    stall_ms: Arc<AtomicU64>, // longest gap seen by the watchdog since the last take_stall, 0 if none
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Heartbeat {
+            start: Instant::now(),
+            last_beat_ms: Arc::new(AtomicU64::new(0)),
+            stall_ms: Arc::new(AtomicU64::new(0)),
+        }
+    }
+}
+
+impl Heartbeat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn now_ms(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+
+    pub fn beat(&self) {
+        self.last_beat_ms.store(self.now_ms(), Ordering::Relaxed);
+    }
+
+    pub fn age(&self) -> Duration {
+        let last = self.last_beat_ms.load(Ordering::Relaxed);
+        Duration::from_millis(self.now_ms().saturating_sub(last))
+    }
+
+    // Stall the watchdog reported since the last call, if any
+    pub fn take_stall(&self) -> Option<Duration> {
+        match self.stall_ms.swap(0, Ordering::Relaxed) {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+}
+
+// Logs once when the heartbeat goes stale and again when it resumes
+pub async fn watch(heartbeat: Heartbeat, threshold: Duration) {
+    let mut stalled = false;
+    let mut interval = tokio::time::interval(threshold.min(Duration::from_secs(1)));
+    loop {
+        interval.tick().await;
+        let age = heartbeat.age();
+        if age > threshold {
+            if !stalled {
+                warn!(
+                    stalled_ms = age.as_millis() as u64,
+                    "UI STALLED: main loop heartbeat is stale"
+                );
+                stalled = true;
+            }
+            heartbeat
+                .stall_ms
+                .fetch_max(age.as_millis() as u64, Ordering::Relaxed);
+        } else if stalled {
+            info!("Main loop heartbeat resumed");
+            stalled = false;
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use binance_ws::clock::MockClock;
+use binance_ws::{db::Database, App, OrderBook};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn book(levels: usize) -> serde_json::Value {
+    let side = |start: f64, step: f64| -> Vec<[String; 2]> {
+        (0..levels)
+            .map(|i| [format!("{:.2}", start + step * i as f64), "1.0".to_string()])
+            .collect()
+    };
+    json!({ "symbol": "BTCUSDT", "bids": side(100.0, -0.5), "asks": side(101.0, 0.5) })
+}
+
+#[test]
+fn reuses_analysis_until_the_symbol_updates() {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.set_symbols(&["btcusdt".to_string()]);
+    app.update_orders(&book(3));
+    assert_eq!(app.analyze_all_symbols()["BTCUSDT"].total_orders, 6);
+
+    // Changed behind update_orders' back, so the cached result still stands
+    app.order_books
+        .insert("BTCUSDT".to_string(), OrderBook::from_json(&book(5)));
+    assert_eq!(app.analyze_all_symbols()["BTCUSDT"].total_orders, 6);
+
+    app.update_orders(&book(4));
+    assert_eq!(app.analyze_all_symbols()["BTCUSDT"].total_orders, 8);
+    assert_eq!(app.cached_analysis("BTCUSDT").unwrap().total_orders, 8);
+}
+
+#[test]
+fn analyze_every_frame_skips_the_cache() {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.set_symbols(&["btcusdt".to_string()]);
+    app.analyze_every_frame = true;
+    app.update_orders(&book(3));
+    app.analyze_all_symbols();
+    app.order_books
+        .insert("BTCUSDT".to_string(), OrderBook::from_json(&book(5)));
+    assert_eq!(app.analyze_all_symbols()["BTCUSDT"].total_orders, 10);
+}
+
+#[test]
+fn displayed_analysis_holds_until_the_refresh_interval() {
+    let clock = MockClock::new(1_700_000_000_000);
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.set_clock(Arc::new(clock.clone()));
+    app.set_symbols(&["btcusdt".to_string()]);
+    app.analysis_refresh = Duration::from_millis(500);
+    app.update_orders(&book(3));
+    assert_eq!(app.displayed_analyses()["BTCUSDT"].total_orders, 6);
+
+    // The live analysis follows the feed while the displayed one waits out the interval
+    app.update_orders(&book(4));
+    clock.advance(Duration::from_millis(400));
+    assert_eq!(app.displayed_analyses()["BTCUSDT"].total_orders, 6);
+    assert_eq!(app.cached_analysis("BTCUSDT").unwrap().total_orders, 8);
+
+    clock.advance(Duration::from_millis(100));
+    assert_eq!(app.displayed_analyses()["BTCUSDT"].total_orders, 8);
+}
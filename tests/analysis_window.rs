@@ -0,0 +1,54 @@
+use binance_ws::db::MarketAnalysisRecord;
+use binance_ws::{AnalysisWindow, ANALYSIS_WRITE_INTERVAL};
+use std::time::{Duration, Instant};
+
+#[test]
+fn closes_once_per_write_interval() {
+    let start = Instant::now();
+    let mut window = AnalysisWindow::new(start);
+    let mut closed = Vec::new();
+    // A tick every 500ms for 12 seconds
+    for tick in 0..=24u64 {
+        let now = start + Duration::from_millis(tick * 500);
+        window.push(10 + tick as usize % 2, 3);
+        if let Some(average) = window.close(now) {
+            closed.push((now.duration_since(start), average));
+        }
+    }
+    let at: Vec<Duration> = closed.iter().map(|&(at, _)| at).collect();
+    assert_eq!(at, [ANALYSIS_WRITE_INTERVAL, ANALYSIS_WRITE_INTERVAL * 2]);
+    // Ticks 0..=10 alternate 10 and 11 orders: six tens and five elevens
+    assert_eq!(closed[0].1, (115.0 / 11.0, 3.0));
+    // The second window holds ticks 11..=20 only
+    assert_eq!(closed[1].1, (105.0 / 10.0, 3.0));
+}
+
+#[test]
+fn an_empty_window_writes_nothing() {
+    let start = Instant::now();
+    let mut window = AnalysisWindow::new(start);
+    assert_eq!(window.close(start + ANALYSIS_WRITE_INTERVAL * 3), None);
+}
+
+#[test]
+fn averages_round_to_whole_orders() {
+    // Truncating 2.9 total / 2.0 human would store 2 total, 2 human
+    let record = MarketAnalysisRecord::from_average("BTCUSDT", 0, 2.9, 2.0);
+    assert_eq!(
+        (record.total_orders, record.human_orders, record.bot_orders),
+        (3, 2, 1)
+    );
+    // Truncating 3.0 - 1.5 would store 1 bot order next to 3 total and 1 human
+    let record = MarketAnalysisRecord::from_average("BTCUSDT", 0, 3.0, 1.5);
+    assert_eq!(record.total_orders, record.human_orders + record.bot_orders);
+    assert!((record.human_ratio - 0.5).abs() < 1e-12);
+}
+
+#[test]
+fn bot_orders_never_go_negative() {
+    for (total, human) in [(2.5, 2.5), (2.4, 2.4), (0.4, 0.4), (0.0, 0.0), (4.49, 4.5)] {
+        let record = MarketAnalysisRecord::from_average("BTCUSDT", 0, total, human);
+        assert!(record.bot_orders >= 0, "{} / {}", total, human);
+        assert_eq!(record.total_orders, record.human_orders + record.bot_orders);
+    }
+}
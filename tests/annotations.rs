@@ -0,0 +1,83 @@
+use binance_ws::clock::MockClock;
+use binance_ws::db::Database;
+use binance_ws::recording::{Playback, Recorder, Replayer};
+use binance_ws::App;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Instant;
+
+fn app() -> App {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.set_clock(Arc::new(MockClock::new(1_700_000_000_000)));
+    app.update_orders(&json!({
+        "symbol": "BTCUSDT",
+        "bids": [["50000.00", "1.5"]],
+        "asks": [["50001.00", "0.25"]]
+    }));
+    app
+}
+
+#[test]
+fn saves_the_note_with_the_top_of_book() {
+    let mut app = app();
+    app.start_annotation();
+    app.annotation
+        .as_mut()
+        .unwrap()
+        .push_str("  iceberg on the bid ");
+    app.save_annotation().unwrap();
+    assert!(app.annotation.is_none());
+
+    let notes = app.db.get_annotations(Some("BTCUSDT")).unwrap();
+    assert_eq!(notes.len(), 1);
+    let note = &notes[0];
+    assert_eq!(note.note, "iceberg on the bid");
+    assert_eq!(note.timestamp, 1_700_000_000);
+    assert_eq!(note.bid_price.as_deref(), Some("50000.00"));
+    assert_eq!(note.bid_quantity.as_deref(), Some("1.5"));
+    assert_eq!(note.ask_price.as_deref(), Some("50001.00"));
+    assert_eq!(note.ask_quantity.as_deref(), Some("0.25"));
+    assert!(app.db.get_annotations(Some("ETHUSDT")).unwrap().is_empty());
+    assert_eq!(app.db.get_annotations(None).unwrap().len(), 1);
+}
+
+#[test]
+fn blank_notes_are_dropped() {
+    let mut app = app();
+    app.start_annotation();
+    app.annotation.as_mut().unwrap().push_str("   ");
+    app.save_annotation().unwrap();
+    assert!(app.annotation.is_none());
+    assert!(app.db.get_annotations(None).unwrap().is_empty());
+}
+
+#[test]
+fn notes_taken_in_playback_use_the_recording_time() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.jsonl");
+    let mut recorder = Recorder::create(&path, 60_000).unwrap();
+    recorder
+        .record(&json!({ "symbol": "BTCUSDT", "bids": [], "asks": [], "receivedAt": 1_600_000_000_000u64 }))
+        .unwrap();
+    recorder.flush().unwrap();
+
+    let mut app = app();
+    app.playback = Some(Playback::new(
+        Replayer::open(&path).unwrap(),
+        1_600_000_000_000,
+        Instant::now(),
+    ));
+    app.annotation_db = Some(Database::open_in_memory().unwrap());
+    app.start_annotation();
+    app.annotation.as_mut().unwrap().push_str("replayed");
+    app.save_annotation().unwrap();
+
+    assert!(app.db.get_annotations(None).unwrap().is_empty());
+    let notes = app
+        .annotation_db
+        .as_ref()
+        .unwrap()
+        .get_annotations(None)
+        .unwrap();
+    assert_eq!(notes[0].timestamp, 1_600_000_000);
+}
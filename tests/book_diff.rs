@@ -0,0 +1,80 @@
+use binance_ws::{LevelChange, OrderBook};
+use serde_json::json;
+use std::time::Instant;
+
+fn before() -> OrderBook {
+    OrderBook::from_json(&json!({
+        "bids": [["100.00", "1.0"], ["99.00", "2.0"], ["98.00", "3.0"]],
+        "asks": [["101.00", "1.0"], ["102.00", "2.0"]]
+    }))
+}
+
+fn after() -> OrderBook {
+    OrderBook::from_json(&json!({
+        "bids": [["100.0", "1.0"], ["99.00", "0.5"], ["97.00", "4.0"]],
+        "asks": [["101.00", "1.0"], ["102.00", "2.0"]]
+    }))
+}
+
+#[test]
+fn reports_added_removed_and_modified_levels_per_side() {
+    let diff = after().diff(&before());
+
+    let added: Vec<&str> = diff.bids.added.iter().map(|l| l.price.as_str()).collect();
+    let removed: Vec<&str> = diff.bids.removed.iter().map(|l| l.price.as_str()).collect();
+    assert_eq!(added, ["97.00"]);
+    assert_eq!(removed, ["98.00"]);
+    assert_eq!(
+        diff.bids.modified,
+        [LevelChange {
+            price: "99.00".to_string(),
+            before: "2.0".to_string(),
+            after: "0.5".to_string(),
+        }]
+    );
+    assert!(diff.asks.is_empty());
+}
+
+#[test]
+fn identical_books_have_an_empty_diff() {
+    assert!(before().diff(&before()).is_empty());
+}
+
+#[test]
+fn last_diff_compares_against_the_replaced_levels() {
+    let mut book = before();
+    book.replace_levels(
+        &json!({
+            "bids": [["100.00", "1.0"], ["99.00", "2.0"], ["98.00", "3.0"]],
+            "asks": [["102.00", "2.0"]]
+        }),
+        0.0,
+        Default::default(),
+        Instant::now(),
+    );
+    let diff = book.last_diff();
+    assert!(diff.bids.is_empty());
+    assert_eq!(diff.asks.removed.len(), 1);
+    assert_eq!(diff.asks.removed[0].price, "101.00");
+}
+
+#[test]
+fn a_diff_event_is_compared_against_the_book_before_it() {
+    let mut book = before();
+    book.apply(
+        &json!({
+            "bids": [["99.00", "0.5"], ["98.00", "0"], ["97.00", "4.0"]],
+            "asks": [],
+            "eventTime": 1
+        }),
+        0.0,
+        Default::default(),
+        Instant::now(),
+    );
+    let diff = book.last_diff();
+    assert_eq!(diff.bids.added[0].price, "97.00");
+    assert_eq!(diff.bids.removed[0].price, "98.00");
+    assert_eq!(diff.bids.modified[0].price, "99.00");
+    // Levels the event doesn't name are unchanged, not removed
+    assert!(diff.asks.is_empty());
+}
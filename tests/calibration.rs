@@ -0,0 +1,46 @@
+use binance_ws::calibration::Calibration;
+
+#[test]
+fn waits_for_the_warmup_before_setting_a_threshold() {
+    let mut calibration = Calibration::new(80.0, 10);
+    calibration.observe("BTCUSDT", [0.1, 0.2, 0.3]);
+    assert_eq!(calibration.threshold("BTCUSDT"), None);
+    assert!((calibration.progress("BTCUSDT") - 0.3).abs() < 1e-9);
+
+    calibration.observe("BTCUSDT", (0..7).map(|i| i as f64 / 10.0));
+    assert!(calibration.threshold("BTCUSDT").is_some());
+    assert_eq!(calibration.threshold("ETHUSDT"), None);
+}
+
+#[test]
+fn roughly_the_top_share_of_distinct_scores_is_above_the_threshold() {
+    let mut calibration = Calibration::new(80.0, 100);
+    let scores: Vec<f64> = (1..=100).map(f64::from).collect();
+    calibration.observe("BTCUSDT", scores.iter().copied());
+
+    let threshold = calibration.threshold("BTCUSDT").unwrap();
+    assert_eq!(threshold, 80.0);
+    assert_eq!(scores.iter().filter(|&&s| s > threshold).count(), 20);
+}
+
+#[test]
+fn ties_at_the_percentile_still_leave_levels_above_the_threshold() {
+    let mut calibration = Calibration::new(80.0, 20);
+    // 8 of 20 levels score exactly 1.0, so the 80th percentile value is 1.0 itself
+    let scores: Vec<f64> = (0..12)
+        .map(|i| i as f64 / 20.0)
+        .chain(std::iter::repeat_n(1.0, 8))
+        .collect();
+    calibration.observe("BTCUSDT", scores.iter().copied());
+
+    let threshold = calibration.threshold("BTCUSDT").unwrap();
+    assert_eq!(threshold, 0.55);
+    assert_eq!(scores.iter().filter(|&&s| s > threshold).count(), 8);
+}
+
+#[test]
+fn identical_scores_fall_back_to_that_score() {
+    let mut calibration = Calibration::new(80.0, 5);
+    calibration.observe("BTCUSDT", [0.5; 5]);
+    assert_eq!(calibration.threshold("BTCUSDT"), Some(0.5));
+}
@@ -0,0 +1,42 @@
+use binance_ws::capture::{CaptureTrigger, EventCapture};
+use binance_ws::db::Database;
+use binance_ws::App;
+use serde_json::json;
+use std::fs;
+
+fn app_capturing_to(dir: &std::path::Path) -> App {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    let mut capture = EventCapture::new(dir.to_path_buf());
+    capture.triggers = vec![CaptureTrigger::CrossedBook];
+    app.capture = Some(capture);
+    app
+}
+
+#[test]
+fn parses_trigger_names() {
+    assert_eq!("crossed-book".parse(), Ok(CaptureTrigger::CrossedBook));
+    assert_eq!("Human-Spike".parse(), Ok(CaptureTrigger::HumanSpike));
+    assert!("spike".parse::<CaptureTrigger>().is_err());
+}
+
+#[test]
+fn crossed_book_writes_one_bundle_per_cooldown() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut app = app_capturing_to(dir.path());
+
+    app.update_orders(&json!({
+        "symbol": "BTCUSDT",
+        "bids": [["50001.00", "1.0"]],
+        "asks": [["50000.00", "2.0"]]
+    }));
+    let written = app.capture_events().unwrap();
+    assert_eq!(written.len(), 1);
+    assert!(app.capture_events().unwrap().is_empty());
+
+    let bundle: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&written[0]).unwrap()).unwrap();
+    assert_eq!(bundle["symbol"], "BTCUSDT");
+    assert_eq!(bundle["trigger"], "crossed-book");
+    assert_eq!(bundle["bids"][0], json!(["50001.00", "1.0"]));
+    assert_eq!(bundle["messages"].as_array().unwrap().len(), 2);
+}
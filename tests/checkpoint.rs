@@ -0,0 +1,20 @@
+use binance_ws::db::{BookSnapshotRecord, Database};
+
+#[test]
+fn file_databases_use_the_write_ahead_log() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("wal.db");
+    let db = Database::open(&path).unwrap();
+    db.insert_snapshot(&BookSnapshotRecord {
+        symbol: "BTCUSDT".to_string(),
+        timestamp: 0,
+        bids: "[]".to_string(),
+        asks: "[]".to_string(),
+    })
+    .unwrap();
+    let wal = dir.path().join("wal.db-wal");
+    assert!(std::fs::metadata(&wal).unwrap().len() > 0);
+
+    db.checkpoint().unwrap();
+    assert_eq!(std::fs::metadata(&wal).unwrap().len(), 0);
+}
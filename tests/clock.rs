@@ -0,0 +1,112 @@
+use binance_ws::clock::{Clock, MockClock};
+use binance_ws::db::{Database, MarketAnalysisRecord};
+use binance_ws::{App, DEFAULT_LATEST_CACHE_TTL};
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn app_with_clock(clock: &MockClock) -> App {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.set_clock(Arc::new(clock.clone()));
+    // The fixture is loaded once; keep it from going stale as fake time passes
+    app.stale_after = Duration::from_secs(3600);
+    let mut book: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string("tests/fixtures/btcusdt_book.json").unwrap())
+            .unwrap();
+    book["symbol"] = "BTCUSDT".into();
+    app.update_orders(&book);
+    app
+}
+
+#[test]
+fn mock_clock_only_moves_when_advanced() {
+    let clock = MockClock::new(1_700_000_000_000);
+    let start = clock.now();
+    assert_eq!(clock.now(), start);
+    clock.advance(Duration::from_millis(1500));
+    assert_eq!(clock.now() - start, Duration::from_millis(1500));
+    assert_eq!(clock.unix_secs(), 1_700_000_001);
+}
+
+#[test]
+fn writes_one_record_per_window_of_fake_time() {
+    let clock = MockClock::new(1_700_000_000_000);
+    let mut app = app_with_clock(&clock);
+
+    for _ in 0..=11 {
+        app.analyze_symbol("BTCUSDT");
+        clock.advance(Duration::from_secs(1));
+    }
+    let records = app
+        .db
+        .get_analysis_between("BTCUSDT", 1_700_000_000, 1_700_000_020)
+        .unwrap();
+    let timestamps: Vec<u64> = records.iter().map(|r| r.timestamp).collect();
+    assert_eq!(timestamps, [1_700_000_005, 1_700_000_010]);
+}
+
+#[test]
+fn windows_restart_after_a_pause() {
+    let clock = MockClock::new(1_700_000_000_000);
+    let mut app = app_with_clock(&clock);
+
+    app.analyze_symbol("BTCUSDT");
+    app.toggle_pause();
+    for _ in 0..30 {
+        clock.advance(Duration::from_secs(1));
+        app.analyze_symbol("BTCUSDT");
+    }
+    app.toggle_pause();
+    // The first window after resuming is a full interval of unpaused samples
+    for _ in 0..=5 {
+        app.analyze_symbol("BTCUSDT");
+        clock.advance(Duration::from_secs(1));
+    }
+    let records = app
+        .db
+        .get_analysis_between("BTCUSDT", 1_700_000_000, 1_700_000_100)
+        .unwrap();
+    let timestamps: Vec<u64> = records.iter().map(|r| r.timestamp).collect();
+    assert_eq!(timestamps, [1_700_000_035]);
+}
+
+#[test]
+fn latest_analysis_is_cached_for_the_ttl() {
+    let clock = MockClock::new(1_700_000_000_000);
+    let mut app = app_with_clock(&clock);
+    assert!(app.latest_analysis("BTCUSDT").is_none());
+
+    // Written behind the app's back, so only the TTL expiring can reveal it
+    app.db
+        .insert_analysis(&MarketAnalysisRecord::from_average(
+            "BTCUSDT",
+            1_700_000_000,
+            10.0,
+            4.0,
+        ))
+        .unwrap();
+    clock.advance(DEFAULT_LATEST_CACHE_TTL - Duration::from_millis(1));
+    assert!(app.latest_analysis("BTCUSDT").is_none());
+    clock.advance(Duration::from_millis(1));
+    assert_eq!(app.latest_analysis("BTCUSDT").unwrap().human_orders, 4);
+}
+
+#[test]
+fn nothing_is_written_during_warmup() {
+    let clock = MockClock::new(1_700_000_000_000);
+    let mut app = app_with_clock(&clock);
+    app.warmup = Duration::from_secs(10);
+
+    for _ in 0..=20 {
+        assert!(app.analyze_symbol("BTCUSDT").total_orders > 0);
+        clock.advance(Duration::from_secs(1));
+    }
+    // The first window opens when warm-up ends at +10s
+    let records = app
+        .db
+        .get_analysis_between("BTCUSDT", 1_700_000_000, 1_700_000_030)
+        .unwrap();
+    let timestamps: Vec<u64> = records.iter().map(|r| r.timestamp).collect();
+    assert_eq!(timestamps, [1_700_000_015, 1_700_000_020]);
+    assert!(app.warmup_remaining().is_none());
+}
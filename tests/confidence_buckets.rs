@@ -0,0 +1,22 @@
+use binance_ws::{ConfidenceBucket, ConfidenceCutoffs};
+
+#[test]
+fn default_cutoffs_split_scores_three_ways() {
+    let cutoffs = ConfidenceCutoffs::default();
+    assert_eq!(cutoffs.classify(0.9), ConfidenceBucket::LikelyHuman);
+    assert_eq!(cutoffs.classify(0.7), ConfidenceBucket::Uncertain);
+    assert_eq!(cutoffs.classify(0.5), ConfidenceBucket::Uncertain);
+    assert_eq!(cutoffs.classify(0.3), ConfidenceBucket::Uncertain);
+    assert_eq!(cutoffs.classify(0.1), ConfidenceBucket::LikelyBot);
+}
+
+#[test]
+fn equal_cutoffs_leave_no_uncertain_range_but_the_cutoff_itself() {
+    let cutoffs = ConfidenceCutoffs {
+        human: 0.5,
+        bot: 0.5,
+    };
+    assert_eq!(cutoffs.classify(0.51), ConfidenceBucket::LikelyHuman);
+    assert_eq!(cutoffs.classify(0.5), ConfidenceBucket::Uncertain);
+    assert_eq!(cutoffs.classify(0.49), ConfidenceBucket::LikelyBot);
+}
@@ -0,0 +1,62 @@
+use binance_ws::config::{Config, DEFAULT_CONFIG};
+use binance_ws::{
+    MarkerSet, DEFAULT_BURST_MULTIPLIER, DEFAULT_FILL_WINDOW_BPS, DEFAULT_IMBALANCE_WINDOWS,
+    DEFAULT_MAX_INDICATORS,
+};
+
+// The generated file with every setting uncommented
+fn uncommented_default() -> String {
+    DEFAULT_CONFIG
+        .lines()
+        .map(|line| match line.strip_prefix("# ") {
+            Some(setting) if setting.contains(" = ") || setting.starts_with('[') => setting,
+            _ => line,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn generated_default_parses_with_every_key_set() {
+    let config = Config::parse(&uncommented_default()).unwrap();
+    assert_eq!(config.human_threshold, Some(0.6));
+    assert_eq!(config.display_levels, Some(10));
+    assert_eq!(config.alias.unwrap()["BTCUSDT"], "BTC");
+    assert_eq!(config.min_notional_for.unwrap()["BTCUSDT"], 1000.0);
+}
+
+#[test]
+fn unmarked_values_are_the_built_in_defaults() {
+    let defaults: String = uncommented_default()
+        .lines()
+        .take_while(|line| !line.starts_with('['))
+        .filter(|line| !line.contains("# example"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let config = Config::parse(&defaults).unwrap();
+    let markers = MarkerSet::default();
+    assert_eq!(config.human_marker, Some(markers.human));
+    assert_eq!(config.ask_marker, Some(markers.ask));
+    assert_eq!(config.analysis_refresh_ms, Some(0));
+    assert_eq!(config.max_indicators, Some(DEFAULT_MAX_INDICATORS));
+    assert_eq!(config.imbalance_windows, Some(DEFAULT_IMBALANCE_WINDOWS));
+    assert_eq!(config.burst_multiplier, Some(DEFAULT_BURST_MULTIPLIER));
+    assert_eq!(config.fill_window_bps, Some(DEFAULT_FILL_WINDOW_BPS));
+    // Unset by default, so only ever shown as examples
+    assert!(config.max_reconnect_attempts.is_none());
+    assert!(config.warmup.is_none());
+    assert!(config.history_file.is_none());
+    assert!(config.alert_human_orders.is_none());
+}
+
+#[test]
+fn commented_default_sets_nothing() {
+    let config = Config::parse(DEFAULT_CONFIG).unwrap();
+    assert!(config.symbols.is_none());
+    assert!(config.human_threshold.is_none());
+}
+
+#[test]
+fn rejects_unknown_keys() {
+    assert!(Config::parse("human-treshold = 0.7").is_err());
+}
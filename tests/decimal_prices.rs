@@ -0,0 +1,74 @@
+use binance_ws::exchange::SymbolFilters;
+use binance_ws::{is_round_price, step_alignment, LevelSignals, OrderBook};
+use rust_decimal::Decimal;
+use serde_json::json;
+
+fn signals(bids: serde_json::Value) -> LevelSignals {
+    let book = OrderBook::from_json(&json!({ "bids": bids, "asks": [] }));
+    LevelSignals::compute(&book.bids, 0, None, None).unwrap()
+}
+
+fn decimal(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+#[test]
+fn round_price_is_exact_either_side_of_fifty_thousand() {
+    assert!(signals(json!([["50000.00", "1.0"]])).round_price);
+    assert!(!signals(json!([["49999.99", "1.0"]])).round_price);
+}
+
+#[test]
+fn spacing_on_a_tenth_multiple_is_not_human() {
+    // 50000.30 - 50000.00 is 0.3 exactly; as f64, 0.3 % 0.1 is not zero
+    let aligned = signals(json!([["50000.30", "1.0"], ["50000.00", "1.0"]]));
+    assert_eq!(aligned.human_spacing, Some(false));
+    let ragged = signals(json!([["50000.00", "1.0"], ["49999.63", "1.0"]]));
+    assert_eq!(ragged.human_spacing, Some(true));
+}
+
+#[test]
+fn step_alignment_uses_exact_step_multiples() {
+    let step = decimal("0.00001000");
+    assert_eq!(step_alignment(decimal("0.13000000"), step), Some(true));
+    assert_eq!(step_alignment(decimal("0.13742000"), step), Some(false));
+    assert_eq!(step_alignment(decimal("0.137425"), step), None);
+}
+
+#[test]
+fn round_prices_scale_with_a_fine_tick() {
+    // XRP-style 0.0001 tick: whole cents are round, a full-precision price is not
+    let tick = Some(decimal("0.0001"));
+    assert!(is_round_price(decimal("0.5200"), tick));
+    assert!(is_round_price(decimal("0.5000"), tick));
+    assert!(!is_round_price(decimal("0.5234"), tick));
+    assert!(!is_round_price(decimal("0.5250"), tick));
+    // The fixed levels would call every one of these round: their whole part is zero
+    assert!(is_round_price(decimal("0.5234"), None));
+}
+
+#[test]
+fn round_prices_scale_with_a_coarse_tick() {
+    // BTC-style 0.01 tick: whole dollars are round, cents are not
+    let tick = Some(decimal("0.01"));
+    assert!(is_round_price(decimal("50001.00"), tick));
+    assert!(is_round_price(decimal("50000.00"), tick));
+    assert!(!is_round_price(decimal("50000.37"), tick));
+    assert!(!is_round_price(decimal("50000.50"), tick));
+}
+
+#[test]
+fn level_signals_use_the_symbol_tick_size() {
+    let book = OrderBook::from_json(&json!({ "bids": [["0.5234", "100"]], "asks": [] }));
+    let filters = SymbolFilters {
+        step_size: decimal("1"),
+        tick_size: decimal("0.0001"),
+    };
+    let with_tick = LevelSignals::compute(&book.bids, 0, Some(&filters), None).unwrap();
+    assert!(!with_tick.round_price);
+    assert!(
+        LevelSignals::compute(&book.bids, 0, None, None)
+            .unwrap()
+            .round_price
+    );
+}
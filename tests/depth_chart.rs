@@ -0,0 +1,42 @@
+use binance_ws::depth_chart::{cumulative_depth, render_svg};
+use binance_ws::{AppError, OrderBook};
+use serde_json::json;
+use std::fs;
+
+fn book() -> OrderBook {
+    OrderBook::from_json(&json!({
+        "bids": [["99.00", "2.0"], ["100.00", "1.5"], ["98.00", "0.5"]],
+        "asks": [["101.00", "1.0"], ["102.00", "3.0"]]
+    }))
+}
+
+#[test]
+fn sums_quantity_outwards_from_the_best_price() {
+    let (bids, asks) = cumulative_depth(&book());
+    assert_eq!(bids, [(100.0, 1.5), (99.0, 3.5), (98.0, 4.0)]);
+    assert_eq!(asks, [(101.0, 1.0), (102.0, 4.0)]);
+}
+
+#[test]
+fn an_empty_book_has_no_curve_and_no_chart() {
+    let book = OrderBook::new();
+    let (bids, asks) = cumulative_depth(&book);
+    assert!(bids.is_empty() && asks.is_empty());
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("empty.svg");
+    assert!(matches!(
+        render_svg(&book, "BTCUSDT", &path),
+        Err(AppError::Chart(_))
+    ));
+}
+
+#[test]
+fn renders_an_svg() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("depth.svg");
+    render_svg(&book(), "BTCUSDT", &path).unwrap();
+    let svg = fs::read_to_string(&path).unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("BTCUSDT depth"));
+}
@@ -0,0 +1,62 @@
+use binance_ws::exchange::parse_depth_snapshot;
+use binance_ws::AppError;
+use serde_json::json;
+
+#[test]
+fn normalizes_a_depth_response() {
+    let snapshot = parse_depth_snapshot(
+        "btcusdt",
+        200,
+        &json!({
+            "lastUpdateId": 42,
+            "bids": [["50000.00", "1.0"]],
+            "asks": [["50001.00", "2.0"]]
+        }),
+    )
+    .unwrap();
+    assert_eq!(snapshot["symbol"], "BTCUSDT");
+    assert_eq!(snapshot["lastUpdateId"], 42);
+    assert_eq!(snapshot["bids"][0][0], "50000.00");
+}
+
+#[test]
+fn rate_limit_body_is_a_retryable_error() {
+    let err = parse_depth_snapshot(
+        "btcusdt",
+        429,
+        &json!({"code": -1003, "msg": "Too many requests; current limit is 6000 request weight per 1 MINUTE."}),
+    )
+    .unwrap_err();
+    assert!(matches!(err, AppError::Api { code: -1003, .. }));
+    assert!(err.is_transient());
+    assert!(err.to_string().contains("Too many requests"));
+
+    let err = parse_depth_snapshot(
+        "btcusdt",
+        400,
+        &json!({"code": -1121, "msg": "Invalid symbol."}),
+    )
+    .unwrap_err();
+    assert!(!err.is_transient());
+}
+
+#[test]
+fn missing_sides_are_rejected() {
+    let err =
+        parse_depth_snapshot("btcusdt", 200, &json!({"lastUpdateId": 1, "bids": []})).unwrap_err();
+    assert!(matches!(err, AppError::BadSnapshot(ref symbol, _) if symbol == "BTCUSDT"));
+    assert!(parse_depth_snapshot("btcusdt", 200, &json!({"bids": {}, "asks": []})).is_err());
+}
+
+#[test]
+fn an_ip_ban_is_not_retried() {
+    let err = parse_depth_snapshot(
+        "btcusdt",
+        418,
+        &json!({"code": -1003, "msg": "Way too many requests; IP banned until 1700000000000."}),
+    )
+    .unwrap_err();
+    assert!(matches!(err, AppError::Banned(_)));
+    assert!(!err.is_transient());
+    assert!(err.to_string().contains("banned until 1700000000000"));
+}
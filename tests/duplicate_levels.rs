@@ -0,0 +1,32 @@
+use binance_ws::{DuplicatePolicy, OrderBook};
+use serde_json::json;
+use std::time::Instant;
+
+fn payload() -> serde_json::Value {
+    json!({
+        "symbol": "BTCUSDT",
+        "bids": [["100.00", "1.5"], ["99.00", "2.0"], ["100.0", "0.25"]],
+        "asks": [["101.00", "1.0"]]
+    })
+}
+
+#[test]
+fn keeps_the_last_row_for_a_repeated_price() {
+    let mut book = OrderBook::new();
+    let merged = book.replace_levels(&payload(), 0.0, DuplicatePolicy::KeepLast, Instant::now());
+    assert_eq!(merged, 1);
+    assert_eq!(book.bids.len(), 2);
+    assert_eq!(book.bids[0].quantity, "0.25");
+    assert_eq!(book.bids[1].price, "99.00");
+}
+
+#[test]
+fn sums_quantities_for_a_repeated_price() {
+    let mut book = OrderBook::new();
+    let merged = book.replace_levels(&payload(), 0.0, DuplicatePolicy::Sum, Instant::now());
+    assert_eq!(merged, 1);
+    assert_eq!(book.bids.len(), 2);
+    assert_eq!(book.bids[0].price, "100.00");
+    assert_eq!(book.bids[0].quantity, "1.75");
+    assert!((book.bids[0].total() - 175.0).abs() < 1e-9);
+}
@@ -0,0 +1,49 @@
+use binance_ws::db::Database;
+use binance_ws::exchange::{is_invalid_symbol_error, parse_exchange_info};
+use binance_ws::App;
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::fs;
+
+fn fixture() -> serde_json::Value {
+    serde_json::from_str(&fs::read_to_string("tests/fixtures/exchange_info.json").unwrap()).unwrap()
+}
+
+#[test]
+fn reads_step_and_tick_sizes_per_symbol() {
+    let filters = parse_exchange_info(&fixture());
+    let btc = &filters["BTCUSDT"];
+    assert_eq!(btc.step_size, "0.00001".parse::<Decimal>().unwrap());
+    assert_eq!(btc.tick_size, "0.01".parse::<Decimal>().unwrap());
+    assert_eq!(
+        filters["ETHUSDT"].step_size,
+        "0.0001".parse::<Decimal>().unwrap()
+    );
+    // No LOT_SIZE filter, so nothing to align quantities to
+    assert!(!filters.contains_key("NOLOTUSDT"));
+    assert_eq!(filters.len(), 2);
+}
+
+#[test]
+fn an_error_body_has_no_filters() {
+    let response = json!({ "code": -1121, "msg": "Invalid symbol." });
+    assert!(is_invalid_symbol_error(&response));
+    assert!(parse_exchange_info(&response).is_empty());
+    assert!(!is_invalid_symbol_error(&fixture()));
+}
+
+#[test]
+fn rejected_symbols_stop_being_tracked() {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.set_symbols(&["btcusdt".to_string(), "nopeusdt".to_string()]);
+    app.current_symbol = "NOPEUSDT".to_string();
+
+    app.reject_symbols(&["NOPEUSDT".to_string()]);
+    assert!(!app.order_books.contains_key("NOPEUSDT"));
+    assert_eq!(app.rejected_symbols, ["NOPEUSDT"]);
+    assert_eq!(app.current_symbol, "BTCUSDT");
+
+    // Rejecting again doesn't list it twice
+    app.reject_symbols(&["nopeusdt".to_string()]);
+    assert_eq!(app.rejected_symbols, ["NOPEUSDT"]);
+}
@@ -0,0 +1,140 @@
+use binance_ws::clock::MockClock;
+use binance_ws::db::{AnalysisStore, Database, MarketAnalysisRecord};
+use binance_ws::file_store::{utc_date, CsvSink, CsvStore, SignalDump};
+use binance_ws::App;
+use serde_json::json;
+use std::fs;
+use std::sync::Arc;
+
+fn record(timestamp: u64, human_orders: i64) -> MarketAnalysisRecord {
+    MarketAnalysisRecord {
+        symbol: "BTCUSDT".to_string(),
+        timestamp,
+        total_orders: 10,
+        human_orders,
+        bot_orders: 10 - human_orders,
+        human_ratio: human_orders as f64 / 10.0,
+    }
+}
+
+#[test]
+fn formats_utc_dates() {
+    assert_eq!(utc_date(0), "1970-01-01");
+    assert_eq!(utc_date(1_704_067_199), "2023-12-31");
+    assert_eq!(utc_date(1_704_067_200), "2024-01-01");
+    assert_eq!(utc_date(1_709_164_800), "2024-02-29");
+}
+
+#[test]
+fn partitions_records_by_day_and_reads_them_back() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let store = CsvStore::open(root).unwrap();
+
+    // Either side of midnight on 2024-01-01
+    for (ts, human) in [(1_704_067_100, 2), (1_704_067_150, 3), (1_704_067_300, 4)] {
+        store.insert_analysis(&record(ts, human)).unwrap();
+    }
+    assert!(root.join("BTCUSDT/2023-12-31.csv").exists());
+    let day = fs::read_to_string(root.join("BTCUSDT/2024-01-01.csv")).unwrap();
+    assert_eq!(day.lines().count(), 2); // header and one row
+
+    let between = store
+        .get_analysis_between("BTCUSDT", 1_704_067_120, 1_704_067_400)
+        .unwrap();
+    let timestamps: Vec<u64> = between.iter().map(|r| r.timestamp).collect();
+    assert_eq!(timestamps, [1_704_067_150, 1_704_067_300]);
+
+    let history = store.get_analysis_history("BTCUSDT", 2).unwrap();
+    assert_eq!(history[0].human_orders, 4);
+    assert_eq!(history[1].human_orders, 3);
+    assert_eq!(
+        store
+            .get_latest_analysis("BTCUSDT")
+            .unwrap()
+            .unwrap()
+            .timestamp,
+        1_704_067_300
+    );
+    assert!(store.get_latest_analysis("ETHUSDT").unwrap().is_none());
+}
+
+#[test]
+fn csv_store_counts_and_filters_by_ratio() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = CsvStore::open(dir.path()).unwrap();
+    for (ts, human) in [(1_704_067_100, 2), (1_704_067_300, 6), (1_704_067_400, 7)] {
+        store.insert_analysis(&record(ts, human)).unwrap();
+    }
+    assert_eq!(store.count_records(Some("BTCUSDT")).unwrap(), 3);
+    assert_eq!(store.count_records(Some("ETHUSDT")).unwrap(), 0);
+    assert_eq!(store.count_records(None).unwrap(), 3);
+
+    let found = store
+        .find_by_ratio("BTCUSDT", 0.5, 1.0, 0, 1_704_067_400, 10)
+        .unwrap();
+    let timestamps: Vec<u64> = found.iter().map(|r| r.timestamp).collect();
+    assert_eq!(timestamps, [1_704_067_400, 1_704_067_300]);
+    let earlier = store
+        .find_by_ratio("BTCUSDT", 0.5, 1.0, 0, 1_704_067_399, 10)
+        .unwrap();
+    assert_eq!(earlier.len(), 1);
+}
+
+#[test]
+fn sink_appends_rows_and_recreates_a_rotated_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let sink = CsvSink::open(dir.path()).unwrap();
+    sink.append(&record(100, 4), Some(0.5), Some(0.25)).unwrap();
+    sink.append(&record(105, 6), None, None).unwrap();
+
+    let path = sink.file_for("BTCUSDT");
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "symbol,timestamp,total_orders,human_orders,bot_orders,human_ratio,spread,imbalance\n\
+         BTCUSDT,100,10,4,6,0.4,0.5,0.25\n\
+         BTCUSDT,105,10,6,4,0.6,,\n"
+    );
+
+    // Moved away by a log rotator: the next row starts a fresh file with its header
+    fs::rename(&path, dir.path().join("BTCUSDT.csv.1")).unwrap();
+    sink.append(&record(110, 5), None, Some(0.0)).unwrap();
+    let lines: Vec<String> = fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1], "BTCUSDT,110,10,5,5,0.5,,0");
+}
+
+#[test]
+fn signal_dump_writes_one_row_per_scored_level() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("signals.csv");
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.set_clock(Arc::new(MockClock::new(1_700_000_000_000)));
+    app.signal_dump = Some(SignalDump::open(&path).unwrap());
+    app.update_orders(&json!({
+        "symbol": "BTCUSDT",
+        "bids": [["50000.00", "1.0"]],
+        "asks": [["50001.37", "0.12345"], ["50002.00", "2.0"]]
+    }));
+    app.analyze_symbol("BTCUSDT");
+
+    let contents = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(
+        lines[0],
+        "timestamp,symbol,side,price,round_price,human_size,human_spacing,\
+         step_alignment,round_notional,score,classified"
+    );
+    assert_eq!(lines.len(), 4);
+    // A lone bid has no spacing, and no step size or notional units were configured
+    assert!(lines[1].starts_with("1700000000000,BTCUSDT,bid,50000.00,1,"));
+    assert!(lines[1].contains(",,"));
+    assert!(lines[2..].iter().all(|l| l.contains(",ask,")));
+    assert!(lines[1..]
+        .iter()
+        .all(|l| l.ends_with(",human") || l.ends_with(",bot")));
+}
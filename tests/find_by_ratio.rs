@@ -0,0 +1,46 @@
+use binance_ws::db::{Database, MarketAnalysisRecord};
+
+#[test]
+fn finds_windows_inside_the_ratio_range_newest_first() {
+    let db = Database::open_in_memory().unwrap();
+    for (timestamp, human) in [(100, 2.0), (105, 6.0), (110, 5.0), (115, 9.0), (120, 7.0)] {
+        db.insert_analysis(&MarketAnalysisRecord::from_average(
+            "BTCUSDT", timestamp, 10.0, human,
+        ))
+        .unwrap();
+    }
+    db.insert_analysis(&MarketAnalysisRecord::from_average(
+        "ETHUSDT", 105, 10.0, 6.0,
+    ))
+    .unwrap();
+
+    let found = |min, max, limit| -> Vec<u64> {
+        db.find_by_ratio("BTCUSDT", min, max, 0, u32::MAX as u64, limit)
+            .unwrap()
+            .iter()
+            .map(|r| r.timestamp)
+            .collect()
+    };
+    // Both bounds are inclusive
+    assert_eq!(found(0.5, 0.7, 10), [120, 110, 105]);
+    assert_eq!(found(0.5, 0.7, 2), [120, 110]);
+    assert!(found(0.95, 1.0, 10).is_empty());
+}
+
+#[test]
+fn the_time_range_is_inclusive() {
+    let db = Database::open_in_memory().unwrap();
+    for timestamp in [100, 105, 110, 115] {
+        db.insert_analysis(&MarketAnalysisRecord::from_average(
+            "BTCUSDT", timestamp, 10.0, 6.0,
+        ))
+        .unwrap();
+    }
+    let found: Vec<u64> = db
+        .find_by_ratio("BTCUSDT", 0.0, 1.0, 105, 110, 10)
+        .unwrap()
+        .iter()
+        .map(|r| r.timestamp)
+        .collect();
+    assert_eq!(found, [110, 105]);
+}
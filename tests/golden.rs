@@ -0,0 +1,59 @@
+use binance_ws::{db::Database, App, OrderBook};
+use std::collections::BTreeMap;
+use std::fs;
+
+const BOOK_FIXTURE: &str = "tests/fixtures/btcusdt_book.json";
+const EXPECTED_SCORES: &str = "tests/fixtures/btcusdt_expected_scores.json";
+
+fn app_with_fixture(path: &str) -> App {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    let order_book = OrderBook::from_json_file(path).unwrap();
+    app.order_books.insert("BTCUSDT".to_string(), order_book);
+    app.current_symbol = "BTCUSDT".to_string();
+    app
+}
+
+// Set BLESS=1 to rewrite the expected scores after an intended heuristic change
+#[test]
+fn confidence_scores_match_golden_file() {
+    let mut app = app_with_fixture(BOOK_FIXTURE);
+    let scores: BTreeMap<String, f64> =
+        app.analyze_market().confidence_scores.into_iter().collect();
+
+    if std::env::var_os("BLESS").is_some() {
+        fs::write(
+            EXPECTED_SCORES,
+            serde_json::to_string_pretty(&scores).unwrap() + "\n",
+        )
+        .unwrap();
+    }
+
+    let expected: BTreeMap<String, f64> =
+        serde_json::from_str(&fs::read_to_string(EXPECTED_SCORES).unwrap()).unwrap();
+    assert_eq!(scores, expected);
+}
+
+#[test]
+fn pattern_lists_are_stable_across_runs() {
+    let first = app_with_fixture(BOOK_FIXTURE).analyze_market();
+    let second = app_with_fixture(BOOK_FIXTURE).analyze_market();
+
+    assert_eq!(first.human_patterns, second.human_patterns);
+    assert_eq!(first.bot_patterns, second.bot_patterns);
+}
+
+#[test]
+fn patterns_are_ordered_by_price_descending() {
+    let analysis = app_with_fixture(BOOK_FIXTURE).analyze_market();
+    let prices = |patterns: &[String]| -> Vec<f64> {
+        patterns
+            .iter()
+            .map(|p| p.split_whitespace().nth(2).unwrap().parse().unwrap())
+            .collect()
+    };
+
+    for patterns in [&analysis.human_patterns, &analysis.bot_patterns] {
+        let prices = prices(patterns);
+        assert!(prices.windows(2).all(|w| w[0] >= w[1]), "{:?}", prices);
+    }
+}
@@ -0,0 +1,50 @@
+use binance_ws::{OrderBook, OrderSide};
+use serde_json::json;
+
+fn book() -> OrderBook {
+    OrderBook::from_json(&json!({
+        "bids": [["100.00", "1.0"], ["99.99", "2.0"], ["99.00", "0.5"], ["98.50", "4.0"]],
+        "asks": [["100.01", "1.0"], ["100.99", "2.0"], ["101.00", "0.5"], ["101.01", "3.0"]]
+    }))
+}
+
+#[test]
+fn bids_floor_and_asks_ceil_to_their_bucket() {
+    let book = book();
+    // 100.00 sits on a boundary and is its own bucket; 99.99 floors to 99
+    assert_eq!(
+        book.grouped(OrderSide::Bid, 1.0),
+        [(100.0, 1.0), (99.0, 2.5), (98.0, 4.0)]
+    );
+    // 101.00 is on a boundary and shares it with 100.99 below; 101.01 ceils to 102
+    assert_eq!(
+        book.grouped(OrderSide::Ask, 1.0),
+        [(101.0, 1.0 + 2.0 + 0.5), (102.0, 3.0)]
+    );
+}
+
+#[test]
+fn boundaries_hold_for_fractional_bucket_sizes() {
+    let book = OrderBook::from_json(&json!({
+        "bids": [["0.3", "1.0"], ["0.29", "2.0"]],
+        "asks": [["0.7", "1.0"], ["0.71", "2.0"]]
+    }));
+    let bids: Vec<(String, f64)> = book
+        .grouped(OrderSide::Bid, 0.1)
+        .into_iter()
+        .map(|(price, quantity)| (format!("{:.1}", price), quantity))
+        .collect();
+    assert_eq!(bids, [("0.3".to_string(), 1.0), ("0.2".to_string(), 2.0)]);
+    let asks: Vec<(String, f64)> = book
+        .grouped(OrderSide::Ask, 0.1)
+        .into_iter()
+        .map(|(price, quantity)| (format!("{:.1}", price), quantity))
+        .collect();
+    assert_eq!(asks, [("0.7".to_string(), 1.0), ("0.8".to_string(), 2.0)]);
+}
+
+#[test]
+fn a_non_positive_bucket_groups_nothing() {
+    assert!(book().grouped(OrderSide::Bid, 0.0).is_empty());
+    assert!(book().grouped(OrderSide::Ask, -1.0).is_empty());
+}
@@ -0,0 +1,35 @@
+use binance_ws::{db::Database, App};
+use serde_json::json;
+
+fn app() -> App {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.set_symbols(&["btcusdt".to_string()]);
+    app.display_levels = 2;
+    app.update_orders(&json!({
+        "symbol": "BTCUSDT",
+        "bids": [["100.00", "1.0"], ["99.50", "2.5"], ["99.00", "3.0"]],
+        "asks": [["101.00", "1.2"], ["101.50", "5.0"], ["102.00", "0.7"]]
+    }));
+    app
+}
+
+#[test]
+fn heat_row_is_empty_of_scores_until_analyzed() {
+    let app = app();
+    assert_eq!(app.heat_row("BTCUSDT"), [None; 4]);
+    assert!(app.heat_row("NOPEUSDT").is_empty());
+}
+
+#[test]
+fn heat_row_follows_the_book_panel_order() {
+    let mut app = app();
+    let scores = app.analyze_all_symbols()["BTCUSDT"]
+        .confidence_scores
+        .clone();
+    let expected: Vec<Option<f64>> = ["101.50", "101.00", "100.00", "99.50"]
+        .iter()
+        .map(|price| scores.get(*price).copied())
+        .collect();
+    assert_eq!(app.heat_row("BTCUSDT"), expected);
+    assert!(expected.iter().any(Option::is_some));
+}
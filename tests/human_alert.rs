@@ -0,0 +1,28 @@
+use binance_ws::{HumanAlert, ALERT_FLASH_FOR};
+use std::time::{Duration, Instant};
+
+fn alert() -> HumanAlert {
+    let mut alert = HumanAlert::default();
+    alert.enabled = true;
+    alert.threshold = 10;
+    alert.cooldown = Duration::from_secs(30);
+    alert
+}
+
+#[test]
+fn is_off_by_default() {
+    let mut alert = HumanAlert::default();
+    assert!(!alert.check(1_000, Instant::now()));
+}
+
+#[test]
+fn fires_at_the_threshold_and_debounces() {
+    let mut alert = alert();
+    let start = Instant::now();
+    assert!(!alert.check(9, start));
+    assert!(alert.check(10, start));
+    assert!(alert.is_flashing(start));
+    assert!(!alert.is_flashing(start + ALERT_FLASH_FOR));
+    assert!(!alert.check(50, start + Duration::from_secs(29)));
+    assert!(alert.check(50, start + Duration::from_secs(30)));
+}
@@ -0,0 +1,60 @@
+use binance_ws::{DuplicatePolicy, OrderBook, ICEBERG_INDICATOR};
+use serde_json::{json, Value};
+use std::time::Instant;
+
+fn snapshot() -> Value {
+    json!({
+        "bids": [["100.00", "5.0"], ["99.00", "1.0"]],
+        "asks": [["101.00", "1.0"]]
+    })
+}
+
+// Diff event setting the 100.00 bid to `quantity`
+fn bid(quantity: &str) -> Value {
+    json!({ "bids": [["100.00", quantity]], "asks": [], "eventTime": 1 })
+}
+
+fn replay(book: &mut OrderBook, updates: &[Value]) {
+    for update in updates {
+        book.apply(update, 0.0, DuplicatePolicy::default(), Instant::now());
+    }
+}
+
+#[test]
+fn a_level_refilled_to_the_same_size_twice_is_an_iceberg() {
+    let mut book = OrderBook::from_json(&snapshot());
+    replay(&mut book, &[bid("2.0"), bid("5.0"), bid("3.5")]);
+    assert!(book.iceberg_candidates().is_empty());
+
+    replay(&mut book, &[bid("5.0")]);
+    assert_eq!(book.iceberg_candidates(), ["100.00"]);
+    assert!(book.bids[0]
+        .human_indicators
+        .iter()
+        .any(|i| i == ICEBERG_INDICATOR));
+}
+
+#[test]
+fn levels_a_diff_leaves_out_are_not_depleted() {
+    let mut book = OrderBook::from_json(&snapshot());
+    let other = json!({ "bids": [["99.00", "2.0"]], "asks": [], "eventTime": 1 });
+    for _ in 0..3 {
+        replay(&mut book, &[other.clone(), bid("5.0")]);
+    }
+    assert!(book.iceberg_candidates().is_empty());
+}
+
+#[test]
+fn a_removed_and_reposted_level_is_not_a_refill() {
+    let mut book = OrderBook::from_json(&snapshot());
+    replay(&mut book, &[bid("0"), bid("5.0"), bid("0"), bid("5.0")]);
+    assert!(book.iceberg_candidates().is_empty());
+}
+
+#[test]
+fn a_snapshot_starts_tracking_over() {
+    let mut book = OrderBook::from_json(&snapshot());
+    replay(&mut book, &[bid("2.0"), bid("5.0"), bid("2.0")]);
+    replay(&mut book, &[snapshot(), bid("5.0")]);
+    assert!(book.iceberg_candidates().is_empty());
+}
@@ -0,0 +1,106 @@
+use binance_ws::recording::{Recorder, Replayer};
+use binance_ws::{DuplicatePolicy, OrderBook};
+use serde_json::json;
+use std::time::Instant;
+
+fn levels(orders: &[binance_ws::OrderBookEntry]) -> Vec<(&str, &str)> {
+    orders
+        .iter()
+        .map(|l| (l.price.as_str(), l.quantity.as_str()))
+        .collect()
+}
+
+#[test]
+fn diff_events_update_only_the_levels_they_name() {
+    let mut book = OrderBook::from_json(&json!({
+        "bids": [["100.00", "1.0"], ["99.00", "2.0"], ["98.00", "3.0"]],
+        "asks": [["101.00", "1.0"], ["102.00", "2.0"]]
+    }));
+    book.apply(
+        &json!({
+            "bids": [["99.00", "0.5"], ["98.00", "0"], ["97.00", "4.0"]],
+            "asks": [],
+            "eventTime": 1
+        }),
+        0.0,
+        DuplicatePolicy::default(),
+        Instant::now(),
+    );
+    assert_eq!(
+        levels(&book.bids),
+        [("100.00", "1.0"), ("99.00", "0.5"), ("97.00", "4.0")]
+    );
+    assert_eq!(book.asks.len(), 2);
+}
+
+#[test]
+fn levels_beyond_the_depth_fill_in_when_the_top_is_removed() {
+    let mut book = OrderBook::new();
+    book.depth = 2;
+    book.apply(
+        &json!({
+            "bids": [["100.00", "1.0"], ["99.00", "2.0"], ["98.00", "3.0"], ["97.00", "4.0"]],
+            "asks": [["101.00", "1.0"]]
+        }),
+        0.0,
+        DuplicatePolicy::default(),
+        Instant::now(),
+    );
+    assert_eq!(levels(&book.bids), [("100.00", "1.0"), ("99.00", "2.0")]);
+
+    book.apply(
+        &json!({ "bids": [["100.00", "0"]], "asks": [], "eventTime": 1 }),
+        0.0,
+        DuplicatePolicy::default(),
+        Instant::now(),
+    );
+    assert_eq!(levels(&book.bids), [("99.00", "2.0"), ("98.00", "3.0")]);
+}
+
+#[test]
+fn a_message_without_an_event_time_replaces_the_book() {
+    let mut book = OrderBook::from_json(&json!({
+        "bids": [["100.00", "1.0"], ["99.00", "2.0"]],
+        "asks": [["101.00", "1.0"]]
+    }));
+    book.apply(
+        &json!({ "bids": [["98.00", "3.0"]], "asks": [["103.00", "1.0"]] }),
+        0.0,
+        DuplicatePolicy::default(),
+        Instant::now(),
+    );
+    assert_eq!(levels(&book.bids), [("98.00", "3.0")]);
+    assert_eq!(levels(&book.asks), [("103.00", "1.0")]);
+}
+
+#[test]
+fn snapshot_markers_hold_the_book_built_from_diffs() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.jsonl");
+    let mut recorder = Recorder::create(&path, 1000).unwrap();
+    let diff = |bids: serde_json::Value, ts: u64| json!({ "symbol": "BTCUSDT", "bids": bids, "asks": [], "eventTime": ts, "receivedAt": ts });
+    recorder
+        .record(&json!({
+            "symbol": "BTCUSDT",
+            "bids": [["100.0", "1.0"], ["99.0", "2.0"]],
+            "asks": [],
+            "receivedAt": 0
+        }))
+        .unwrap();
+    recorder
+        .record(&diff(json!([["98.0", "3.0"]]), 500))
+        .unwrap();
+    recorder
+        .record(&diff(json!([["100.0", "0"]]), 1000))
+        .unwrap();
+    recorder.flush().unwrap();
+
+    let mut replayer = Replayer::open(&path).unwrap();
+    let messages = replayer.seek(1000).unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(
+        messages[0]["bids"],
+        json!([["99.0", "2.0"], ["98.0", "3.0"]])
+    );
+    assert!(messages[0].get("eventTime").is_none());
+}
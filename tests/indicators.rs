@@ -0,0 +1,29 @@
+use binance_ws::OrderBookEntry;
+
+fn entry() -> OrderBookEntry {
+    OrderBookEntry {
+        price: "50000.00".to_string(),
+        quantity: "1.0".to_string(),
+        is_likely_human: false,
+        human_indicators: Vec::new(),
+    }
+}
+
+#[test]
+fn repeated_indicators_are_stored_once() {
+    let mut level = entry();
+    for _ in 0..1000 {
+        level.add_indicator("iceberg?", 8);
+    }
+    assert_eq!(level.human_indicators, ["iceberg?"]);
+}
+
+#[test]
+fn keeps_only_the_most_recent_distinct_indicators() {
+    let mut level = entry();
+    for indicator in ["a", "b", "c", "a", "d"] {
+        level.add_indicator(indicator, 3);
+    }
+    // "a" was refreshed after "b" and "c", so "b" is the oldest and goes first
+    assert_eq!(level.human_indicators, ["c", "a", "d"]);
+}
@@ -0,0 +1,98 @@
+use binance_ws::{db::Database, App, FillInference, OrderBook, OrderSide, TradeSide};
+use serde_json::{json, Value};
+
+fn book() -> OrderBook {
+    OrderBook::from_json(&json!({
+        "bids": [["100.00", "5"], ["99.90", "5"], ["95.00", "5"]],
+        "asks": [["100.10", "5"], ["100.20", "5"], ["105.00", "5"]]
+    }))
+}
+
+fn update(bids: Value, asks: Value) -> Value {
+    json!({
+        "symbol": "BTCUSDT",
+        "bids": bids,
+        "asks": asks,
+        "eventTime": 1,
+        "receivedAt": 1
+    })
+}
+
+const WINDOW: FillInference = FillInference {
+    max_distance_bps: 20.0,
+};
+
+#[test]
+fn drop_at_best_bid_is_a_sell() {
+    let fills = book().inferred_fills(&update(json!([["100.00", "3"]]), json!([])), &WINDOW);
+    assert_eq!(fills, vec![(100.0, 2.0, TradeSide::Sell)]);
+}
+
+#[test]
+fn drop_at_best_ask_is_a_buy() {
+    let fills = book().inferred_fills(&update(json!([]), json!([["100.10", "0"]])), &WINDOW);
+    assert_eq!(fills, vec![(100.1, 5.0, TradeSide::Buy)]);
+}
+
+#[test]
+fn drop_deep_in_the_book_is_a_cancel() {
+    // 95.00 is 500 bps below the best bid, 105.00 about 490 bps above the best ask
+    let fills = book().inferred_fills(
+        &update(json!([["95.00", "1"]]), json!([["105.00", "1"]])),
+        &WINDOW,
+    );
+    assert!(fills.is_empty());
+}
+
+#[test]
+fn window_controls_fill_versus_cancel() {
+    let second_level = update(json!([["99.90", "4"]]), json!([]));
+    let tight = FillInference {
+        max_distance_bps: 0.0,
+    };
+    assert!(book().inferred_fills(&second_level, &tight).is_empty());
+    assert_eq!(book().inferred_fills(&second_level, &WINDOW).len(), 1);
+}
+
+#[test]
+fn growth_and_new_levels_are_not_fills() {
+    let fills = book().inferred_fills(
+        &update(json!([["100.00", "8"], ["99.95", "1"]]), json!([])),
+        &WINDOW,
+    );
+    assert!(fills.is_empty());
+}
+
+#[test]
+fn is_fill_measures_distance_away_from_the_spread() {
+    let inference = FillInference {
+        max_distance_bps: 10.0,
+    };
+    assert!(inference.is_fill(OrderSide::Bid, 100.0, 99.95));
+    assert!(!inference.is_fill(OrderSide::Bid, 100.0, 99.8));
+    assert!(inference.is_fill(OrderSide::Ask, 100.0, 100.05));
+    assert!(!inference.is_fill(OrderSide::Ask, 100.0, 100.2));
+}
+
+#[test]
+fn app_tape_records_fill_but_not_cancel_in_a_sequence() {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.fill_inference = WINDOW;
+    app.update_orders(&update(
+        json!([["100.00", "5"], ["95.00", "5"]]),
+        json!([["100.10", "5"]]),
+    ));
+    // Best bid partially filled while a deep bid is pulled
+    app.update_orders(&update(
+        json!([["100.00", "2"], ["95.00", "0"]]),
+        json!([["100.10", "5"]]),
+    ));
+
+    let tape: Vec<_> = app
+        .tape
+        .iter()
+        .map(|t| (t.price, t.quantity, t.side))
+        .collect();
+    assert_eq!(tape, vec![(100.0, 3.0, TradeSide::Sell)]);
+    assert!(app.tape.iter().all(|t| t.inferred));
+}
@@ -0,0 +1,91 @@
+use binance_ws::clock::MockClock;
+use binance_ws::db::Database;
+use binance_ws::{App, Liquidation, TradeSide, LIQUIDATION_INDICATOR, LIQUIDATION_MARK_FOR};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn force_order(symbol: &str, side: &str, price: &str) -> serde_json::Value {
+    json!({
+        "e": "forceOrder",
+        "E": 1_700_000_000_000i64,
+        "o": {
+            "s": symbol, "S": side, "o": "LIMIT", "f": "IOC",
+            "q": "0.5", "p": "49900.00", "ap": price, "X": "FILLED",
+            "l": "0.5", "z": "0.5", "T": 1_700_000_000_000i64
+        }
+    })
+}
+
+fn app_with_book(clock: &MockClock) -> App {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.set_clock(Arc::new(clock.clone()));
+    app.update_orders(&json!({
+        "symbol": "BTCUSDT",
+        "bids": [["50000.00", "1.0"], ["49990.00", "2.0"], ["49980.00", "3.0"]],
+        "asks": [["50010.00", "1.0"], ["50020.00", "2.0"]]
+    }));
+    app
+}
+
+fn marked(levels: &[binance_ws::OrderBookEntry]) -> Vec<&str> {
+    levels
+        .iter()
+        .filter(|l| {
+            l.human_indicators
+                .iter()
+                .any(|i| i == LIQUIDATION_INDICATOR)
+        })
+        .map(|l| l.price.as_str())
+        .collect()
+}
+
+#[test]
+fn parses_force_order_events() {
+    let liquidation =
+        Liquidation::from_force_order(&force_order("btcusdt", "SELL", "49985.5")).unwrap();
+    assert_eq!(liquidation.symbol, "BTCUSDT");
+    assert_eq!(liquidation.side, TradeSide::Sell);
+    assert_eq!(liquidation.price, 49985.5);
+    assert_eq!(liquidation.quantity, 0.5);
+    assert_eq!(liquidation.trade_time, Some(1_700_000_000_000));
+
+    // Unfilled orders report an average price of zero and fall back to the order price
+    let unfilled = Liquidation::from_force_order(&force_order("BTCUSDT", "BUY", "0")).unwrap();
+    assert_eq!(unfilled.price, 49900.0);
+    assert!(Liquidation::from_force_order(&json!({ "e": "forceOrder" })).is_none());
+}
+
+#[test]
+fn marks_the_nearest_level_on_the_side_hit() {
+    let clock = MockClock::new(1_700_000_000_000);
+    let mut app = app_with_book(&clock);
+    app.receive_update(force_order("BTCUSDT", "SELL", "49988.00"));
+    app.receive_update(force_order("ETHUSDT", "BUY", "3000.00"));
+    app.receive_update(force_order("DOGEUSDT", "BUY", "0.1"));
+
+    // Untracked symbols are dropped
+    assert_eq!(app.liquidations.len(), 2);
+    let book = &app.order_books["BTCUSDT"];
+    assert_eq!(marked(&book.bids), ["49990.00"]);
+    assert!(marked(&book.asks).is_empty());
+}
+
+#[test]
+fn marks_survive_book_updates_until_they_expire() {
+    let clock = MockClock::new(1_700_000_000_000);
+    let mut app = app_with_book(&clock);
+    app.receive_update(force_order("BTCUSDT", "BUY", "50019.00"));
+    let update = json!({
+        "symbol": "BTCUSDT",
+        "bids": [["50000.00", "1.0"]],
+        "asks": [["50010.00", "1.5"], ["50020.00", "2.0"]]
+    });
+
+    app.update_orders(&update);
+    assert_eq!(marked(&app.order_books["BTCUSDT"].asks), ["50020.00"]);
+
+    clock.advance(LIQUIDATION_MARK_FOR + Duration::from_secs(1));
+    app.update_orders(&update);
+    assert!(marked(&app.order_books["BTCUSDT"].asks).is_empty());
+}
@@ -0,0 +1,87 @@
+use binance_ws::clock::MockClock;
+use binance_ws::db::Database;
+use binance_ws::App;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn app() -> App {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.set_symbols(&[
+        "btcusdt".to_string(),
+        "ethusdt".to_string(),
+        "xrpusdt".to_string(),
+    ]);
+    app
+}
+
+#[test]
+fn weights_human_ratios_by_book_notional() {
+    let mut app = app();
+    // Round BTCUSDT levels read as human, ragged ETHUSDT ones as bot
+    app.update_orders(&json!({
+        "symbol": "BTCUSDT",
+        "bids": [["1000.00", "2.0"]],
+        "asks": [["1000.00", "2.0"]]
+    }));
+    app.update_orders(&json!({
+        "symbol": "ETHUSDT",
+        "bids": [["123.37", "13.13742"]],
+        "asks": [["123.37", "13.13742"]]
+    }));
+    app.analyze_all_symbols();
+
+    let ratio = |symbol: &str| {
+        let analysis = app.cached_analysis(symbol).unwrap();
+        analysis.likely_human_orders as f64 / analysis.total_orders as f64
+    };
+    assert!(ratio("BTCUSDT") > ratio("ETHUSDT"));
+    let eth_notional = 2.0 * 123.37 * 13.13742;
+    let expected =
+        (ratio("BTCUSDT") * 4000.0 + ratio("ETHUSDT") * eth_notional) / (4000.0 + eth_notional);
+
+    // XRPUSDT has no book yet and must not drag the index towards zero
+    let symbols = ["BTCUSDT", "ETHUSDT", "XRPUSDT"].map(String::from);
+    let index = app.market_index(&symbols).unwrap();
+    assert!(
+        (index - expected).abs() < 1e-12,
+        "{} vs {}",
+        index,
+        expected
+    );
+}
+
+#[test]
+fn no_data_means_no_index() {
+    let app = app();
+    assert_eq!(app.market_index(&["XRPUSDT".to_string()]), None);
+}
+
+#[test]
+fn stale_books_are_left_out() {
+    let clock = MockClock::new(1_700_000_000_000);
+    let mut app = app();
+    app.set_clock(Arc::new(clock.clone()));
+    app.update_orders(&json!({
+        "symbol": "BTCUSDT",
+        "bids": [["1000.00", "2.0"]],
+        "asks": [["1000.00", "2.0"]]
+    }));
+    clock.advance(app.stale_after + Duration::from_secs(1));
+    app.update_orders(&json!({
+        "symbol": "ETHUSDT",
+        "bids": [["123.37", "13.13742"]],
+        "asks": [["123.37", "13.13742"]]
+    }));
+    app.analyze_all_symbols();
+
+    assert!(app.is_stale("BTCUSDT"));
+    assert!(!app.is_stale("ETHUSDT"));
+    // Never updated is waiting, not stale
+    assert!(!app.is_stale("XRPUSDT"));
+    let eth = app.cached_analysis("ETHUSDT").unwrap();
+    let eth_ratio = eth.likely_human_orders as f64 / eth.total_orders as f64;
+    let symbols = ["BTCUSDT", "ETHUSDT"].map(String::from);
+    assert_eq!(app.market_index(&symbols), Some(eth_ratio));
+    assert_eq!(app.active_feeds(), 1);
+}
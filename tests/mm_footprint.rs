@@ -0,0 +1,54 @@
+use binance_ws::db::Database;
+use binance_ws::{App, LevelSignals, OrderBook, MM_FOOTPRINT_THRESHOLD};
+use serde_json::json;
+
+#[test]
+fn symmetric_evenly_spaced_quotes_score_high() {
+    let book = OrderBook::from_json(&json!({
+        "bids": [["100.00", "1.0"], ["99.99", "2.0"], ["99.98", "3.0"]],
+        "asks": [["100.01", "1.0"], ["100.02", "2.0"], ["100.03", "3.0"]]
+    }));
+    assert!(book.mm_footprint().unwrap() >= MM_FOOTPRINT_THRESHOLD);
+}
+
+#[test]
+fn lopsided_ragged_book_scores_low() {
+    let book = OrderBook::from_json(&json!({
+        "bids": [["100.00", "0.1"], ["99.50", "7.0"], ["97.13", "0.3"]],
+        "asks": [["101.00", "5.0"], ["101.07", "0.2"], ["104.00", "9.0"]]
+    }));
+    assert!(book.mm_footprint().unwrap() < 0.5);
+}
+
+#[test]
+fn needs_two_levels_per_side() {
+    let book = OrderBook::from_json(&json!({
+        "bids": [["100.00", "1.0"]],
+        "asks": [["100.01", "1.0"], ["100.02", "1.0"]]
+    }));
+    assert_eq!(book.mm_footprint(), None);
+}
+
+#[test]
+fn the_footprint_does_not_move_the_human_count() {
+    let quotes = json!({
+        "symbol": "BTCUSDT",
+        "bids": [["100.00", "1.0"], ["99.99", "2.0"], ["99.98", "3.0"]],
+        "asks": [["100.01", "1.0"], ["100.02", "2.0"], ["100.03", "3.0"]]
+    });
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.update_orders(&quotes);
+    let analysis = app.analyze_symbol("BTCUSDT");
+    assert!(analysis.mm_footprint >= MM_FOOTPRINT_THRESHOLD);
+
+    let book = &app.order_books["BTCUSDT"];
+    let scored = [&book.bids, &book.asks]
+        .into_iter()
+        .flat_map(|side| (0..side.len()).map(move |i| (side, i)))
+        .filter(|&(side, i)| {
+            LevelSignals::compute(side, i, None, None)
+                .is_some_and(|s| s.human_score() > app.human_threshold)
+        })
+        .count();
+    assert_eq!(analysis.likely_human_orders, scored);
+}
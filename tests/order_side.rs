@@ -0,0 +1,25 @@
+use binance_ws::OrderSide;
+
+#[test]
+fn serializes_as_upper_case_strings() {
+    assert_eq!(serde_json::to_string(&OrderSide::Bid).unwrap(), "\"BID\"");
+    assert_eq!(serde_json::to_string(&OrderSide::Ask).unwrap(), "\"ASK\"");
+}
+
+#[test]
+fn display_matches_serialized_form() {
+    assert_eq!(OrderSide::Bid.to_string(), "BID");
+    assert_eq!(OrderSide::Ask.to_string(), "ASK");
+}
+
+#[test]
+fn deserializes_current_and_legacy_spellings() {
+    for (input, side) in [
+        ("\"BID\"", OrderSide::Bid),
+        ("\"Bid\"", OrderSide::Bid),
+        ("\"ASK\"", OrderSide::Ask),
+        ("\"Ask\"", OrderSide::Ask),
+    ] {
+        assert_eq!(serde_json::from_str::<OrderSide>(input).unwrap(), side);
+    }
+}
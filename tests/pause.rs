@@ -0,0 +1,84 @@
+use binance_ws::db::Database;
+use binance_ws::{App, MAX_PENDING_UPDATES};
+use serde_json::json;
+
+fn levels(app: &App) -> Vec<(&str, &str)> {
+    app.order_books["BTCUSDT"]
+        .bids
+        .iter()
+        .map(|l| (l.price.as_str(), l.quantity.as_str()))
+        .collect()
+}
+
+fn snapshot(id: u64) -> serde_json::Value {
+    json!({
+        "symbol": "BTCUSDT",
+        "bids": [["100.00", "1.0"], ["99.00", "2.0"], ["98.00", "3.0"]],
+        "asks": [["101.00", "1.0"]],
+        "lastUpdateId": id
+    })
+}
+
+fn diff(id: u64, bids: serde_json::Value) -> serde_json::Value {
+    json!({
+        "symbol": "BTCUSDT",
+        "bids": bids,
+        "asks": [],
+        "lastUpdateId": id,
+        "eventTime": 1_700_000_000_000u64 + id
+    })
+}
+
+#[test]
+fn every_diff_received_while_paused_is_applied_on_resume() {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.receive_update(snapshot(1));
+
+    app.toggle_pause();
+    app.receive_update(diff(2, json!([["99.00", "0"]])));
+    app.receive_update(diff(3, json!([["97.00", "4.0"]])));
+    app.receive_update(diff(4, json!([["100.00", "1.5"]])));
+    assert_eq!(
+        levels(&app),
+        [("100.00", "1.0"), ("99.00", "2.0"), ("98.00", "3.0")]
+    );
+
+    app.toggle_pause();
+    assert_eq!(
+        levels(&app),
+        [("100.00", "1.5"), ("98.00", "3.0"), ("97.00", "4.0")]
+    );
+    assert_eq!(app.last_update_id("BTCUSDT"), Some(4));
+}
+
+#[test]
+fn an_overflowing_backlog_resyncs_from_a_snapshot() {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.receive_update(snapshot(1));
+
+    app.toggle_pause();
+    for id in 2..MAX_PENDING_UPDATES as u64 + 3 {
+        app.receive_update(diff(id, json!([["99.00", "0"]])));
+    }
+    assert!(app.resync_requests.is_empty());
+
+    app.toggle_pause();
+    assert_eq!(app.resync_requests, ["BTCUSDT"]);
+    // Diffs keep waiting until the snapshot arrives
+    let next = MAX_PENDING_UPDATES as u64 + 3;
+    app.receive_update(diff(next, json!([["97.00", "4.0"]])));
+    assert_eq!(levels(&app).len(), 3);
+
+    app.receive_update(snapshot(next - 1));
+    assert_eq!(
+        levels(&app),
+        [
+            ("100.00", "1.0"),
+            ("99.00", "2.0"),
+            ("98.00", "3.0"),
+            ("97.00", "4.0")
+        ]
+    );
+    app.receive_update(diff(next + 1, json!([["100.00", "0"]])));
+    assert_eq!(levels(&app)[0], ("99.00", "2.0"));
+}
@@ -0,0 +1,110 @@
+use binance_ws::recording::{Playback, Recorder, Replayer, REPLAY_SPEEDS};
+use serde_json::json;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+// Updates received at 1000, 1100, 1200 and 2000 ms; the directory goes away when dropped
+fn recording() -> (TempDir, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.jsonl");
+    let mut recorder = Recorder::create(&path, 60_000).unwrap();
+    for ts in [1000u64, 1100, 1200, 2000] {
+        recorder
+            .record(&json!({ "symbol": "BTCUSDT", "bids": [], "asks": [], "receivedAt": ts }))
+            .unwrap();
+    }
+    recorder.flush().unwrap();
+    (dir, path)
+}
+
+fn times(messages: &[serde_json::Value]) -> Vec<u64> {
+    messages
+        .iter()
+        .map(|m| m["receivedAt"].as_u64().unwrap())
+        .collect()
+}
+
+fn playback(path: &PathBuf, start: Instant) -> Playback {
+    let mut replayer = Replayer::open(path).unwrap();
+    assert_eq!(times(&replayer.seek(1000).unwrap()), [1000]);
+    Playback::new(replayer, 1000, start)
+}
+
+#[test]
+fn plays_in_recorded_time_scaled_by_speed() {
+    let (_dir, path) = recording();
+    let start = Instant::now();
+    let mut playback = playback(&path, start);
+    assert_eq!(playback.speed(), 1.0);
+
+    assert_eq!(
+        times(&playback.due(start + Duration::from_millis(150)).unwrap()),
+        [1100]
+    );
+    playback.faster(start + Duration::from_millis(150));
+    assert_eq!(playback.speed(), 2.0);
+    // 50ms at 2x reaches 1250
+    assert_eq!(
+        times(&playback.due(start + Duration::from_millis(200)).unwrap()),
+        [1200]
+    );
+    assert_eq!(playback.position(), 1250);
+    assert!(!playback.is_finished());
+
+    assert_eq!(
+        times(&playback.due(start + Duration::from_secs(1)).unwrap()),
+        [2000]
+    );
+    assert!(playback
+        .due(start + Duration::from_secs(2))
+        .unwrap()
+        .is_empty());
+    assert!(playback.is_finished());
+}
+
+#[test]
+fn speed_stays_within_the_presets() {
+    let (_dir, path) = recording();
+    let now = Instant::now();
+    let mut playback = playback(&path, now);
+    for _ in 0..10 {
+        playback.faster(now);
+    }
+    assert_eq!(playback.speed(), *REPLAY_SPEEDS.last().unwrap());
+    for _ in 0..10 {
+        playback.slower(now);
+    }
+    assert_eq!(playback.speed(), REPLAY_SPEEDS[0]);
+}
+
+#[test]
+fn stepping_pauses_and_advances_one_update_at_a_time() {
+    let (_dir, path) = recording();
+    let start = Instant::now();
+    let mut playback = playback(&path, start);
+
+    let first = playback.step(start).unwrap().unwrap();
+    assert_eq!(first["receivedAt"], 1100);
+    assert!(playback.paused);
+    // Time passing while paused plays nothing
+    assert!(playback
+        .due(start + Duration::from_secs(5))
+        .unwrap()
+        .is_empty());
+    assert_eq!(playback.step(start).unwrap().unwrap()["receivedAt"], 1200);
+    assert_eq!(playback.position(), 1200);
+
+    // Resuming continues from the stepped position
+    let resumed = start + Duration::from_secs(10);
+    playback.toggle_pause(resumed);
+    assert!(playback
+        .due(resumed + Duration::from_millis(700))
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        times(&playback.due(resumed + Duration::from_millis(800)).unwrap()),
+        [2000]
+    );
+    assert!(playback.step(resumed).unwrap().is_none());
+}
@@ -0,0 +1,22 @@
+use binance_ws::OrderBook;
+use serde_json::json;
+
+#[test]
+fn near_share_per_side() {
+    let book = OrderBook::from_json(&json!({
+        "bids": [["100", "3"], ["99", "1"], ["98", "4"], ["97", "2"]],
+        "asks": [["101", "1"], ["102", "1"], ["103", "8"]]
+    }));
+    assert_eq!(book.pressure(2), (0.4, 0.2));
+    assert_eq!(book.pressure(0), (0.0, 0.0));
+}
+
+#[test]
+fn shallow_and_empty_sides() {
+    let book = OrderBook::from_json(&json!({
+        "bids": [["100", "3"], ["99", "1"]],
+        "asks": []
+    }));
+    // Fewer levels than near_levels is all near; no liquidity reads 0
+    assert_eq!(book.pressure(5), (1.0, 0.0));
+}
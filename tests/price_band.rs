@@ -0,0 +1,37 @@
+use binance_ws::db::Database;
+use binance_ws::{App, PriceBand};
+use serde_json::json;
+
+fn app() -> App {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.update_orders(&json!({
+        "symbol": "BTCUSDT",
+        "bids": [["99.90", "1.0"], ["99.80", "2.0"], ["90.00", "5.0"]],
+        "asks": [["100.10", "1.0"], ["100.20", "2.0"], ["110.00", "5.0"]]
+    }));
+    app
+}
+
+#[test]
+fn band_bounds_follow_mid_and_overrides() {
+    let band = PriceBand {
+        pct: Some(1.0),
+        overrides: [("ETHUSDT".to_string(), 10.0)].into_iter().collect(),
+    };
+    assert_eq!(band.bounds("BTCUSDT", Some(100.0)), Some((99.0, 101.0)));
+    assert_eq!(band.bounds("ETHUSDT", Some(100.0)), Some((90.0, 110.0)));
+    assert_eq!(band.bounds("BTCUSDT", None), None);
+    assert_eq!(PriceBand::default().bounds("BTCUSDT", Some(100.0)), None);
+}
+
+#[test]
+fn far_levels_are_left_out_of_the_analysis() {
+    let mut app = app();
+    assert_eq!(app.analyze_symbol("BTCUSDT").total_orders, 6);
+
+    app.price_band.pct = Some(0.5);
+    let analysis = app.analyze_symbol("BTCUSDT");
+    assert_eq!(analysis.total_orders, 4);
+    assert!(!analysis.confidence_scores.contains_key("90.00"));
+    assert!(!analysis.confidence_scores.contains_key("110.00"));
+}
@@ -0,0 +1,14 @@
+use binance_ws::linear_slope;
+
+#[test]
+fn fits_the_slope_of_a_noisy_line() {
+    let points = [(0.0, 0.50), (1.0, 0.61), (2.0, 0.69), (3.0, 0.80)];
+    assert!((linear_slope(&points) - 0.098).abs() < 1e-9);
+}
+
+#[test]
+fn is_flat_without_enough_points() {
+    assert_eq!(linear_slope(&[]), 0.0);
+    assert_eq!(linear_slope(&[(1.0, 0.5)]), 0.0);
+    assert_eq!(linear_slope(&[(1.0, 0.5), (1.0, 0.9)]), 0.0);
+}
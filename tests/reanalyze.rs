@@ -0,0 +1,83 @@
+use binance_ws::db::{
+    get_current_timestamp, BookSnapshotRecord, Database, MarketAnalysisRecord, SnapshotPolicy,
+};
+use binance_ws::{App, BackfillReport};
+use std::fs;
+use std::time::Duration;
+
+#[test]
+fn reanalysis_of_a_stored_snapshot_matches_live_analysis() {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.snapshot_policy = Some(SnapshotPolicy::Interval(Duration::ZERO));
+    let mut book: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string("tests/fixtures/btcusdt_book.json").unwrap())
+            .unwrap();
+    book["symbol"] = "BTCUSDT".into();
+    app.update_orders(&book);
+    let live = app.analyze_symbol("BTCUSDT");
+
+    let now = get_current_timestamp();
+    let records = app
+        .reanalyze_snapshots("BTCUSDT", now - 60, now + 60)
+        .unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].total_orders, live.total_orders as i64);
+    assert_eq!(records[0].human_orders, live.likely_human_orders as i64);
+
+    assert!(app
+        .reanalyze_snapshots("BTCUSDT", now + 60, now + 120)
+        .unwrap()
+        .is_empty());
+}
+
+fn snapshot_at(ts: u64) -> BookSnapshotRecord {
+    BookSnapshotRecord {
+        symbol: "BTCUSDT".to_string(),
+        timestamp: ts * 1000,
+        bids: r#"[["50000.00", "1.0"], ["49999.37", "0.13742"]]"#.to_string(),
+        asks: r#"[["50001.00", "2.0"]]"#.to_string(),
+    }
+}
+
+#[test]
+fn backfill_only_fills_intervals_without_analysis() {
+    let app = App::with_database(Database::open_in_memory().unwrap());
+    for ts in [1_000, 1_070, 1_130] {
+        app.db.insert_snapshot(&snapshot_at(ts)).unwrap();
+    }
+    // [1000, 1059] already analyzed; [1180, 1239] has no snapshots
+    app.db
+        .insert_analysis(&MarketAnalysisRecord {
+            symbol: "BTCUSDT".to_string(),
+            timestamp: 1_010,
+            total_orders: 3,
+            human_orders: 1,
+            bot_orders: 2,
+            human_ratio: 1.0 / 3.0,
+        })
+        .unwrap();
+
+    let report = app
+        .backfill_analysis("BTCUSDT", 1_000, 1_239, Duration::from_secs(60))
+        .unwrap();
+    assert_eq!(
+        report,
+        BackfillReport {
+            missing: 3,
+            filled: 2,
+            inserted: 2,
+        }
+    );
+    let stored = app
+        .db
+        .get_analysis_between("BTCUSDT", 1_000, 1_239)
+        .unwrap();
+    let timestamps: Vec<u64> = stored.iter().map(|r| r.timestamp).collect();
+    assert_eq!(timestamps, [1_010, 1_070, 1_130]);
+
+    // Running again finds nothing left to fill
+    let again = app
+        .backfill_analysis("BTCUSDT", 1_000, 1_179, Duration::from_secs(60))
+        .unwrap();
+    assert_eq!(again.missing, 0);
+}
@@ -0,0 +1,43 @@
+use binance_ws::{reconnect_delay, reconnect_limit_reached};
+use std::time::Duration;
+
+const BASE: Duration = Duration::from_secs(5);
+const MAX: Duration = Duration::from_secs(300);
+
+#[test]
+fn first_attempt_uses_base_delay() {
+    assert_eq!(reconnect_delay(BASE, 0, MAX), BASE);
+}
+
+#[test]
+fn delay_grows_by_half_each_attempt() {
+    assert_eq!(reconnect_delay(BASE, 1, MAX), Duration::from_secs_f64(7.5));
+    assert_eq!(
+        reconnect_delay(BASE, 2, MAX),
+        Duration::from_secs_f64(11.25)
+    );
+}
+
+#[test]
+fn large_attempt_counts_clamp_instead_of_panicking() {
+    // 1.5^2000 is infinite in f64, which would panic in Duration::mul_f64
+    for attempts in [20, 100, 2000, u32::MAX] {
+        assert_eq!(reconnect_delay(BASE, attempts, MAX), MAX);
+    }
+}
+
+#[test]
+fn huge_max_still_clamps_to_max() {
+    assert_eq!(
+        reconnect_delay(BASE, u32::MAX, Duration::MAX),
+        Duration::MAX
+    );
+}
+
+#[test]
+fn the_limit_allows_exactly_that_many_attempts() {
+    assert!(!reconnect_limit_reached(2, Some(3)));
+    assert!(reconnect_limit_reached(3, Some(3)));
+    assert!(reconnect_limit_reached(1, Some(0)));
+    assert!(!reconnect_limit_reached(u32::MAX, None));
+}
@@ -0,0 +1,35 @@
+use binance_ws::{db::Database, App, Rotation, MIN_ROTATE_INTERVAL};
+use std::time::{Duration, Instant};
+
+#[test]
+fn rotation_fires_once_per_interval_and_can_pause() {
+    let start = Instant::now();
+    let mut rotation = Rotation::new(Duration::from_secs(10), start);
+    assert_eq!(
+        rotation.remaining(start + Duration::from_secs(3)),
+        Duration::from_secs(7)
+    );
+    assert!(!rotation.due(start + Duration::from_secs(9)));
+    assert!(rotation.due(start + Duration::from_secs(10)));
+    assert!(!rotation.due(start + Duration::from_secs(11)));
+
+    rotation.toggle_pause(start + Duration::from_secs(12));
+    assert!(!rotation.due(start + Duration::from_secs(60)));
+
+    rotation.scale_interval(0.01);
+    assert_eq!(rotation.interval, MIN_ROTATE_INTERVAL);
+}
+
+#[test]
+fn tick_advances_the_current_symbol() {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.set_symbols(&["btcusdt".to_string(), "ethusdt".to_string()]);
+    let start = Instant::now();
+    app.rotation = Some(Rotation::new(Duration::from_secs(5), start));
+    let first = app.current_symbol.clone();
+
+    app.tick_rotation(start + Duration::from_secs(1));
+    assert_eq!(app.current_symbol, first);
+    app.tick_rotation(start + Duration::from_secs(5));
+    assert_ne!(app.current_symbol, first);
+}
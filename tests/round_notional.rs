@@ -0,0 +1,56 @@
+use binance_ws::{LevelSignals, OrderBook, RoundNotional};
+use serde_json::json;
+
+#[test]
+fn flags_round_quote_amounts() {
+    let round = RoundNotional::default();
+    // 0.02 BTC at 50000 is $1000
+    assert!(round.is_round(0.02 * 50000.0));
+    for notional in [100.0, 250.0, 750.0, 1500.0, 5000.0, 20000.0] {
+        assert!(round.is_round(notional), "{} should be round", notional);
+    }
+    // $1000 bought at market, quantity cut to the lot step
+    assert!(round.is_round(0.01995 * 50123.0));
+}
+
+#[test]
+fn ignores_ragged_and_tiny_notionals() {
+    let round = RoundNotional::default();
+    for notional in [0.0, 50.0, 1234.0, 1070.0, 9876.5] {
+        assert!(
+            !round.is_round(notional),
+            "{} should not be round",
+            notional
+        );
+    }
+}
+
+#[test]
+fn round_notional_counts_as_a_human_signal() {
+    let book = OrderBook::from_json(&json!({
+        "bids": [["50000.00", "0.02"], ["49990.37", "0.07313"]],
+        "asks": []
+    }));
+    let round = RoundNotional::default();
+    let with = LevelSignals::compute(&book.bids, 0, None, Some(&round)).unwrap();
+    let without = LevelSignals::compute(&book.bids, 0, None, None).unwrap();
+    assert_eq!(with.round_notional, Some(true));
+    assert_eq!(without.round_notional, None);
+
+    let ragged = LevelSignals::compute(&book.bids, 1, None, Some(&round)).unwrap();
+    assert_eq!(ragged.round_notional, Some(false));
+}
+
+#[test]
+fn a_ragged_notional_never_lowers_the_score() {
+    let book = OrderBook::from_json(&json!({
+        "bids": [["50000.00", "0.02"], ["49990.37", "0.07313"]],
+        "asks": []
+    }));
+    let round = RoundNotional::default();
+    for index in 0..2 {
+        let with = LevelSignals::compute(&book.bids, index, None, Some(&round)).unwrap();
+        let without = LevelSignals::compute(&book.bids, index, None, None).unwrap();
+        assert!(with.human_score() >= without.human_score());
+    }
+}
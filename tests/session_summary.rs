@@ -0,0 +1,38 @@
+use binance_ws::clock::MockClock;
+use binance_ws::db::Database;
+use binance_ws::App;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn aggregates_updates_spread_and_imbalance_per_symbol() {
+    let clock = MockClock::new(1_700_000_000_000);
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.set_clock(Arc::new(clock.clone()));
+
+    app.update_orders(&json!({
+        "symbol": "BTCUSDT",
+        "bids": [["100.00", "3.0"]],
+        "asks": [["101.00", "1.0"]]
+    }));
+    app.update_orders(&json!({
+        "symbol": "BTCUSDT",
+        "bids": [["100.00", "1.0"]],
+        "asks": [["100.50", "1.0"]]
+    }));
+    app.analyze_symbol("BTCUSDT");
+    clock.advance(Duration::from_secs(3725));
+
+    let btc = app.session.get("BTCUSDT").unwrap();
+    assert_eq!(btc.updates, 2);
+    assert_eq!(btc.max_spread, Some(1.0));
+    assert_eq!(btc.peak_imbalance, Some(0.5));
+    assert_eq!(btc.ratio_samples, 1);
+    assert!(app.session.get("ETHUSDT").is_none());
+
+    let summary = app.session_summary();
+    assert!(summary.starts_with("Session summary (01:02:05)"));
+    let row = summary.lines().find(|l| l.starts_with("BTCUSDT")).unwrap();
+    assert!(row.contains(" 2 ") && row.contains("1.00000000") && row.contains("0.500"));
+}
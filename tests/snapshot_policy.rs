@@ -0,0 +1,39 @@
+use binance_ws::db::{LastSnapshot, SnapshotPolicy};
+use std::time::{Duration, Instant};
+
+fn last(at: Instant) -> LastSnapshot {
+    LastSnapshot {
+        best_bid: 100.0,
+        best_ask: 101.0,
+        at,
+    }
+}
+
+#[test]
+fn interval_policy_waits_for_the_interval() {
+    let policy = SnapshotPolicy::Interval(Duration::from_secs(10));
+    let start = Instant::now();
+    assert!(policy.should_store(None, 100.0, 101.0, start));
+    assert!(!policy.should_store(
+        Some(&last(start)),
+        100.0,
+        101.0,
+        start + Duration::from_secs(9)
+    ));
+    assert!(policy.should_store(
+        Some(&last(start)),
+        100.0,
+        101.0,
+        start + Duration::from_secs(10)
+    ));
+}
+
+#[test]
+fn on_change_policy_needs_a_top_of_book_move() {
+    let policy = SnapshotPolicy::OnChange(0.5);
+    let start = Instant::now();
+    let later = start + Duration::from_secs(3600);
+    assert!(!policy.should_store(Some(&last(start)), 100.4, 101.0, later));
+    assert!(policy.should_store(Some(&last(start)), 100.5, 101.0, later));
+    assert!(policy.should_store(Some(&last(start)), 100.0, 100.4, later));
+}
@@ -0,0 +1,94 @@
+use binance_ws::{db::Database, App, DuplicatePolicy, OrderBook, SPOOF_INDICATOR};
+use serde_json::{json, Value};
+use std::time::Instant;
+
+// Snapshot of twelve 1.0 levels a side, bids from 100 down and asks from 100.5 up
+fn book() -> Value {
+    let side = |start: f64, step: f64| -> Vec<[String; 2]> {
+        (0..12)
+            .map(|i| [format!("{:.2}", start + i as f64 * step), "1.0".to_string()])
+            .collect()
+    };
+    json!({ "symbol": "BTCUSDT", "bids": side(100.0, -0.5), "asks": side(100.5, 0.5) })
+}
+
+// Diff event setting the given (price, quantity) bids; a quantity of 0 removes the level
+fn diff(bids: &[(f64, f64)]) -> Value {
+    let bids: Vec<[String; 2]> = bids
+        .iter()
+        .map(|&(price, quantity)| [format!("{:.2}", price), format!("{:.1}", quantity)])
+        .collect();
+    json!({ "symbol": "BTCUSDT", "bids": bids, "asks": [], "eventTime": 1 })
+}
+
+fn apply(order_book: &mut OrderBook, update: &Value) {
+    order_book.apply(update, 0.0, DuplicatePolicy::default(), Instant::now());
+}
+
+#[test]
+fn a_large_level_pulled_before_the_price_arrives_is_a_spoof() {
+    let mut order_book = OrderBook::from_json(&book());
+    apply(&mut order_book, &diff(&[(97.0, 50.0)]));
+    assert_eq!(order_book.new_spoofs(), 0);
+
+    apply(&mut order_book, &diff(&[(97.0, 0.0)]));
+    assert_eq!(order_book.new_spoofs(), 1);
+    assert_eq!(order_book.spoof_candidates(), ["97.00"]);
+
+    // Reposted at the same price, the level carries the indicator
+    apply(&mut order_book, &diff(&[(97.0, 50.0)]));
+    let level = order_book.bids.iter().find(|l| l.price == "97.00").unwrap();
+    assert!(level.human_indicators.iter().any(|i| i == SPOOF_INDICATOR));
+}
+
+#[test]
+fn a_large_level_left_out_of_a_diff_is_still_resting() {
+    let mut order_book = OrderBook::from_json(&book());
+    apply(&mut order_book, &diff(&[(97.0, 50.0)]));
+    // Later diffs only touch other levels
+    apply(&mut order_book, &diff(&[(99.5, 2.0)]));
+    apply(&mut order_book, &diff(&[(100.0, 0.5)]));
+    assert_eq!(order_book.new_spoofs(), 0);
+    assert!(order_book.spoof_candidates().is_empty());
+    assert!(order_book.bids.iter().any(|l| l.price == "97.00"));
+}
+
+#[test]
+fn a_large_level_the_price_trades_through_is_not_a_spoof() {
+    let mut order_book = OrderBook::from_json(&book());
+    apply(&mut order_book, &diff(&[(97.0, 50.0)]));
+    // Every bid down to and including 97 is gone: the level was hit, not pulled
+    let hit: Vec<(f64, f64)> = (0..7).map(|i| (100.0 - i as f64 * 0.5, 0.0)).collect();
+    apply(&mut order_book, &diff(&hit));
+    assert_eq!(order_book.new_spoofs(), 0);
+    assert!(order_book.spoof_candidates().is_empty());
+}
+
+#[test]
+fn levels_at_the_touch_are_not_watched() {
+    let mut order_book = OrderBook::from_json(&book());
+    apply(&mut order_book, &diff(&[(100.0, 50.0)]));
+    apply(&mut order_book, &diff(&[(100.0, 0.0)]));
+    assert!(order_book.spoof_candidates().is_empty());
+}
+
+#[test]
+fn a_snapshot_stops_watching_large_levels() {
+    let mut order_book = OrderBook::from_json(&book());
+    apply(&mut order_book, &diff(&[(97.0, 50.0)]));
+    apply(&mut order_book, &book());
+    assert_eq!(order_book.new_spoofs(), 0);
+    assert!(order_book.spoof_candidates().is_empty());
+}
+
+#[test]
+fn spoofs_are_counted_in_the_database() {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.update_orders(&book());
+    for _ in 0..2 {
+        app.update_orders(&diff(&[(97.0, 50.0)]));
+        app.update_orders(&diff(&[(97.0, 0.0)]));
+    }
+    assert_eq!(app.db.spoof_count("BTCUSDT").unwrap(), 2);
+    assert_eq!(app.db.spoof_count("ETHUSDT").unwrap(), 0);
+}
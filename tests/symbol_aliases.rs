@@ -0,0 +1,42 @@
+use binance_ws::symbols::SymbolAliases;
+use std::collections::HashMap;
+
+fn aliases(overrides: &[(&str, &str)]) -> SymbolAliases {
+    SymbolAliases {
+        quote_suffix: Some("usdt".to_string()),
+        overrides: overrides
+            .iter()
+            .map(|(symbol, alias)| (symbol.to_string(), alias.to_string()))
+            .collect::<HashMap<_, _>>(),
+    }
+}
+
+#[test]
+fn overrides_win_over_the_stripped_quote() {
+    let aliases = aliases(&[("ETHUSDT", "Ether")]);
+    assert_eq!(aliases.display("btcusdt"), "BTC");
+    assert_eq!(aliases.display("ETHUSDT"), "Ether");
+    assert_eq!(aliases.display("ETHBTC"), "ETHBTC");
+    // Nothing left to show once the suffix is stripped
+    assert_eq!(aliases.display("USDT"), "USDT");
+}
+
+#[test]
+fn symbols_sharing_a_display_name_are_reported() {
+    let aliases = aliases(&[("BTCFDUSD", "BTC"), ("ETHBTC", "ETH")]);
+    let symbols = ["btcusdt", "btcfdusd", "ethusdt", "ethbtc", "bnbusdt"];
+    assert_eq!(
+        aliases.collisions(&symbols),
+        [
+            (
+                "BTC".to_string(),
+                vec!["BTCUSDT".to_string(), "BTCFDUSD".to_string()]
+            ),
+            (
+                "ETH".to_string(),
+                vec!["ETHUSDT".to_string(), "ETHBTC".to_string()]
+            ),
+        ]
+    );
+    assert!(aliases.collisions(&["btcusdt", "ethusdt"]).is_empty());
+}
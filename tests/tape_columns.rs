@@ -0,0 +1,57 @@
+use binance_ws::db::Database;
+use binance_ws::{App, TapeColumn, TapeField, Trade, TradeSide};
+use std::time::Instant;
+
+#[test]
+fn parses_field_and_width() {
+    let column: TapeColumn = "Price:12".parse().unwrap();
+    assert_eq!(
+        column,
+        TapeColumn {
+            field: TapeField::Price,
+            width: 12
+        }
+    );
+    let column: TapeColumn = "total".parse().unwrap();
+    assert_eq!(column.field, TapeField::Total);
+}
+
+#[test]
+fn rejects_unknown_fields_and_bad_widths() {
+    assert!("notional:10".parse::<TapeColumn>().is_err());
+    assert!("price:0".parse::<TapeColumn>().is_err());
+    assert!("price:wide".parse::<TapeColumn>().is_err());
+}
+
+#[test]
+fn tape_scroll_stops_at_the_oldest_trade() {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.trade_stream = true;
+    for (i, symbol) in ["BTCUSDT", "ETHUSDT", "BTCUSDT", "BTCUSDT", "BTCUSDT"]
+        .into_iter()
+        .enumerate()
+    {
+        app.record_trade(Trade {
+            timestamp: Instant::now(),
+            symbol: symbol.to_string(),
+            price: 50_000.0 + i as f64,
+            quantity: 1.0,
+            side: TradeSide::Buy,
+            inferred: false,
+            trade_time: None,
+        });
+    }
+    app.current_symbol = "BTCUSDT".to_string();
+
+    // Four BTCUSDT trades in two rows leave two to scroll past
+    for _ in 0..5 {
+        app.scroll_tape(1, 2);
+    }
+    assert_eq!(app.tape_scroll, 2);
+    app.scroll_tape(-1, 2);
+    assert_eq!(app.tape_scroll, 1);
+    for _ in 0..3 {
+        app.scroll_tape(-1, 2);
+    }
+    assert_eq!(app.tape_scroll, 0);
+}
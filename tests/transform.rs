@@ -0,0 +1,66 @@
+use binance_ws::transform_depth_message;
+use serde_json::{json, Value};
+
+fn combined_fixture() -> Value {
+    let contents = std::fs::read_to_string("tests/fixtures/depth_update_combined.json").unwrap();
+    serde_json::from_str(&contents).unwrap()
+}
+
+#[test]
+fn unwraps_combined_stream_payload() {
+    let transformed = transform_depth_message(&combined_fixture()).unwrap();
+    assert_eq!(
+        transformed,
+        json!({
+            "symbol": "BTCUSDT",
+            "bids": [["67012.34000000", "0.51200000"], ["67012.00000000", "0.00000000"]],
+            "asks": [["67012.35000000", "1.20000000"]],
+            "lastUpdateId": 48211011234u64,
+            "eventTime": 1718000000123u64
+        })
+    );
+}
+
+#[test]
+fn accepts_bare_event_and_uppercases_symbol() {
+    let mut data = combined_fixture()["data"].clone();
+    data["s"] = json!("ethusdt");
+    let transformed = transform_depth_message(&data).unwrap();
+    assert_eq!(transformed["symbol"], "ETHUSDT");
+    assert_eq!(transformed["lastUpdateId"], 48211011234u64);
+}
+
+#[test]
+fn empty_sides_are_still_an_update() {
+    let raw =
+        json!({"data": {"e": "depthUpdate", "s": "BTCUSDT", "b": [], "a": [], "u": 1, "E": 2}});
+    let transformed = transform_depth_message(&raw).unwrap();
+    assert_eq!(transformed["bids"], json!([]));
+    assert_eq!(transformed["asks"], json!([]));
+}
+
+#[test]
+fn rejects_other_event_types() {
+    let raw = json!({
+        "stream": "btcusdt@aggTrade",
+        "data": {"e": "aggTrade", "s": "BTCUSDT", "p": "1", "q": "1", "m": true}
+    });
+    assert_eq!(transform_depth_message(&raw), None);
+}
+
+#[test]
+fn rejects_malformed_payloads() {
+    let cases = [
+        json!({}),
+        json!({"data": null}),
+        json!({"data": {"e": "depthUpdate", "b": [], "a": []}}),
+        json!({"data": {"e": "depthUpdate", "s": "", "b": [], "a": []}}),
+        json!({"data": {"e": "depthUpdate", "s": 7, "b": [], "a": []}}),
+        json!({"data": {"e": "depthUpdate", "s": "BTCUSDT", "a": []}}),
+        json!({"data": {"e": "depthUpdate", "s": "BTCUSDT", "b": "oops", "a": []}}),
+        json!({"result": null, "id": 1}),
+    ];
+    for raw in cases {
+        assert_eq!(transform_depth_message(&raw), None, "accepted {}", raw);
+    }
+}
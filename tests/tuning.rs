@@ -0,0 +1,65 @@
+use binance_ws::clock::MockClock;
+use binance_ws::db::Database;
+use binance_ws::App;
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn app() -> App {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.set_symbols(&["btcusdt".to_string()]);
+    let mut book: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string("tests/fixtures/btcusdt_book.json").unwrap())
+            .unwrap();
+    book["symbol"] = "BTCUSDT".into();
+    app.update_orders(&book);
+    app
+}
+
+#[test]
+fn threshold_steps_stay_in_range_and_on_hundredths() {
+    let mut app = app();
+    app.human_threshold = 0.6;
+    for _ in 0..3 {
+        app.adjust_threshold(0.01);
+    }
+    assert_eq!(app.human_threshold, 0.63);
+    app.adjust_threshold(-5.0);
+    assert_eq!(app.human_threshold, 0.0);
+    app.adjust_threshold(5.0);
+    assert_eq!(app.human_threshold, 1.0);
+}
+
+#[test]
+fn counts_follow_the_threshold_without_a_book_update() {
+    let mut app = app();
+    app.toggle_tuning();
+    app.adjust_threshold(-1.0);
+    let lenient = app.analyze_all_symbols()["BTCUSDT"].likely_human_orders;
+    app.adjust_threshold(1.0);
+    let strict = app.analyze_all_symbols()["BTCUSDT"].likely_human_orders;
+    assert!(lenient > strict);
+    assert_eq!(strict, 0);
+}
+
+#[test]
+fn nothing_is_stored_while_tuning() {
+    let clock = MockClock::new(1_700_000_000_000);
+    let mut app = app();
+    app.set_clock(Arc::new(clock.clone()));
+    // The book is loaded once; keep it from going stale as fake time passes
+    app.stale_after = Duration::from_secs(3600);
+    app.toggle_tuning();
+    for _ in 0..=11 {
+        app.analyze_symbol("BTCUSDT");
+        clock.advance(Duration::from_secs(1));
+    }
+    assert!(app.db.get_latest_analysis("BTCUSDT").unwrap().is_none());
+
+    app.toggle_tuning();
+    for _ in 0..=5 {
+        app.analyze_symbol("BTCUSDT");
+        clock.advance(Duration::from_secs(1));
+    }
+    assert!(app.db.get_latest_analysis("BTCUSDT").unwrap().is_some());
+}
@@ -0,0 +1,49 @@
+use binance_ws::{DuplicatePolicy, OrderBook, DEFAULT_BURST_MULTIPLIER};
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+fn update(book: &mut OrderBook, at: Instant) {
+    let levels = json!({ "bids": [["100.00", "1.0"]], "asks": [["101.00", "1.0"]] });
+    book.replace_levels(&levels, 0.0, DuplicatePolicy::default(), at);
+}
+
+#[test]
+fn rate_counts_the_last_second_only() {
+    let start = Instant::now();
+    let mut book = OrderBook::new();
+    for ms in [0, 600, 900, 1500] {
+        update(&mut book, start + Duration::from_millis(ms));
+    }
+    assert_eq!(book.update_rate(start + Duration::from_millis(1500)), 3.0);
+    assert_eq!(book.update_rate(start + Duration::from_secs(5)), 0.0);
+    // Under five seconds of history is too little for a baseline
+    assert_eq!(
+        book.baseline_rate(start + Duration::from_millis(1500)),
+        None
+    );
+}
+
+#[test]
+fn a_spike_over_the_baseline_is_a_burst() {
+    let start = Instant::now();
+    let mut book = OrderBook::new();
+    for second in 0..30 {
+        update(&mut book, start + Duration::from_secs(second));
+    }
+    let calm = start + Duration::from_millis(29_500);
+    assert!(!book.is_update_burst(DEFAULT_BURST_MULTIPLIER, calm));
+
+    for k in 0..20 {
+        update(&mut book, start + Duration::from_millis(30_000 + k * 40));
+    }
+    let now = start + Duration::from_millis(30_800);
+    assert_eq!(book.update_rate(now), 20.0);
+    let baseline = book.baseline_rate(now).unwrap();
+    assert!((baseline - 30.0 / 29.8).abs() < 1e-9);
+    assert!(book.is_update_burst(DEFAULT_BURST_MULTIPLIER, now));
+    // A multiplier above the spike keeps it from counting
+    assert!(!book.is_update_burst(25.0, now));
+
+    // Once it passes, the rate falls back under the burst floor
+    assert!(!book.is_update_burst(DEFAULT_BURST_MULTIPLIER, now + Duration::from_secs(2)));
+}
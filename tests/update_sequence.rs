@@ -0,0 +1,52 @@
+use binance_ws::db::Database;
+use binance_ws::App;
+use serde_json::json;
+
+fn depth(id: u64, bid: &str) -> serde_json::Value {
+    json!({
+        "symbol": "BTCUSDT",
+        "bids": [[bid, "1.0"]],
+        "asks": [["50010.00", "1.0"]],
+        "lastUpdateId": id,
+        "eventTime": 1_700_000_000_000u64 + id
+    })
+}
+
+fn best_bid(app: &App) -> String {
+    app.order_books["BTCUSDT"].bids[0].price.clone()
+}
+
+#[test]
+fn delayed_updates_do_not_overwrite_newer_state() {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.update_orders(&depth(10, "50000.00"));
+    app.update_orders(&depth(12, "50002.00"));
+    // Delayed frame, then a duplicate of the newest
+    app.update_orders(&depth(11, "50001.00"));
+    app.update_orders(&depth(12, "49999.00"));
+
+    assert_eq!(best_bid(&app), "50002.00");
+    assert_eq!(app.last_update_id("BTCUSDT"), Some(12));
+    assert_eq!(app.out_of_order_updates(), 2);
+
+    app.update_orders(&depth(13, "50003.00"));
+    assert_eq!(best_bid(&app), "50003.00");
+}
+
+#[test]
+fn snapshots_restart_the_sequence() {
+    let mut app = App::with_database(Database::open_in_memory().unwrap());
+    app.update_orders(&depth(100, "50000.00"));
+
+    // After a reconnect the REST snapshot wins even with a lower id
+    app.update_orders(&json!({
+        "symbol": "BTCUSDT",
+        "bids": [["49000.00", "1.0"]],
+        "asks": [["49010.00", "1.0"]],
+        "lastUpdateId": 5
+    }));
+    assert_eq!(best_bid(&app), "49000.00");
+    app.update_orders(&depth(6, "49001.00"));
+    assert_eq!(best_bid(&app), "49001.00");
+    assert_eq!(app.out_of_order_updates(), 0);
+}
@@ -0,0 +1,18 @@
+use binance_ws::exchange::UpdateSpeed;
+
+#[test]
+fn builds_stream_names_for_each_speed() {
+    assert_eq!(
+        UpdateSpeed::Ms100.depth_stream("btcusdt"),
+        "btcusdt@depth@100ms"
+    );
+    assert_eq!(UpdateSpeed::Ms1000.depth_stream("btcusdt"), "btcusdt@depth");
+}
+
+#[test]
+fn rejects_speeds_binance_does_not_offer() {
+    assert_eq!("100ms".parse(), Ok(UpdateSpeed::Ms100));
+    assert_eq!("1000MS".parse(), Ok(UpdateSpeed::Ms1000));
+    assert!("250ms".parse::<UpdateSpeed>().is_err());
+    assert!("100".parse::<UpdateSpeed>().is_err());
+}
@@ -0,0 +1,34 @@
+use binance_ws::db::{BookSnapshotRecord, Database};
+
+#[test]
+fn vacuum_shrinks_the_file_after_deletes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("vacuum.db");
+    let db = Database::open(&path).unwrap();
+    for timestamp in 0..500 {
+        db.insert_snapshot(&BookSnapshotRecord {
+            symbol: "BTCUSDT".to_string(),
+            timestamp,
+            bids: "x".repeat(2000),
+            asks: "y".repeat(2000),
+        })
+        .unwrap();
+    }
+    drop(db);
+
+    // New files are created with incremental auto-vacuum
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let mode: i64 = conn
+        .query_row("PRAGMA auto_vacuum", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(mode, 2);
+    conn.execute("DELETE FROM book_snapshots", []).unwrap();
+    drop(conn);
+
+    let db = Database::open(&path).unwrap();
+    let before = db.db_file_size().unwrap();
+    db.vacuum().unwrap();
+    let after = db.db_file_size().unwrap();
+    assert!(after * 10 < before, "{} -> {}", before, after);
+    assert!(std::fs::metadata(&path).unwrap().len() <= after);
+}
@@ -0,0 +1,115 @@
+use binance_ws::db::{AnalysisStore, MarketAnalysisRecord, WriteBuffer};
+use binance_ws::AppError;
+use std::cell::{Cell, RefCell};
+
+// Accepts `accept` inserts, then fails every one after that
+struct FlakyStore {
+    accept: Cell<usize>,
+    inserted: RefCell<Vec<u64>>,
+}
+
+impl FlakyStore {
+    fn new(accept: usize) -> Self {
+        FlakyStore {
+            accept: Cell::new(accept),
+            inserted: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl AnalysisStore for FlakyStore {
+    fn insert_analysis(&self, record: &MarketAnalysisRecord) -> Result<(), AppError> {
+        if self.accept.get() == 0 {
+            return Err(AppError::Io(std::io::Error::other("disk full")));
+        }
+        self.accept.set(self.accept.get() - 1);
+        self.inserted.borrow_mut().push(record.timestamp);
+        Ok(())
+    }
+
+    fn get_latest_analysis(&self, _: &str) -> Result<Option<MarketAnalysisRecord>, AppError> {
+        Ok(None)
+    }
+
+    fn get_analysis_history(&self, _: &str, _: i64) -> Result<Vec<MarketAnalysisRecord>, AppError> {
+        Ok(Vec::new())
+    }
+
+    fn get_analysis_between(
+        &self,
+        _: &str,
+        _: u64,
+        _: u64,
+    ) -> Result<Vec<MarketAnalysisRecord>, AppError> {
+        Ok(Vec::new())
+    }
+
+    fn count_records(&self, _: Option<&str>) -> Result<i64, AppError> {
+        Ok(self.inserted.borrow().len() as i64)
+    }
+
+    fn find_by_ratio(
+        &self,
+        _: &str,
+        _: f64,
+        _: f64,
+        _: u64,
+        _: u64,
+        _: i64,
+    ) -> Result<Vec<MarketAnalysisRecord>, AppError> {
+        Ok(Vec::new())
+    }
+}
+
+fn record(timestamp: u64) -> MarketAnalysisRecord {
+    MarketAnalysisRecord {
+        symbol: "BTCUSDT".to_string(),
+        timestamp,
+        total_orders: 10,
+        human_orders: 4,
+        bot_orders: 6,
+        human_ratio: 0.4,
+    }
+}
+
+#[test]
+fn a_full_buffer_drops_the_oldest_records() {
+    let mut buffer = WriteBuffer::new(3);
+    for timestamp in 1..=5 {
+        buffer.push(record(timestamp));
+    }
+    assert_eq!(buffer.len(), 3);
+    assert_eq!(buffer.dropped(), 2);
+
+    let store = FlakyStore::new(usize::MAX);
+    assert_eq!(buffer.flush(&store).unwrap(), 3);
+    assert_eq!(*store.inserted.borrow(), [3, 4, 5]);
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn flush_stops_at_the_first_failure_and_retries_from_there() {
+    let mut buffer = WriteBuffer::new(10);
+    for timestamp in 1..=4 {
+        buffer.push(record(timestamp));
+    }
+
+    let store = FlakyStore::new(2);
+    assert!(buffer.flush(&store).is_err());
+    assert_eq!(*store.inserted.borrow(), [1, 2]);
+    assert_eq!(buffer.len(), 2);
+
+    store.accept.set(usize::MAX);
+    assert_eq!(buffer.flush(&store).unwrap(), 2);
+    assert_eq!(*store.inserted.borrow(), [1, 2, 3, 4]);
+    assert_eq!(buffer.dropped(), 0);
+}
+
+#[test]
+fn a_zero_capacity_still_holds_the_latest_record() {
+    let mut buffer = WriteBuffer::new(0);
+    buffer.push(record(1));
+    buffer.push(record(2));
+    assert_eq!(buffer.len(), 1);
+    assert_eq!(buffer.dropped(), 1);
+}